@@ -2,10 +2,10 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
-use super::{control::ViewDelta, InputMode};
+use super::{control::ViewDelta, export::ExportOptions, InputMode};
 use crate::direction::Direction;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub enum Action {
     Exit,
     SwitchMode(InputMode),
@@ -14,10 +14,10 @@ pub enum Action {
     Visual(VisualAction),
     Filter(FilterAction),
     Config(ConfigAction),
-    ExportFile(PathBuf),
+    ExportFile(PathBuf, ExportOptions),
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum NormalAction {
     PanVertical {
@@ -31,13 +31,26 @@ pub enum NormalAction {
         target_view: Option<usize>,
     },
     FollowOutput,
+    /// Follow the output, or return to where the view was before.
+    ToggleFollowOutput,
     SwitchActive(Direction),
+    /// Switch to the next view whose selected filter has matches.
+    SwitchActiveWithMatches(Direction),
+    JumpBookmark(Direction),
     SwitchActiveIndex {
         target_view: usize,
     },
+    /// Filter the word at the left edge of the cursor line.
+    QuickFilter,
+    /// Scroll to the bracket that pairs with the one at the left edge.
+    JumpMatchingBracket,
+    JumpToOriginal,
+    ReturnFromOriginal,
+    /// Show the unfiltered lines around the selected line in a popup.
+    Peek,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum VisualAction {
     Move {
@@ -46,13 +59,20 @@ pub enum VisualAction {
         delta: ViewDelta,
     },
     ToggleSelectedLine,
+    /// Flip the bookmark of every visible line.
+    InvertSelection,
     ToggleLine {
         target_view: usize,
         line_number: usize,
     },
+    SelectRange {
+        target_view: usize,
+        anchor: usize,
+        index: usize,
+    },
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum FilterAction {
     Move {
@@ -62,13 +82,15 @@ pub enum FilterAction {
     },
     ToggleSelectedFilter,
     RemoveSelectedFilter,
+    CycleStrategy,
+    CopySelectedFilter,
     ToggleFilter {
         target_view: usize,
         filter_index: usize,
     },
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ConfigAction {
     Move {
@@ -80,7 +102,7 @@ pub enum ConfigAction {
     RemoveSelectedFilter,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum CommandAction {
     Move {
@@ -99,10 +121,12 @@ pub enum CommandAction {
     },
     Backspace,
     Submit,
-    Complete,
+    Complete {
+        direction: Direction,
+    },
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
 #[serde(tag = "type")]
 pub enum CommandJump {
     Word,
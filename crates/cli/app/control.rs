@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::{ops::Range, str::FromStr};
 
-#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 #[serde(tag = "mode")]
 pub enum InputMode {
     Prompt(PromptMode),
@@ -14,17 +15,31 @@ impl InputMode {
     pub fn is_prompt_search(&self) -> bool {
         matches!(self, InputMode::Prompt(PromptMode::Search { .. }))
     }
+
+    pub fn is_prompt_find(&self) -> bool {
+        matches!(self, InputMode::Prompt(PromptMode::Find { .. }))
+    }
 }
 
-#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 #[serde(tag = "prompt")]
 pub enum PromptMode {
     Command,
-    Shell { pipe: bool },
-    Search { escaped: bool, edit: bool },
+    Shell {
+        pipe: bool,
+    },
+    Search {
+        escaped: bool,
+        edit: bool,
+    },
+    /// One-shot search backward from the top of the view.
+    Find {
+        escaped: bool,
+    },
+    Help,
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
 #[serde(tag = "delta")]
 pub enum ViewDelta {
     Number(u16),
@@ -33,3 +48,190 @@ pub enum ViewDelta {
     Boundary,
     Match,
 }
+
+/// How selected lines are drawn relative to the filter match color.
+#[derive(PartialEq, Clone, Copy)]
+pub enum SelectionStyle {
+    /// Selection tints the background; the match color stays on the text.
+    Background,
+    /// Selection reverses the line, moving the match color to the background.
+    Reverse,
+}
+
+/// A piece of information that can be shown in the status bar.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StatusSegment {
+    /// The current input mode badge.
+    Mode,
+    /// The name of the active view.
+    Name,
+    /// The latest message, or the line counts when there is none.
+    Info,
+    /// The selected filter while in filter mode.
+    Filter,
+    /// The top row and left column of the view.
+    Position,
+    /// How far down the view has scrolled.
+    Percent,
+    /// The current time of day in UTC.
+    Clock,
+}
+
+impl StatusSegment {
+    const NAMES: [(&'static str, Self); 7] = [
+        ("mode", Self::Mode),
+        ("name", Self::Name),
+        ("info", Self::Info),
+        ("filter", Self::Filter),
+        ("position", Self::Position),
+        ("percent", Self::Percent),
+        ("clock", Self::Clock),
+    ];
+}
+
+impl FromStr for StatusSegment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|&(_, segment)| segment)
+            .ok_or_else(|| {
+                let names = Self::NAMES.map(|(name, _)| name).join("`, `");
+                format!("unknown segment `{s}`, one of `{names}`")
+            })
+    }
+}
+
+/// Which segments the status bar shows and in what order, written as
+/// comma-separated left segments and right segments around a `|`,
+/// e.g. `mode,name,info,filter|position,percent`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StatusLayout {
+    pub left: Vec<StatusSegment>,
+    pub right: Vec<StatusSegment>,
+}
+
+impl Default for StatusLayout {
+    fn default() -> Self {
+        use StatusSegment::*;
+        Self {
+            left: vec![Mode, Name, Info, Filter],
+            right: vec![Position, Percent],
+        }
+    }
+}
+
+impl FromStr for StatusLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn segments(side: &str) -> Result<Vec<StatusSegment>, String> {
+            side.split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::parse)
+                .collect()
+        }
+
+        let (left, right) = s.split_once('|').unwrap_or((s, ""));
+        Ok(Self {
+            left: segments(left)?,
+            right: segments(right)?,
+        })
+    }
+}
+
+/// Lines of a file to show, written as one-based inclusive line numbers
+/// `X:Y`, where either side may be left out to start at the first line or
+/// end at the last.
+#[derive(PartialEq, Clone, Debug)]
+pub struct LineRange(pub Range<usize>);
+
+impl FromStr for LineRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn line(s: &str, default: usize) -> Result<usize, String> {
+            match s.trim() {
+                "" => Ok(default),
+                s => match s.parse::<usize>() {
+                    Ok(0) => Err(String::from("line numbers start at 1")),
+                    Ok(line) => Ok(line),
+                    Err(err) => Err(format!("invalid line number `{s}`: {err}")),
+                },
+            }
+        }
+
+        let (start, end) = s
+            .split_once(':')
+            .ok_or_else(|| String::from("expected a range `X:Y`"))?;
+        let (start, end) = (line(start, 1)?, line(end, usize::MAX)?);
+        if end < start {
+            return Err(format!("range {start}:{end} ends before it starts"));
+        }
+        Ok(Self(start - 1..end))
+    }
+}
+
+/// The input modes in which the mouse is captured, written as `all`, `none`
+/// or comma-separated modes, e.g. `normal,filter`. In the other modes the
+/// terminal keeps the mouse, so text can be selected and copied natively.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct MouseModes(u8);
+
+impl MouseModes {
+    const NAMES: [(&'static str, u8); 5] = [
+        ("normal", 1 << 0),
+        ("visual", 1 << 1),
+        ("filter", 1 << 2),
+        ("config", 1 << 3),
+        ("prompt", 1 << 4),
+    ];
+
+    pub const ALL: Self = Self(0b11111);
+
+    pub fn captures(self, mode: InputMode) -> bool {
+        let name = match mode {
+            InputMode::Normal => "normal",
+            InputMode::Visual => "visual",
+            InputMode::Filter => "filter",
+            InputMode::Config => "config",
+            InputMode::Prompt(_) => "prompt",
+        };
+        Self::NAMES
+            .iter()
+            .any(|&(n, bit)| n == name && self.0 & bit != 0)
+    }
+}
+
+impl Default for MouseModes {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl FromStr for MouseModes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => return Ok(Self::ALL),
+            "none" => return Ok(Self(0)),
+            _ => {}
+        }
+        s.split(',')
+            .map(|mode| {
+                Self::NAMES
+                    .iter()
+                    .find(|(name, _)| *name == mode.trim())
+                    .map(|&(_, bit)| bit)
+                    .ok_or_else(|| {
+                        let names = Self::NAMES.map(|(name, _)| name).join("`, `");
+                        format!("unknown mode `{mode}`, one of `{names}`, `all` or `none`")
+                    })
+            })
+            .try_fold(Self(0), |modes, bit| Ok(Self(modes.0 | bit?)))
+    }
+}
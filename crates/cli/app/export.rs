@@ -0,0 +1,338 @@
+use crate::components::instance::Instance;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub struct ExportOptions {
+    /// Strip trailing whitespace from every line.
+    pub trim: bool,
+    /// Collapse runs of spaces and tabs into a single space.
+    pub squeeze: bool,
+    /// Export the whole file instead of only the lines visible through the
+    /// active filters.
+    pub all: bool,
+    /// Template for each line, see [LineFormat].
+    pub format: Option<String>,
+    /// Wrap the export in a fenced markdown code block, with this language
+    /// hint after the opening fence.
+    pub markdown: Option<String>,
+}
+
+enum FormatPart {
+    Literal(String),
+    Offset,
+    Line,
+    Text,
+}
+
+/// Template for exported lines, where `{offset}` is replaced with the byte
+/// offset of the start of the line in the file, `{line}` with its line
+/// number, and `{text}` with its contents. `\t` stands for a tab.
+pub struct LineFormat(Vec<FormatPart>);
+
+impl LineFormat {
+    pub const OFFSETS: &'static str = "{offset}\\t{line}\\t{text}";
+
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let template = template.replace("\\t", "\t");
+        let mut parts = Vec::new();
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                return Err(String::from("unclosed `{`"));
+            };
+            if start > 0 {
+                parts.push(FormatPart::Literal(rest[..start].to_owned()));
+            }
+            parts.push(match &rest[start + 1..start + len] {
+                "offset" => FormatPart::Offset,
+                "line" => FormatPart::Line,
+                "text" => FormatPart::Text,
+                field => return Err(format!("unknown field `{{{field}}}`")),
+            });
+            rest = &rest[start + len + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(FormatPart::Literal(rest.to_owned()));
+        }
+        Ok(Self(parts))
+    }
+
+    /// Writes a line, keeping the line terminator at the end.
+    pub fn write(
+        &self,
+        mut output: impl Write,
+        line_number: usize,
+        offset: u64,
+        data: &[u8],
+    ) -> std::io::Result<()> {
+        let text = data.strip_suffix(b"\n").unwrap_or(data);
+        let text = text.strip_suffix(b"\r").unwrap_or(text);
+        for part in &self.0 {
+            match part {
+                FormatPart::Literal(literal) => output.write_all(literal.as_bytes())?,
+                FormatPart::Offset => write!(output, "{offset}")?,
+                FormatPart::Line => write!(output, "{}", line_number + 1)?,
+                FormatPart::Text => output.write_all(text)?,
+            }
+        }
+        output.write_all(&data[text.len()..])
+    }
+}
+
+/// Cleans up whitespace line by line as the export is streamed through it,
+/// so that only a single line is ever buffered.
+pub struct TrimWriter<W: Write> {
+    inner: W,
+    squeeze: bool,
+    line: Vec<u8>,
+    scratch: Vec<u8>,
+}
+
+impl<W: Write> TrimWriter<W> {
+    pub fn new(inner: W, options: &ExportOptions) -> Self {
+        Self {
+            inner,
+            squeeze: options.squeeze,
+            line: Vec::new(),
+            scratch: Vec::new(),
+        }
+    }
+
+    fn write_line(&mut self, newline: bool) -> std::io::Result<()> {
+        let crlf = newline && self.line.last() == Some(&b'\r');
+        let mut end = self.line.len();
+        while end > 0 && matches!(self.line[end - 1], b' ' | b'\t' | b'\r') {
+            end -= 1;
+        }
+        let line = &self.line[..end];
+
+        if self.squeeze {
+            self.scratch.clear();
+            let mut prev_blank = false;
+            for &b in line {
+                let blank = matches!(b, b' ' | b'\t');
+                if !blank {
+                    self.scratch.push(b);
+                } else if !prev_blank {
+                    self.scratch.push(b' ');
+                }
+                prev_blank = blank;
+            }
+            self.inner.write_all(&self.scratch)?;
+        } else {
+            self.inner.write_all(line)?;
+        }
+
+        if crlf {
+            self.inner.write_all(b"\r\n")?;
+        } else if newline {
+            self.inner.write_all(b"\n")?;
+        }
+        self.line.clear();
+        Ok(())
+    }
+
+    /// Writes out the last line if it was not terminated by a newline.
+    pub fn finish(mut self) -> std::io::Result<W> {
+        if !self.line.is_empty() {
+            self.write_line(false)?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for TrimWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for piece in buf.split_inclusive(|&b| b == b'\n') {
+            match piece.split_last() {
+                Some((b'\n', line)) => {
+                    self.line.extend_from_slice(line);
+                    self.write_line(true)?;
+                }
+                _ => self.line.extend_from_slice(piece),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Measures the longest run of backticks written through it, so that a
+/// markdown fence can be chosen that does not occur in the content.
+#[derive(Default)]
+struct FenceScan {
+    run: usize,
+    longest: usize,
+    last: Option<u8>,
+}
+
+impl FenceScan {
+    /// A fence longer than any run of backticks seen, and at least three long.
+    fn fence(&self) -> String {
+        "`".repeat(self.longest.max(2) + 1)
+    }
+
+    /// Whether the content needs a newline before the closing fence.
+    fn needs_newline(&self) -> bool {
+        self.last.is_some_and(|b| b != b'\n')
+    }
+}
+
+impl Write for FenceScan {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for &b in buf {
+            if b == b'`' {
+                self.run += 1;
+                self.longest = self.longest.max(self.run);
+            } else {
+                self.run = 0;
+            }
+        }
+        self.last = buf.last().copied().or(self.last);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes `data` inside a fenced markdown code block with the `language`
+/// hint, with a fence that does not occur in `data`.
+fn write_fence(output: &mut impl Write, data: &[u8], language: &str) -> std::io::Result<()> {
+    let mut scan = FenceScan::default();
+    scan.write_all(data)?;
+    let fence = scan.fence();
+
+    writeln!(output, "{fence}{language}")?;
+    output.write_all(data)?;
+    if scan.needs_newline() {
+        writeln!(output)?;
+    }
+    writeln!(output, "{fence}")
+}
+
+/// Wraps `text` in a fenced markdown code block with the `language` hint.
+pub fn fence_text(text: &str, language: &str) -> String {
+    let mut output = Vec::new();
+    // Writing to a vector does not fail
+    let _ = write_fence(&mut output, text.as_bytes(), language);
+    String::from_utf8(output).unwrap_or_default()
+}
+
+/// Writes the exported lines of `instance` to `output` like [write_export],
+/// inside a fenced markdown code block with the `language` hint.
+///
+/// The lines are held in memory until they are written, since the fence
+/// depends on all of them.
+pub fn write_fenced(
+    instance: &mut Instance,
+    output: &mut impl Write,
+    format: Option<&LineFormat>,
+    whole_file: bool,
+    language: &str,
+) -> bvr_core::Result<()> {
+    let mut data = Vec::new();
+    write_export(instance, &mut data, format, whole_file)?;
+    write_fence(output, &data, language)?;
+    Ok(())
+}
+
+/// Writes the exported lines of `instance` to `output`, laid out by `format`
+/// if one is given.
+pub fn write_export(
+    instance: &mut Instance,
+    output: &mut impl Write,
+    format: Option<&LineFormat>,
+    whole_file: bool,
+) -> bvr_core::Result<()> {
+    match format {
+        Some(format) => instance.write_lines(whole_file, |line_number, offset, data| {
+            format.write(&mut *output, line_number, offset, data)
+        }),
+        None => instance.write_bytes(output, whole_file),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn format(template: &str, lines: &[&[u8]]) -> String {
+        let format = LineFormat::parse(template).unwrap();
+        let mut output = Vec::new();
+        let mut offset = 0;
+        for (line_number, data) in lines.iter().enumerate() {
+            format
+                .write(&mut output, line_number, offset, data)
+                .unwrap();
+            offset += data.len() as u64;
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn line_format_round_trips() {
+        let lines: &[&[u8]] = &[b"alpha\n", b"be\tta\r\n", b"gamma"];
+
+        // The text alone is the input as it was
+        assert_eq!(format("{text}", lines), "alpha\nbe\tta\r\ngamma");
+        assert_eq!(
+            format(LineFormat::OFFSETS, lines),
+            "0\t1\talpha\n6\t2\tbe\tta\r\n13\t3\tgamma"
+        );
+        assert_eq!(
+            format("{line}: {text} ({offset})", lines),
+            "1: alpha (0)\n2: be\tta (6)\r\n3: gamma (13)"
+        );
+        // Every field is parsed back out of the lines written with them
+        for line in format(LineFormat::OFFSETS, lines).lines() {
+            let mut fields = line.splitn(3, '\t');
+            let offset = fields.next().unwrap().parse::<usize>().unwrap();
+            let line_number = fields.next().unwrap().parse::<usize>().unwrap();
+            let text = fields.next().unwrap();
+            assert!(lines[line_number - 1].starts_with(text.as_bytes()));
+            let start = lines[..line_number - 1]
+                .iter()
+                .map(|l| l.len())
+                .sum::<usize>();
+            assert_eq!(offset, start);
+        }
+    }
+
+    #[test]
+    fn line_format_rejects_bad_templates() {
+        assert!(LineFormat::parse("{line").is_err());
+        assert!(LineFormat::parse("{column}").is_err());
+        assert!(LineFormat::parse("no fields").is_ok());
+    }
+
+    #[test]
+    fn fence_is_longer_than_any_backticks_in_the_text() {
+        assert_eq!(
+            fence_text("let a = 1;\n", "rust"),
+            "```rust\nlet a = 1;\n```\n"
+        );
+        assert_eq!(
+            fence_text("`a` and ``b``\n", ""),
+            "```\n`a` and ``b``\n```\n"
+        );
+        assert_eq!(
+            fence_text("```sh\nls\n```\n", "md"),
+            "````md\n```sh\nls\n```\n````\n"
+        );
+        // Runs are counted apart, however many backticks there are in total
+        assert_eq!(fence_text("``` `````\n", ""), "``````\n``` `````\n``````\n");
+    }
+
+    #[test]
+    fn fence_closes_on_a_line_of_its_own() {
+        assert_eq!(fence_text("a\nb", ""), "```\na\nb\n```\n");
+        assert_eq!(fence_text("", ""), "```\n```\n");
+    }
+}
@@ -6,7 +6,10 @@ use super::{
     InputMode, PromptMode,
 };
 use crate::direction::Direction;
-use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::time::{Duration, Instant};
+use BindMode as M;
+use KeyCode::*;
 
 pub enum Keybinding {
     // The keybindings are hardcoded into the program.
@@ -14,293 +17,1367 @@ pub enum Keybinding {
     // TODO: custom keybinding feature gate
 }
 
-impl Keybinding {
-    pub fn map_key(&self, input_mode: InputMode, event: &mut Event) -> Option<Action> {
-        match self {
-            Self::Hardcoded => Self::native_keys(input_mode, event),
+/// Speeds up line-by-line movement while a movement key is held down.
+///
+/// Terminals that report event types, which the app asks for on entering the
+/// terminal, send repeats for a held key and a release when it is let go.
+/// Others report a held key as a stream of presses, so a key that fires again
+/// shortly after itself is treated as held as well. The delta grows the
+/// longer the key is held.
+pub struct KeyAccelerator {
+    last: Option<(KeyCode, Instant)>,
+    streak: u16,
+    max_delta: u16,
+}
+
+impl KeyAccelerator {
+    /// Longest gap between two events of a held key, which is above the
+    /// repeat interval but below the initial repeat delay of most terminals.
+    const REPEAT_WINDOW: Duration = Duration::from_millis(100);
+    /// Number of repeats before the delta grows by one line.
+    const REPEATS_PER_STEP: u16 = 5;
+    pub const DEFAULT_MAX_DELTA: u16 = 8;
+
+    pub fn new(max_delta: u16) -> Self {
+        Self {
+            last: None,
+            streak: 0,
+            max_delta: max_delta.max(1),
         }
     }
 
-    fn native_keys(input_mode: InputMode, event: &mut Event) -> Option<Action> {
-        if let Event::Key(key) = event {
-            if key.kind != KeyEventKind::Press {
-                return None;
-            }
+    /// Scales the single-line movement of `action` by how long the key of
+    /// `event` has been held.
+    pub fn accelerate(&mut self, event: &Event, action: Option<Action>) -> Option<Action> {
+        self.accelerate_at(event, action, Instant::now())
+    }
+
+    fn accelerate_at(
+        &mut self,
+        event: &Event,
+        action: Option<Action>,
+        now: Instant,
+    ) -> Option<Action> {
+        let Event::Key(key) = event else {
+            return action;
+        };
+        if key.kind == KeyEventKind::Release {
+            self.last = None;
+            self.streak = 0;
+            return action;
         }
 
-        Self::mode_dependent_bind(input_mode, event)
-            .or_else(|| Self::mode_independent_bind(input_mode, event))
+        let held = key.kind == KeyEventKind::Repeat
+            || self.last.is_some_and(|(code, at)| {
+                code == key.code && now.duration_since(at) < Self::REPEAT_WINDOW
+            });
+        self.streak = if held {
+            self.streak.saturating_add(1)
+        } else {
+            0
+        };
+        self.last = Some((key.code, now));
+
+        let scale = (1 + self.streak / Self::REPEATS_PER_STEP).min(self.max_delta);
+        let scaled = |delta| match delta {
+            ViewDelta::Number(n) => ViewDelta::Number(n.saturating_mul(scale)),
+            delta => delta,
+        };
+        action.map(|action| match action {
+            Action::Normal(NormalAction::PanVertical {
+                direction,
+                delta,
+                target_view,
+            }) => Action::Normal(NormalAction::PanVertical {
+                direction,
+                delta: scaled(delta),
+                target_view,
+            }),
+            Action::Visual(VisualAction::Move {
+                direction,
+                select,
+                delta,
+            }) => Action::Visual(VisualAction::Move {
+                direction,
+                select,
+                delta: scaled(delta),
+            }),
+            action => action,
+        })
     }
+}
+
+/// Which input modes a [KeyBind] applies in.
+#[derive(Clone, Copy, PartialEq)]
+pub enum BindMode {
+    Normal,
+    Visual,
+    Filter,
+    Config,
+    Prompt,
+    /// Every mode, if the mode has no binding of its own for the key.
+    Any,
+}
 
-    fn mode_dependent_bind(input_mode: InputMode, event: &mut Event) -> Option<Action> {
+impl BindMode {
+    fn of(input_mode: InputMode) -> Self {
         match input_mode {
-            InputMode::Normal => match event {
-                Event::Key(key) => match key.code {
-                    KeyCode::Up | KeyCode::Down => {
-                        Some(Action::Normal(NormalAction::PanVertical {
-                            direction: Direction::back_if(key.code == KeyCode::Up),
-                            delta: if key.modifiers.contains(KeyModifiers::SHIFT) {
-                                ViewDelta::HalfPage
-                            } else {
-                                ViewDelta::Number(1)
-                            },
-                            target_view: None,
-                        }))
-                    }
-                    KeyCode::Left | KeyCode::Right => {
-                        Some(Action::Normal(NormalAction::PanHorizontal {
-                            direction: Direction::back_if(key.code == KeyCode::Left),
-                            delta: if key.modifiers.contains(KeyModifiers::SHIFT) {
-                                ViewDelta::HalfPage
-                            } else {
-                                ViewDelta::Number(1)
-                            },
-                            target_view: None,
-                        }))
-                    }
-                    KeyCode::Home | KeyCode::Char('g') => {
-                        Some(Action::Normal(NormalAction::PanVertical {
-                            direction: Direction::Back,
-                            delta: ViewDelta::Boundary,
-                            target_view: None,
-                        }))
-                    }
-                    KeyCode::End | KeyCode::Char('G') => {
-                        Some(Action::Normal(NormalAction::FollowOutput))
-                    }
-                    KeyCode::PageUp | KeyCode::PageDown | KeyCode::Char(' ') => {
-                        Some(Action::Normal(NormalAction::PanVertical {
-                            direction: Direction::back_if(key.code == KeyCode::PageUp),
-                            delta: ViewDelta::Page,
-                            target_view: None,
-                        }))
-                    }
-                    KeyCode::Char(c @ ('u' | 'd')) => {
-                        Some(Action::Normal(NormalAction::PanVertical {
-                            direction: Direction::back_if(c == 'u'),
-                            delta: ViewDelta::HalfPage,
-                            target_view: None,
-                        }))
-                    }
-                    KeyCode::Char(c @ ('p' | 'n')) => {
-                        Some(Action::Normal(NormalAction::PanVertical {
-                            direction: Direction::back_if(c == 'p'),
-                            delta: ViewDelta::Match,
-                            target_view: None,
-                        }))
-                    }
-                    _ => None,
-                },
-                _ => None,
-            },
-            InputMode::Filter => match event {
-                Event::Key(key) => match key.code {
-                    KeyCode::Up | KeyCode::Down => Some(Action::Filter(FilterAction::Move {
-                        direction: Direction::back_if(key.code == KeyCode::Up),
-                        select: key.modifiers.contains(KeyModifiers::SHIFT),
-                        delta: ViewDelta::Number(1),
-                    })),
-                    KeyCode::Home | KeyCode::End => Some(Action::Filter(FilterAction::Move {
-                        direction: Direction::back_if(key.code == KeyCode::Home),
-                        select: key.modifiers.contains(KeyModifiers::SHIFT),
-                        delta: ViewDelta::Boundary,
-                    })),
-                    KeyCode::PageUp | KeyCode::PageDown => {
-                        Some(Action::Filter(FilterAction::Move {
-                            direction: Direction::back_if(key.code == KeyCode::PageUp),
-                            select: key.modifiers.contains(KeyModifiers::SHIFT),
-                            delta: ViewDelta::Page,
-                        }))
-                    }
-                    KeyCode::Char('/') => {
-                        Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Search {
-                            escaped: false,
-                            edit: true,
-                        })))
-                    }
-                    KeyCode::Char(c @ ('u' | 'd')) => Some(Action::Filter(FilterAction::Move {
-                        direction: Direction::back_if(c == 'u'),
-                        select: key.modifiers.contains(KeyModifiers::SHIFT),
-                        delta: ViewDelta::HalfPage,
-                    })),
-                    KeyCode::Char(' ') | KeyCode::Enter => {
-                        Some(Action::Filter(FilterAction::ToggleSelectedFilter))
-                    }
-                    KeyCode::Backspace => Some(Action::Filter(FilterAction::RemoveSelectedFilter)),
-                    _ => None,
+            InputMode::Normal => Self::Normal,
+            InputMode::Visual => Self::Visual,
+            InputMode::Filter => Self::Filter,
+            InputMode::Config => Self::Config,
+            InputMode::Prompt(_) => Self::Prompt,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Visual => "visual",
+            Self::Filter => "filter",
+            Self::Config => "config",
+            Self::Prompt => "prompt",
+            Self::Any => "any",
+        }
+    }
+}
+
+/// The keys that trigger a [KeyBind].
+pub enum BindKeys {
+    Codes(&'static [KeyCode]),
+    /// Any character, for typing into the prompt.
+    AnyChar,
+}
+
+/// One entry of a keymap: in `mode`, pressing one of `keys` while holding
+/// at least `modifiers` performs the action built by `action`.
+pub struct KeyBind {
+    pub mode: BindMode,
+    pub keys: BindKeys,
+    pub modifiers: KeyModifiers,
+    /// Builds the action from the pressed key, which may depend on the other
+    /// modifiers held. Returning `None` leaves the key unbound in this mode.
+    action: fn(&KeyEvent, InputMode) -> Option<Action>,
+    /// The keys as shown in the help, e.g. `Ctrl + u d`.
+    pub label: &'static str,
+    pub description: &'static str,
+}
+
+impl KeyBind {
+    fn triggered_by(&self, key: &KeyEvent) -> bool {
+        let code_matches = match self.keys {
+            BindKeys::Codes(codes) => codes.contains(&key.code),
+            BindKeys::AnyChar => matches!(key.code, KeyCode::Char(_)),
+        };
+        code_matches && key.modifiers.contains(self.modifiers)
+    }
+}
+
+fn shift(key: &KeyEvent) -> bool {
+    key.modifiers.contains(KeyModifiers::SHIFT)
+}
+
+fn back_if(key: &KeyEvent, code: KeyCode) -> Direction {
+    Direction::back_if(key.code == code)
+}
+
+const fn bind(
+    mode: BindMode,
+    keys: &'static [KeyCode],
+    modifiers: KeyModifiers,
+    action: fn(&KeyEvent, InputMode) -> Option<Action>,
+    label: &'static str,
+    description: &'static str,
+) -> KeyBind {
+    KeyBind {
+        mode,
+        keys: BindKeys::Codes(keys),
+        modifiers,
+        action,
+        label,
+        description,
+    }
+}
+
+const NONE: KeyModifiers = KeyModifiers::NONE;
+const CONTROL: KeyModifiers = KeyModifiers::CONTROL;
+const ALT: KeyModifiers = KeyModifiers::ALT;
+
+/// The default keymap. The first binding of the current mode that is
+/// triggered by a key decides its action, and bindings of [BindMode::Any]
+/// only apply to keys that the mode leaves unbound.
+pub const DEFAULT_BINDINGS: &[KeyBind] = &[
+    // Normal mode
+    bind(
+        M::Normal,
+        &[Up, Down],
+        NONE,
+        |key, _| {
+            Some(Action::Normal(NormalAction::PanVertical {
+                direction: back_if(key, Up),
+                delta: if shift(key) {
+                    ViewDelta::HalfPage
+                } else {
+                    ViewDelta::Number(1)
                 },
-                _ => None,
-            },
-            InputMode::Config => match event {
-                Event::Key(key) => match key.code {
-                    KeyCode::Up | KeyCode::Down => Some(Action::Config(ConfigAction::Move {
-                        direction: Direction::back_if(key.code == KeyCode::Up),
-                        select: key.modifiers.contains(KeyModifiers::SHIFT),
-                        delta: ViewDelta::Number(1),
-                    })),
-                    KeyCode::Home | KeyCode::End => Some(Action::Config(ConfigAction::Move {
-                        direction: Direction::back_if(key.code == KeyCode::Home),
-                        select: key.modifiers.contains(KeyModifiers::SHIFT),
-                        delta: ViewDelta::Boundary,
-                    })),
-                    KeyCode::PageUp | KeyCode::PageDown => {
-                        Some(Action::Config(ConfigAction::Move {
-                            direction: Direction::back_if(key.code == KeyCode::PageUp),
-                            select: key.modifiers.contains(KeyModifiers::SHIFT),
-                            delta: ViewDelta::Page,
-                        }))
-                    }
-                    KeyCode::Char(c @ ('u' | 'd')) => Some(Action::Config(ConfigAction::Move {
-                        direction: Direction::back_if(c == 'u'),
-                        select: key.modifiers.contains(KeyModifiers::SHIFT),
-                        delta: ViewDelta::HalfPage,
-                    })),
-                    KeyCode::Enter => Some(Action::Config(ConfigAction::LoadSelectedFilter)),
-                    KeyCode::Backspace => Some(Action::Config(ConfigAction::RemoveSelectedFilter)),
-                    _ => None,
+                target_view: None,
+            }))
+        },
+        "Up Down",
+        "Scroll the view, by a half-page with Shift.",
+    ),
+    bind(
+        M::Normal,
+        &[Left, Right],
+        NONE,
+        |key, _| {
+            Some(Action::Normal(NormalAction::PanHorizontal {
+                direction: back_if(key, Left),
+                delta: if shift(key) {
+                    ViewDelta::HalfPage
+                } else {
+                    ViewDelta::Number(1)
                 },
-                _ => None,
-            },
-            InputMode::Visual => match event {
-                Event::Key(key) => match key.code {
-                    KeyCode::Up | KeyCode::Down => Some(Action::Visual(VisualAction::Move {
-                        direction: Direction::back_if(key.code == KeyCode::Up),
-                        select: key.modifiers.contains(KeyModifiers::SHIFT),
-                        delta: if key
-                            .modifiers
-                            .intersects(KeyModifiers::ALT | KeyModifiers::CONTROL)
-                        {
-                            ViewDelta::HalfPage
-                        } else {
-                            ViewDelta::Number(1)
-                        },
-                    })),
-                    KeyCode::Char(c @ ('n' | 'N')) => Some(Action::Visual(VisualAction::Move {
-                        direction: Direction::back_if(c.to_ascii_lowercase() == 'N'),
-                        delta: ViewDelta::Match,
-                        select: key.modifiers.contains(KeyModifiers::SHIFT),
-                    })),
-                    KeyCode::Home | KeyCode::End => Some(Action::Visual(VisualAction::Move {
-                        direction: Direction::back_if(key.code == KeyCode::Home),
-                        select: key.modifiers.contains(KeyModifiers::SHIFT),
-                        delta: ViewDelta::Boundary,
-                    })),
-                    KeyCode::PageUp | KeyCode::PageDown => {
-                        Some(Action::Visual(VisualAction::Move {
-                            direction: Direction::back_if(key.code == KeyCode::PageUp),
-                            select: key.modifiers.contains(KeyModifiers::SHIFT),
-                            delta: ViewDelta::Page,
-                        }))
-                    }
-                    KeyCode::Char(' ') | KeyCode::Enter => {
-                        Some(Action::Visual(VisualAction::ToggleSelectedLine))
-                    }
-                    _ => None,
+                target_view: None,
+            }))
+        },
+        "Left Right",
+        "Pan the view horizontally, by a half-page with Shift.",
+    ),
+    bind(
+        M::Normal,
+        &[Home, Char('g')],
+        NONE,
+        |_, _| {
+            Some(Action::Normal(NormalAction::PanVertical {
+                direction: Direction::Back,
+                delta: ViewDelta::Boundary,
+                target_view: None,
+            }))
+        },
+        "Home g",
+        "Jump to the start of the view.",
+    ),
+    bind(
+        M::Normal,
+        &[End, Char('G')],
+        NONE,
+        |_, _| Some(Action::Normal(NormalAction::FollowOutput)),
+        "End G",
+        "Jump to the end of the view and follow new output.",
+    ),
+    bind(
+        M::Normal,
+        &[Char('F')],
+        NONE,
+        |_, _| Some(Action::Normal(NormalAction::ToggleFollowOutput)),
+        "F",
+        "Follow new output, or go back to where the view was before.",
+    ),
+    bind(
+        M::Normal,
+        &[PageUp, PageDown, Char(' ')],
+        NONE,
+        |key, _| {
+            Some(Action::Normal(NormalAction::PanVertical {
+                direction: back_if(key, PageUp),
+                delta: ViewDelta::Page,
+                target_view: None,
+            }))
+        },
+        "PageUp PageDown Space",
+        "Scroll the view by a page.",
+    ),
+    bind(
+        M::Normal,
+        &[Char('b'), Char('f')],
+        CONTROL,
+        |key, _| {
+            Some(Action::Normal(NormalAction::PanVertical {
+                direction: back_if(key, Char('b')),
+                delta: ViewDelta::Page,
+                target_view: None,
+            }))
+        },
+        "Ctrl + b f",
+        "Scroll the view by a page.",
+    ),
+    bind(
+        M::Normal,
+        &[Char('u'), Char('d')],
+        NONE,
+        |key, _| {
+            Some(Action::Normal(NormalAction::PanVertical {
+                direction: back_if(key, Char('u')),
+                delta: ViewDelta::HalfPage,
+                target_view: None,
+            }))
+        },
+        "u d",
+        "Scroll the view by a half-page.",
+    ),
+    bind(
+        M::Normal,
+        &[Char('p'), Char('n')],
+        NONE,
+        |key, _| {
+            Some(Action::Normal(NormalAction::PanVertical {
+                direction: back_if(key, Char('p')),
+                delta: ViewDelta::Match,
+                target_view: None,
+            }))
+        },
+        "p n",
+        "Jump to the previous/next match.",
+    ),
+    bind(
+        M::Normal,
+        &[Char('P'), Char('N')],
+        NONE,
+        |key, _| {
+            Some(Action::Normal(NormalAction::SwitchActiveWithMatches(
+                back_if(key, Char('P')),
+            )))
+        },
+        "P N",
+        "Switch to the previous/next file with matches for the selected filter.",
+    ),
+    bind(
+        M::Normal,
+        &[Char('*')],
+        NONE,
+        |_, _| Some(Action::Normal(NormalAction::QuickFilter)),
+        "*",
+        "Filter the word at the left edge of the cursor line (or the selected line).",
+    ),
+    bind(
+        M::Normal,
+        &[Char('%')],
+        NONE,
+        |_, _| Some(Action::Normal(NormalAction::JumpMatchingBracket)),
+        "%",
+        "Scroll to the bracket pairing with the one at the left edge of the cursor line.",
+    ),
+    bind(
+        M::Normal,
+        &[Char('['), Char(']')],
+        NONE,
+        |key, _| {
+            Some(Action::Normal(NormalAction::JumpBookmark(back_if(
+                key,
+                Char('['),
+            ))))
+        },
+        "[ ]",
+        "Jump to the previous/next bookmark.",
+    ),
+    bind(
+        M::Normal,
+        &[Char('o')],
+        NONE,
+        |_, _| Some(Action::Normal(NormalAction::JumpToOriginal)),
+        "o",
+        "Show the current line in the unfiltered file.",
+    ),
+    bind(
+        M::Normal,
+        &[Char('O')],
+        NONE,
+        |_, _| Some(Action::Normal(NormalAction::ReturnFromOriginal)),
+        "O",
+        "Return to the filtered view.",
+    ),
+    bind(
+        M::Normal,
+        &[Char('K')],
+        NONE,
+        |_, _| Some(Action::Normal(NormalAction::Peek)),
+        "K",
+        "Peek at the unfiltered lines around the current line (Esc closes).",
+    ),
+    // Filter mode
+    bind(
+        M::Filter,
+        &[Up, Down],
+        NONE,
+        |key, _| {
+            Some(Action::Filter(FilterAction::Move {
+                direction: back_if(key, Up),
+                select: shift(key),
+                delta: ViewDelta::Number(1),
+            }))
+        },
+        "Up Down",
+        "Change which filter is selected, extending with Shift.",
+    ),
+    bind(
+        M::Filter,
+        &[Home, End],
+        NONE,
+        |key, _| {
+            Some(Action::Filter(FilterAction::Move {
+                direction: back_if(key, Home),
+                select: shift(key),
+                delta: ViewDelta::Boundary,
+            }))
+        },
+        "Home End",
+        "Select the first/last filter.",
+    ),
+    bind(
+        M::Filter,
+        &[PageUp, PageDown],
+        NONE,
+        |key, _| {
+            Some(Action::Filter(FilterAction::Move {
+                direction: back_if(key, PageUp),
+                select: shift(key),
+                delta: ViewDelta::Page,
+            }))
+        },
+        "PageUp PageDown",
+        "Move the filter selection by a page.",
+    ),
+    bind(
+        M::Filter,
+        &[Char('/')],
+        NONE,
+        |_, _| {
+            Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Search {
+                escaped: false,
+                edit: true,
+            })))
+        },
+        "/",
+        "Edit the selected filter.",
+    ),
+    bind(
+        M::Filter,
+        &[Char('u'), Char('d')],
+        NONE,
+        |key, _| {
+            Some(Action::Filter(FilterAction::Move {
+                direction: back_if(key, Char('u')),
+                select: shift(key),
+                delta: ViewDelta::HalfPage,
+            }))
+        },
+        "u d",
+        "Move the filter selection by a half-page.",
+    ),
+    bind(
+        M::Filter,
+        &[Char(' '), Enter],
+        NONE,
+        |_, _| Some(Action::Filter(FilterAction::ToggleSelectedFilter)),
+        "Space Enter",
+        "Toggle the selected filter.",
+    ),
+    bind(
+        M::Filter,
+        &[Backspace],
+        NONE,
+        |_, _| Some(Action::Filter(FilterAction::RemoveSelectedFilter)),
+        "Backspace",
+        "Remove the selected filter.",
+    ),
+    bind(
+        M::Filter,
+        &[Char('&')],
+        NONE,
+        |_, _| Some(Action::Filter(FilterAction::CycleStrategy)),
+        "&",
+        "Switch between union and intersection of filters.",
+    ),
+    bind(
+        M::Filter,
+        &[Char('y')],
+        NONE,
+        |_, _| Some(Action::Filter(FilterAction::CopySelectedFilter)),
+        "y",
+        "Copy the selected filter.",
+    ),
+    // Config mode
+    bind(
+        M::Config,
+        &[Up, Down],
+        NONE,
+        |key, _| {
+            Some(Action::Config(ConfigAction::Move {
+                direction: back_if(key, Up),
+                select: shift(key),
+                delta: ViewDelta::Number(1),
+            }))
+        },
+        "Up Down",
+        "Change which saved filter set is selected.",
+    ),
+    bind(
+        M::Config,
+        &[Home, End],
+        NONE,
+        |key, _| {
+            Some(Action::Config(ConfigAction::Move {
+                direction: back_if(key, Home),
+                select: shift(key),
+                delta: ViewDelta::Boundary,
+            }))
+        },
+        "Home End",
+        "Select the first/last saved filter set.",
+    ),
+    bind(
+        M::Config,
+        &[PageUp, PageDown],
+        NONE,
+        |key, _| {
+            Some(Action::Config(ConfigAction::Move {
+                direction: back_if(key, PageUp),
+                select: shift(key),
+                delta: ViewDelta::Page,
+            }))
+        },
+        "PageUp PageDown",
+        "Move the selection by a page.",
+    ),
+    bind(
+        M::Config,
+        &[Char('u'), Char('d')],
+        NONE,
+        |key, _| {
+            Some(Action::Config(ConfigAction::Move {
+                direction: back_if(key, Char('u')),
+                select: shift(key),
+                delta: ViewDelta::HalfPage,
+            }))
+        },
+        "u d",
+        "Move the selection by a half-page.",
+    ),
+    bind(
+        M::Config,
+        &[Enter],
+        NONE,
+        |_, _| Some(Action::Config(ConfigAction::LoadSelectedFilter)),
+        "Enter",
+        "Load the selected filter set.",
+    ),
+    bind(
+        M::Config,
+        &[Backspace],
+        NONE,
+        |_, _| Some(Action::Config(ConfigAction::RemoveSelectedFilter)),
+        "Backspace",
+        "Remove the selected filter set.",
+    ),
+    // Visual mode
+    bind(
+        M::Visual,
+        &[Up, Down],
+        NONE,
+        |key, _| {
+            Some(Action::Visual(VisualAction::Move {
+                direction: back_if(key, Up),
+                select: shift(key),
+                delta: if key
+                    .modifiers
+                    .intersects(KeyModifiers::ALT | KeyModifiers::CONTROL)
+                {
+                    ViewDelta::HalfPage
+                } else {
+                    ViewDelta::Number(1)
                 },
-                _ => None,
-            },
-            InputMode::Prompt(prompt_mode) => match event {
-                Event::Paste(input) => Some(Action::Command(CommandAction::Paste {
-                    input: std::mem::take(input),
-                })),
-                Event::Key(key) => match key.code {
-                    KeyCode::Enter => Some(Action::Command(CommandAction::Submit)),
-                    KeyCode::Left | KeyCode::Right => Some(Action::Command(CommandAction::Move {
-                        direction: Direction::back_if(key.code == KeyCode::Left),
-                        select: key.modifiers.contains(KeyModifiers::SHIFT),
-                        jump: if key
-                            .modifiers
-                            .intersects(KeyModifiers::ALT | KeyModifiers::CONTROL)
-                        {
-                            CommandJump::Word
-                        } else {
-                            CommandJump::None
-                        },
-                    })),
-                    KeyCode::Home | KeyCode::End => Some(Action::Command(CommandAction::Move {
-                        direction: Direction::back_if(key.code == KeyCode::Home),
-                        select: key.modifiers.contains(KeyModifiers::SHIFT),
-                        jump: CommandJump::Boundary,
-                    })),
-                    KeyCode::Up | KeyCode::Down => Some(Action::Command(CommandAction::History {
-                        direction: Direction::back_if(key.code == KeyCode::Up),
-                    })),
-                    KeyCode::Backspace => Some(Action::Command(CommandAction::Backspace)),
-                    KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        match prompt_mode {
-                            PromptMode::Search { escaped, edit } => {
-                                Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Search {
-                                    escaped: !escaped,
-                                    edit,
-                                })))
-                            }
-                            _ => None,
-                        }
-                    }
-                    KeyCode::Char(to_insert) => match to_insert {
-                        'b' | 'f' if key.modifiers.contains(KeyModifiers::ALT) => {
-                            Some(Action::Command(CommandAction::Move {
-                                direction: Direction::back_if(to_insert == 'b'),
-                                select: key.modifiers.contains(KeyModifiers::SHIFT),
-                                jump: CommandJump::Word,
-                            }))
-                        }
-                        'a' | 'e' if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            Some(Action::Command(CommandAction::Move {
-                                direction: Direction::back_if(to_insert == 'a'),
-                                select: key.modifiers.contains(KeyModifiers::SHIFT),
-                                jump: CommandJump::Boundary,
-                            }))
-                        }
-                        input => Some(Action::Command(CommandAction::Type { input })),
-                    },
-                    KeyCode::Tab => Some(Action::Command(CommandAction::Complete)),
-                    _ => None,
+            }))
+        },
+        "Up Down",
+        "Move the select cursor, extending the selection with Shift.",
+    ),
+    bind(
+        M::Visual,
+        &[Char('n'), Char('N')],
+        NONE,
+        |key, _| {
+            // Both keys move forward, Shift extends the selection
+            Some(Action::Visual(VisualAction::Move {
+                direction: Direction::Next,
+                delta: ViewDelta::Match,
+                select: shift(key),
+            }))
+        },
+        "n N",
+        "Select the next active match.",
+    ),
+    bind(
+        M::Visual,
+        &[Home, End],
+        NONE,
+        |key, _| {
+            Some(Action::Visual(VisualAction::Move {
+                direction: back_if(key, Home),
+                select: shift(key),
+                delta: ViewDelta::Boundary,
+            }))
+        },
+        "Home End",
+        "Select the first/last line.",
+    ),
+    bind(
+        M::Visual,
+        &[PageUp, PageDown],
+        NONE,
+        |key, _| {
+            Some(Action::Visual(VisualAction::Move {
+                direction: back_if(key, PageUp),
+                select: shift(key),
+                delta: ViewDelta::Page,
+            }))
+        },
+        "PageUp PageDown",
+        "Move the select cursor by a page.",
+    ),
+    bind(
+        M::Visual,
+        &[Char('u'), Char('d')],
+        CONTROL,
+        |key, _| {
+            Some(Action::Visual(VisualAction::Move {
+                direction: back_if(key, Char('u')),
+                select: shift(key),
+                delta: ViewDelta::HalfPage,
+            }))
+        },
+        "Ctrl + u d",
+        "Move the select cursor by a half-page.",
+    ),
+    bind(
+        M::Visual,
+        &[Char('b'), Char('f')],
+        CONTROL,
+        |key, _| {
+            Some(Action::Visual(VisualAction::Move {
+                direction: back_if(key, Char('b')),
+                select: shift(key),
+                delta: ViewDelta::Page,
+            }))
+        },
+        "Ctrl + b f",
+        "Move the select cursor by a page.",
+    ),
+    bind(
+        M::Visual,
+        &[Char(' '), Enter],
+        NONE,
+        |_, _| Some(Action::Visual(VisualAction::ToggleSelectedLine)),
+        "Space Enter",
+        "Toggle the bookmark of the selected lines.",
+    ),
+    bind(
+        M::Visual,
+        &[Char('~')],
+        NONE,
+        |_, _| Some(Action::Visual(VisualAction::InvertSelection)),
+        "~",
+        "Invert the bookmarks of all visible lines.",
+    ),
+    bind(
+        M::Visual,
+        &[Char('*')],
+        NONE,
+        |_, _| Some(Action::Normal(NormalAction::QuickFilter)),
+        "*",
+        "Filter the word at the left edge of the cursor line (or the selected line).",
+    ),
+    bind(
+        M::Visual,
+        &[Char('['), Char(']')],
+        NONE,
+        |key, _| {
+            Some(Action::Normal(NormalAction::JumpBookmark(back_if(
+                key,
+                Char('['),
+            ))))
+        },
+        "[ ]",
+        "Select the previous/next bookmark.",
+    ),
+    // Prompts
+    bind(
+        M::Prompt,
+        &[Enter],
+        NONE,
+        |_, _| Some(Action::Command(CommandAction::Submit)),
+        "Enter",
+        "Submit the prompt.",
+    ),
+    bind(
+        M::Prompt,
+        &[Left, Right],
+        NONE,
+        |key, _| {
+            Some(Action::Command(CommandAction::Move {
+                direction: back_if(key, Left),
+                select: shift(key),
+                jump: if key
+                    .modifiers
+                    .intersects(KeyModifiers::ALT | KeyModifiers::CONTROL)
+                {
+                    CommandJump::Word
+                } else {
+                    CommandJump::None
                 },
-                _ => None,
-            },
+            }))
+        },
+        "Left Right",
+        "Move the prompt cursor, by a word with Alt or Ctrl.",
+    ),
+    bind(
+        M::Prompt,
+        &[Home, End],
+        NONE,
+        |key, _| {
+            Some(Action::Command(CommandAction::Move {
+                direction: back_if(key, Home),
+                select: shift(key),
+                jump: CommandJump::Boundary,
+            }))
+        },
+        "Home End",
+        "Move the prompt cursor to the start/end.",
+    ),
+    bind(
+        M::Prompt,
+        &[Up, Down],
+        NONE,
+        |key, _| {
+            Some(Action::Command(CommandAction::History {
+                direction: back_if(key, Up),
+            }))
+        },
+        "Up Down",
+        "Recall earlier/later prompt history.",
+    ),
+    bind(
+        M::Prompt,
+        &[Backspace],
+        NONE,
+        |_, _| Some(Action::Command(CommandAction::Backspace)),
+        "Backspace",
+        "Delete the character before the cursor.",
+    ),
+    bind(
+        M::Prompt,
+        &[Char('r')],
+        CONTROL,
+        |_, input_mode| match input_mode {
+            InputMode::Prompt(PromptMode::Search { escaped, edit }) => {
+                Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Search {
+                    escaped: !escaped,
+                    edit,
+                })))
+            }
+            InputMode::Prompt(PromptMode::Find { escaped }) => {
+                Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Find {
+                    escaped: !escaped,
+                })))
+            }
+            _ => None,
+        },
+        "Ctrl + r",
+        "Switch the search prompt between regex and literal.",
+    ),
+    bind(
+        M::Prompt,
+        &[Char('b'), Char('f')],
+        ALT,
+        |key, _| {
+            Some(Action::Command(CommandAction::Move {
+                direction: back_if(key, Char('b')),
+                select: shift(key),
+                jump: CommandJump::Word,
+            }))
+        },
+        "Alt + b f",
+        "Move the prompt cursor by a word.",
+    ),
+    bind(
+        M::Prompt,
+        &[Char('a'), Char('e')],
+        CONTROL,
+        |key, _| {
+            Some(Action::Command(CommandAction::Move {
+                direction: back_if(key, Char('a')),
+                select: shift(key),
+                jump: CommandJump::Boundary,
+            }))
+        },
+        "Ctrl + a e",
+        "Move the prompt cursor to the start/end.",
+    ),
+    KeyBind {
+        mode: M::Prompt,
+        keys: BindKeys::AnyChar,
+        modifiers: NONE,
+        action: |key, _| match key.code {
+            KeyCode::Char(input) => Some(Action::Command(CommandAction::Type { input })),
+            _ => None,
+        },
+        label: "",
+        description: "Type into the prompt.",
+    },
+    bind(
+        M::Prompt,
+        &[Tab],
+        NONE,
+        |_, _| {
+            Some(Action::Command(CommandAction::Complete {
+                direction: Direction::Next,
+            }))
+        },
+        "Tab",
+        "Complete the command, path or name, again for the next candidate.",
+    ),
+    bind(
+        M::Prompt,
+        &[BackTab],
+        NONE,
+        |_, _| {
+            Some(Action::Command(CommandAction::Complete {
+                direction: Direction::Back,
+            }))
+        },
+        "Shift + Tab",
+        "Go back to the previous completion candidate.",
+    ),
+    // Every mode
+    bind(
+        M::Any,
+        &[Char(':')],
+        NONE,
+        |_, _| Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Command))),
+        ":",
+        "Enter command mode.",
+    ),
+    bind(
+        M::Any,
+        &[Char('/')],
+        NONE,
+        |_, _| {
+            Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Search {
+                escaped: false,
+                edit: false,
+            })))
+        },
+        "/",
+        "Add a search filter.",
+    ),
+    bind(
+        M::Any,
+        &[Char('?')],
+        NONE,
+        |_, _| {
+            Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Find {
+                escaped: false,
+            })))
+        },
+        "?",
+        "Search backward without adding a filter.",
+    ),
+    bind(
+        M::Any,
+        &[Char('!')],
+        NONE,
+        |_, _| {
+            Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Shell {
+                pipe: false,
+            })))
+        },
+        "!",
+        "Run a shell command.",
+    ),
+    bind(
+        M::Any,
+        &[Char('f')],
+        NONE,
+        |_, _| Some(Action::SwitchMode(InputMode::Filter)),
+        "f",
+        "Enter filter mode.",
+    ),
+    bind(
+        M::Any,
+        &[F(1)],
+        NONE,
+        |_, _| Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Help))),
+        "F1",
+        "Search the list of commands and keys.",
+    ),
+    bind(
+        M::Any,
+        &[Tab],
+        NONE,
+        |_, _| Some(Action::Normal(NormalAction::SwitchActive(Direction::Next))),
+        "Tab",
+        "Switch to the next tab/view.",
+    ),
+    bind(
+        M::Any,
+        &[Esc],
+        NONE,
+        |_, _| Some(Action::SwitchMode(InputMode::Normal)),
+        "Esc",
+        "Return to normal mode.",
+    ),
+    bind(
+        M::Any,
+        &[Char('v')],
+        NONE,
+        |_, _| Some(Action::SwitchMode(InputMode::Visual)),
+        "v",
+        "Enter visual mode.",
+    ),
+    bind(
+        M::Any,
+        &[BackTab],
+        NONE,
+        |_, _| Some(Action::Normal(NormalAction::SwitchActive(Direction::Back))),
+        "Shift + Tab",
+        "Switch to the previous tab/view.",
+    ),
+    bind(
+        M::Any,
+        &[
+            Char('1'),
+            Char('2'),
+            Char('3'),
+            Char('4'),
+            Char('5'),
+            Char('6'),
+            Char('7'),
+            Char('8'),
+            Char('9'),
+        ],
+        NONE,
+        |key, _| {
+            let KeyCode::Char(c @ '1'..='9') = key.code else {
+                return None;
+            };
+            Some(Action::Normal(NormalAction::SwitchActiveIndex {
+                target_view: c as usize - '1' as usize,
+            }))
+        },
+        "1-9",
+        "Switch to the tab/view with this number.",
+    ),
+    bind(
+        M::Any,
+        &[Char('c')],
+        CONTROL,
+        |_, _| Some(Action::Exit),
+        "Ctrl + c",
+        "Quit.",
+    ),
+];
+
+impl Keybinding {
+    pub fn map_key(&self, input_mode: InputMode, event: &mut Event) -> Option<Action> {
+        match self {
+            Self::Hardcoded => Self::native_keys(input_mode, event),
         }
     }
 
-    fn mode_independent_bind(_input_mode: InputMode, event: &mut Event) -> Option<Action> {
+    fn native_keys(input_mode: InputMode, event: &mut Event) -> Option<Action> {
         match event {
-            Event::Key(key) => match key.code {
-                KeyCode::Char(':') => {
-                    Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Command)))
-                }
-                KeyCode::Char('/') => {
-                    Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Search {
-                        escaped: false,
-                        edit: false,
-                    })))
-                }
-                KeyCode::Char('!') => {
-                    Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Shell {
-                        pipe: false,
-                    })))
-                }
-                // TODO: feature still in development
-                // KeyCode::Char('|') => {
-                //     Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Shell { pipe: true })))
-                // }
-                KeyCode::Char('f') => Some(Action::SwitchMode(InputMode::Filter)),
-                KeyCode::Tab => Some(Action::Normal(NormalAction::SwitchActive(Direction::Next))),
-                KeyCode::Esc => Some(Action::SwitchMode(InputMode::Normal)),
-                KeyCode::Char('v') => Some(Action::SwitchMode(InputMode::Visual)),
-                KeyCode::BackTab => {
-                    Some(Action::Normal(NormalAction::SwitchActive(Direction::Back)))
-                }
-                KeyCode::Char(c @ '1'..='9') => {
-                    Some(Action::Normal(NormalAction::SwitchActiveIndex {
-                        target_view: c as usize - '1' as usize,
-                    }))
-                }
-                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    Some(Action::Exit)
-                }
-                _ => None,
-            },
+            Event::Key(key) if key.kind == KeyEventKind::Release => None,
+            Event::Key(key) => Self::bound_action(DEFAULT_BINDINGS, input_mode, key),
+            Event::Paste(input) if matches!(input_mode, InputMode::Prompt(_)) => {
+                Some(Action::Command(CommandAction::Paste {
+                    input: std::mem::take(input),
+                }))
+            }
             _ => None,
         }
     }
+
+    /// The action of the first binding of `input_mode` triggered by `key`, or
+    /// else of the first binding of [BindMode::Any].
+    fn bound_action(bindings: &[KeyBind], input_mode: InputMode, key: &KeyEvent) -> Option<Action> {
+        let mode = BindMode::of(input_mode);
+        let first = |mode: BindMode| {
+            bindings
+                .iter()
+                .find(|bind| bind.mode == mode && bind.triggered_by(key))
+                .and_then(|bind| (bind.action)(key, input_mode))
+        };
+        first(mode).or_else(|| first(BindMode::Any))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossterm::event::KeyEventState;
+
+    fn map(input_mode: InputMode, code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        let mut event = Event::Key(KeyEvent::new(code, modifiers));
+        Keybinding::Hardcoded.map_key(input_mode, &mut event)
+    }
+
+    #[test]
+    fn documented_keys_keep_their_actions() {
+        let shifted = KeyModifiers::SHIFT;
+        let search = InputMode::Prompt(PromptMode::Search {
+            escaped: false,
+            edit: false,
+        });
+        let command = InputMode::Prompt(PromptMode::Command);
+        let pan = |direction, delta| {
+            Some(Action::Normal(NormalAction::PanVertical {
+                direction,
+                delta,
+                target_view: None,
+            }))
+        };
+        let select = |direction, select, delta| {
+            Some(Action::Visual(VisualAction::Move {
+                direction,
+                select,
+                delta,
+            }))
+        };
+
+        let cases = [
+            // Normal mode
+            (
+                InputMode::Normal,
+                Up,
+                NONE,
+                pan(Direction::Back, ViewDelta::Number(1)),
+            ),
+            (
+                InputMode::Normal,
+                Down,
+                shifted,
+                pan(Direction::Next, ViewDelta::HalfPage),
+            ),
+            (
+                InputMode::Normal,
+                Right,
+                NONE,
+                Some(Action::Normal(NormalAction::PanHorizontal {
+                    direction: Direction::Next,
+                    delta: ViewDelta::Number(1),
+                    target_view: None,
+                })),
+            ),
+            (
+                InputMode::Normal,
+                Char('g'),
+                NONE,
+                pan(Direction::Back, ViewDelta::Boundary),
+            ),
+            (
+                InputMode::Normal,
+                End,
+                NONE,
+                Some(Action::Normal(NormalAction::FollowOutput)),
+            ),
+            (
+                InputMode::Normal,
+                Char('F'),
+                NONE,
+                Some(Action::Normal(NormalAction::ToggleFollowOutput)),
+            ),
+            (
+                InputMode::Normal,
+                Char('%'),
+                NONE,
+                Some(Action::Normal(NormalAction::JumpMatchingBracket)),
+            ),
+            (
+                InputMode::Normal,
+                Char(' '),
+                NONE,
+                pan(Direction::Next, ViewDelta::Page),
+            ),
+            (
+                InputMode::Normal,
+                Char('b'),
+                CONTROL,
+                pan(Direction::Back, ViewDelta::Page),
+            ),
+            (
+                InputMode::Normal,
+                Char('u'),
+                NONE,
+                pan(Direction::Back, ViewDelta::HalfPage),
+            ),
+            (
+                InputMode::Normal,
+                Char('n'),
+                NONE,
+                pan(Direction::Next, ViewDelta::Match),
+            ),
+            (
+                InputMode::Normal,
+                Char('N'),
+                NONE,
+                Some(Action::Normal(NormalAction::SwitchActiveWithMatches(
+                    Direction::Next,
+                ))),
+            ),
+            (
+                InputMode::Normal,
+                Char('['),
+                NONE,
+                Some(Action::Normal(NormalAction::JumpBookmark(Direction::Back))),
+            ),
+            (
+                InputMode::Normal,
+                Char('O'),
+                NONE,
+                Some(Action::Normal(NormalAction::ReturnFromOriginal)),
+            ),
+            // Without Ctrl, `f` falls through to the bindings of every mode
+            (
+                InputMode::Normal,
+                Char('f'),
+                NONE,
+                Some(Action::SwitchMode(InputMode::Filter)),
+            ),
+            // Visual mode
+            (
+                InputMode::Visual,
+                Up,
+                shifted,
+                select(Direction::Back, true, ViewDelta::Number(1)),
+            ),
+            (
+                InputMode::Visual,
+                Down,
+                ALT,
+                select(Direction::Next, false, ViewDelta::HalfPage),
+            ),
+            (
+                InputMode::Visual,
+                Char('d'),
+                CONTROL,
+                select(Direction::Next, false, ViewDelta::HalfPage),
+            ),
+            (InputMode::Visual, Char('d'), NONE, None),
+            (
+                InputMode::Visual,
+                Enter,
+                NONE,
+                Some(Action::Visual(VisualAction::ToggleSelectedLine)),
+            ),
+            (
+                InputMode::Visual,
+                Char('~'),
+                NONE,
+                Some(Action::Visual(VisualAction::InvertSelection)),
+            ),
+            // Filter and config modes
+            (
+                InputMode::Filter,
+                Char('/'),
+                NONE,
+                Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Search {
+                    escaped: false,
+                    edit: true,
+                }))),
+            ),
+            (
+                InputMode::Filter,
+                Char('&'),
+                NONE,
+                Some(Action::Filter(FilterAction::CycleStrategy)),
+            ),
+            (
+                InputMode::Config,
+                Enter,
+                NONE,
+                Some(Action::Config(ConfigAction::LoadSelectedFilter)),
+            ),
+            // Prompts
+            (
+                search,
+                Char('r'),
+                CONTROL,
+                Some(Action::SwitchMode(InputMode::Prompt(PromptMode::Search {
+                    escaped: true,
+                    edit: false,
+                }))),
+            ),
+            (command, Char('r'), CONTROL, None),
+            (
+                command,
+                Char('q'),
+                NONE,
+                Some(Action::Command(CommandAction::Type { input: 'q' })),
+            ),
+            (
+                command,
+                Char(':'),
+                NONE,
+                Some(Action::Command(CommandAction::Type { input: ':' })),
+            ),
+            (
+                command,
+                Char('b'),
+                ALT,
+                Some(Action::Command(CommandAction::Move {
+                    direction: Direction::Back,
+                    select: false,
+                    jump: CommandJump::Word,
+                })),
+            ),
+            (
+                command,
+                Tab,
+                NONE,
+                Some(Action::Command(CommandAction::Complete {
+                    direction: Direction::Next,
+                })),
+            ),
+            (
+                command,
+                BackTab,
+                NONE,
+                Some(Action::Command(CommandAction::Complete {
+                    direction: Direction::Back,
+                })),
+            ),
+            (
+                command,
+                Esc,
+                NONE,
+                Some(Action::SwitchMode(InputMode::Normal)),
+            ),
+            // Every mode
+            (
+                InputMode::Normal,
+                Char(':'),
+                NONE,
+                Some(Action::SwitchMode(command)),
+            ),
+            (
+                InputMode::Filter,
+                Char('3'),
+                NONE,
+                Some(Action::Normal(NormalAction::SwitchActiveIndex {
+                    target_view: 2,
+                })),
+            ),
+            (InputMode::Visual, Char('c'), CONTROL, Some(Action::Exit)),
+            (InputMode::Normal, Char('z'), NONE, None),
+        ];
+
+        for (input_mode, code, modifiers, action) in cases {
+            assert_eq!(
+                map(input_mode, code, modifiers),
+                action,
+                "{code:?} with {modifiers:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn released_keys_are_ignored() {
+        let mut event = Event::Key(KeyEvent::new_with_kind(
+            Char(':'),
+            NONE,
+            KeyEventKind::Release,
+        ));
+        assert!(Keybinding::Hardcoded
+            .map_key(InputMode::Normal, &mut event)
+            .is_none());
+    }
+
+    fn key(code: KeyCode, kind: KeyEventKind) -> Event {
+        Event::Key(KeyEvent::new_with_kind_and_state(
+            code,
+            NONE,
+            kind,
+            KeyEventState::NONE,
+        ))
+    }
+
+    /// The delta of each event, fed to the accelerator `gap` apart.
+    fn deltas(accelerator: &mut KeyAccelerator, events: &[Event], gap: Duration) -> Vec<u16> {
+        let start = Instant::now();
+        events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| {
+                let action = Some(Action::Normal(NormalAction::PanVertical {
+                    direction: Direction::Next,
+                    delta: ViewDelta::Number(1),
+                    target_view: None,
+                }));
+                match accelerator.accelerate_at(event, action, start + gap * i as u32) {
+                    Some(Action::Normal(NormalAction::PanVertical {
+                        delta: ViewDelta::Number(n),
+                        ..
+                    })) => n,
+                    action => panic!("unexpected {action:?}"),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn repeats_act_like_presses() {
+        let mut repeat = key(Down, KeyEventKind::Repeat);
+        let mut release = key(Down, KeyEventKind::Release);
+        assert_eq!(
+            Keybinding::Hardcoded.map_key(InputMode::Normal, &mut repeat),
+            map(InputMode::Normal, Down, NONE)
+        );
+        assert_eq!(
+            Keybinding::Hardcoded.map_key(InputMode::Normal, &mut release),
+            None
+        );
+    }
+
+    #[test]
+    fn accelerator_speeds_up_repeats_until_release() {
+        let mut accelerator = KeyAccelerator::new(3);
+        let mut events = vec![key(Down, KeyEventKind::Press)];
+        events.extend(std::iter::repeat_n(key(Down, KeyEventKind::Repeat), 14));
+        events.push(key(Down, KeyEventKind::Release));
+        events.push(key(Down, KeyEventKind::Press));
+
+        // Repeats count however far apart they are
+        let deltas = deltas(&mut accelerator, &events, Duration::from_secs(1));
+        assert_eq!(deltas[..5], [1; 5]);
+        assert_eq!(deltas[5..10], [2; 5]);
+        // Capped at the maximum delta
+        assert_eq!(deltas[10..15], [3; 5]);
+        // Letting go starts over
+        assert_eq!(deltas[15..], [1, 1]);
+    }
+
+    #[test]
+    fn accelerator_treats_quick_presses_as_held() {
+        let mut accelerator = KeyAccelerator::new(8);
+        let quick = KeyAccelerator::REPEAT_WINDOW / 2;
+        let presses = vec![key(Up, KeyEventKind::Press); 6];
+        assert_eq!(
+            deltas(&mut accelerator, &presses, quick),
+            [1, 1, 1, 1, 1, 2]
+        );
+
+        // Presses further apart are separate
+        let mut accelerator = KeyAccelerator::new(8);
+        let slow = KeyAccelerator::REPEAT_WINDOW * 2;
+        assert_eq!(deltas(&mut accelerator, &presses, slow), [1; 6]);
+
+        // Another key starts over
+        let mut accelerator = KeyAccelerator::new(8);
+        let mut events = presses.clone();
+        events.push(key(Down, KeyEventKind::Press));
+        assert_eq!(
+            deltas(&mut accelerator, &events, quick),
+            [1, 1, 1, 1, 1, 2, 1]
+        );
+    }
 }
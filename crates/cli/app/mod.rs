@@ -1,44 +1,69 @@
 mod actions;
 pub mod control;
-mod keybinding;
+mod export;
+pub mod keybinding;
 mod mouse;
+mod options;
+mod through;
 mod widgets;
 
 use self::{
     actions::{Action, CommandAction, NormalAction, VisualAction},
-    control::{InputMode, PromptMode},
-    keybinding::Keybinding,
+    control::{InputMode, MouseModes, PromptMode, SelectionStyle, StatusLayout, ViewDelta},
+    export::{fence_text, write_export, write_fenced, ExportOptions, LineFormat, TrimWriter},
+    keybinding::{KeyAccelerator, Keybinding},
     mouse::MouseHandler,
+    through::Through,
     widgets::{MultiplexerWidget, PromptWidget},
 };
 use crate::{
+    colors,
     components::{
-        config::filter::FilterConfigApp,
-        instance::Instance,
+        config::{filter::FilterConfigApp, recent::RecentFilesApp, theme::Theme},
+        diff,
+        filters::{BookmarksExport, FilterExportSet},
+        help::{self, HelpApp, HelpEntry},
+        histogram::HistogramApp,
+        instance::{Instance, PendingJump, Position},
         mux::{MultiplexerApp, MultiplexerMode},
+        peek::PeekApp,
+        perf::PerfApp,
         prompt::{self, PromptApp, PromptMovement},
+        query,
         status::StatusApp,
     },
     direction::Direction,
+    numbers::NumberFormat,
     regex_compile,
 };
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use arboard::Clipboard;
-use bvr_core::{buf::SegBuffer, err::Error, index::BoxedStream, matches::CompositeStrategy};
+use bvr_core::{
+    buf::SegBuffer,
+    err::Error,
+    index::{BoxedStream, Delimiter},
+    matches::{CompositeStrategy, MatchCount},
+};
 use crossterm::event::{
     self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    Event, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
 };
-use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+use crossterm::{
+    cursor::MoveTo,
+    style::Print,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use ratatui::style::Color;
 use regex::bytes::Regex;
 use std::{
     borrow::Cow,
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     fs::OpenOptions,
+    io::BufWriter,
     num::NonZeroUsize,
+    ops::Range,
     path::{Path, PathBuf},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 pub type Backend<'a> = ratatui::backend::CrosstermBackend<std::io::StdoutLock<'a>>;
@@ -47,7 +72,172 @@ pub type Terminal<'a> = ratatui::Terminal<Backend<'a>>;
 struct RegexCache {
     pattern: String,
     escaped: bool,
-    regex: Option<Regex>,
+    /// The compiled pattern, or a short reason why it is invalid.
+    regex: std::result::Result<Regex, String>,
+    /// Lines of the active view that the valid pattern matches, counted in
+    /// the background as it is typed.
+    count: Option<MatchCount>,
+}
+
+/// Condenses a regex error into a single line that fits in the prompt.
+fn regex_error_hint(err: &regex::Error) -> String {
+    match err {
+        regex::Error::Syntax(err) => err
+            .lines()
+            .rev()
+            .find_map(|line| line.strip_prefix("error: "))
+            .unwrap_or("invalid syntax")
+            .to_owned(),
+        regex::Error::CompiledTooBig(_) => String::from("pattern too large"),
+        _ => err.to_string(),
+    }
+}
+
+/// Splits the leading whitespace-separated word off `args`.
+fn split_word(args: &str) -> (&str, &str) {
+    let args = args.trim_start();
+    let end = args.find(char::is_whitespace).unwrap_or(args.len());
+    (&args[..end], &args[end..])
+}
+
+/// The rest of a command taken verbatim, so that it may contain spaces.
+/// Quotes around the whole of it are removed.
+fn text_argument(args: &str) -> &str {
+    let args = args.trim();
+    ['"', '\'']
+        .into_iter()
+        .find_map(|quote| args.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(args)
+}
+
+/// The rest of a command taken as a path, see [text_argument]. A leading `--`
+/// ends the options, for paths that start with `-`.
+fn path_argument(args: &str) -> PathBuf {
+    let path = match split_word(args) {
+        ("--", rest) => rest,
+        _ => args,
+    };
+    PathBuf::from(text_argument(path))
+}
+
+/// Entries of the directory in `prefix` whose names start with the rest of
+/// it, in order and with a `/` after directories. Hidden entries are only
+/// listed when the name starts with a `.` too.
+fn complete_path(prefix: &str) -> Vec<String> {
+    let (dir, name) = match prefix.rfind('/') {
+        Some(i) => prefix.split_at(i + 1),
+        None => ("", prefix),
+    };
+    let Ok(entries) = std::fs::read_dir(if dir.is_empty() { "." } else { dir }) else {
+        return Vec::new();
+    };
+    let mut candidates = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let file_name = entry.file_name().into_string().ok()?;
+            if !file_name.starts_with(name)
+                || (file_name.starts_with('.') && !name.starts_with('.'))
+            {
+                return None;
+            }
+            // Followed through symlinks, so that linked directories can be entered
+            let slash = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{dir}{file_name}{slash}"))
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_unstable();
+    candidates
+}
+
+/// Where the word being completed starts in the command typed so far, and
+/// what it could be: a command name, a path for `:open`, `:export` and
+/// `:write`, the name of a view for `:buffer` or a recent file for `:recent`.
+fn completion_candidates(
+    before: &str,
+    mux: &MultiplexerApp,
+    recent: &RecentFilesApp,
+) -> (usize, Vec<String>) {
+    let (name, rest) = split_word(before);
+    if rest.is_empty() {
+        let start = before.len() - name.len();
+        let mut names = help::COMMANDS
+            .iter()
+            .filter_map(|help| help.command.split_whitespace().next())
+            .filter(|command| command.starts_with(name))
+            .map(String::from)
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+        return (start, names);
+    }
+
+    // The argument is taken whole, as these take the rest of the command
+    let start = before.len() - rest.trim_start().len();
+    let argument = &before[start..];
+    match name {
+        "open" | "o" | "w" | "write" => (start, complete_path(argument)),
+        "export" => {
+            // Options come before the path, which is the last word
+            let start = before.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+            (start, complete_path(&before[start..]))
+        }
+        "buffer" | "b" => (
+            start,
+            mux.find_by_name(argument)
+                .into_iter()
+                .map(|i| mux.instances()[i].name().to_owned())
+                .collect(),
+        ),
+        "recent" => {
+            let files = recent.files().collect::<Vec<_>>();
+            (
+                start,
+                recent
+                    .find(argument)
+                    .into_iter()
+                    .map(|i| files[i].display().to_string())
+                    .collect(),
+            )
+        }
+        _ => (start, Vec::new()),
+    }
+}
+
+/// Reads bookmarks saved by `:marks save`, refusing those saved in a newer
+/// format than this version knows.
+fn load_bookmarks(path: &Path) -> Result<BookmarksExport> {
+    let file = std::fs::File::open(path)?;
+    let export: BookmarksExport = serde_json::from_reader(std::io::BufReader::new(file))?;
+    if export.version() > BookmarksExport::VERSION {
+        bail!("saved by a newer version of bvr");
+    }
+    Ok(export)
+}
+
+/// Opens a file to view, describing why it cannot be opened in a few words
+/// rather than with the raw error of the system.
+fn open_path(path: &Path) -> Result<std::fs::File> {
+    let describe = |err: std::io::Error| match err.kind() {
+        std::io::ErrorKind::NotFound => anyhow!("no such file"),
+        std::io::ErrorKind::PermissionDenied => anyhow!("permission denied"),
+        _ => anyhow!(err),
+    };
+    let file_type = std::fs::metadata(path).map_err(describe)?.file_type();
+    if file_type.is_dir() {
+        bail!("is a directory");
+    }
+    // Pipes and character devices are read as streams, but these cannot be
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_block_device() {
+            bail!("block device, not supported");
+        }
+        if file_type.is_socket() {
+            bail!("socket, not supported");
+        }
+    }
+    std::fs::File::open(path).map_err(describe)
 }
 
 pub struct App<'term> {
@@ -59,16 +249,73 @@ pub struct App<'term> {
     prompt: PromptApp,
 
     keybinds: Keybinding,
+    accelerator: KeyAccelerator,
+    /// Whether the terminal was asked to report repeats and releases of keys.
+    key_events: bool,
 
     clipboard: Option<Clipboard>,
     filter_config: FilterConfigApp,
+    recent: RecentFilesApp,
+    theme: Theme,
+    help: HelpApp,
 
     action_queue: VecDeque<Action>,
     regex_cache: Option<RegexCache>,
 
     gutter: bool,
+    time_delta: bool,
+    line_lengths: bool,
+    selection_style: SelectionStyle,
+    dim: bool,
+    wrap: bool,
+    /// How long copied lines are highlighted, zero to not highlight them.
+    flash: Duration,
+    flash_color: Color,
+    json: bool,
+    json_fields: Vec<String>,
+    rulers: Vec<usize>,
     mouse_capture: bool,
+    mouse_modes: MouseModes,
+    /// Whether the terminal is currently sending mouse events.
+    mouse_captured: bool,
+    alt_screen: bool,
+    /// Leave out bracketed paste and colors, mark lines with text instead and
+    /// redraw less often, see [App::set_accessible].
+    accessible: bool,
+    lock_files: bool,
+    max_matches: Option<usize>,
+    stream_memory_limit: Option<u64>,
+    readahead: usize,
+    /// Where files were left when their view was closed, and their filters,
+    /// to pick up from there if they are opened again.
+    closed: HashMap<PathBuf, (Position, FilterExportSet)>,
+    /// Command that files are piped through when opened, see
+    /// [App::set_through].
+    through: Option<String>,
+    delimiter: Delimiter,
+    /// Zero-based lines of each file to show, instead of the whole file.
+    line_range: Option<Range<usize>>,
+    tail: Option<usize>,
+    line_offset: usize,
     linked_filters: bool,
+    /// Backspace at an empty prompt returns to normal mode.
+    backspace_exits_prompt: bool,
+    /// Escape from the search prompt adds the filter typed so far.
+    esc_keeps_search: bool,
+    status_layout: StatusLayout,
+    numbers: NumberFormat,
+    histogram: Option<HistogramApp>,
+    /// Running `:count`, with the pattern it was given.
+    count: Option<(String, MatchCount)>,
+    /// Pattern of a running `?` find in the active view.
+    find: Option<String>,
+    peek: Option<PeekApp>,
+    show_messages: bool,
+    /// Whether `:debug` commands are allowed, see [App::set_debug].
+    debug: bool,
+    perf: Option<PerfApp>,
+    /// Show the filters on a row under each view outside of filter mode.
+    filter_strip: bool,
     refresh: bool,
 }
 
@@ -80,6 +327,16 @@ impl Drop for App<'_> {
 }
 
 impl<'term> App<'term> {
+    pub const DEFAULT_FLASH_MS: u64 = 150;
+    const DEFAULT_FLASH: Duration = Duration::from_millis(Self::DEFAULT_FLASH_MS);
+    /// Segments read in ahead when scrolling, see [App::set_readahead].
+    pub const DEFAULT_READAHEAD: usize = 1;
+    /// How long to wait for input before drawing again.
+    const TICK: Duration = Duration::from_nanos(1_000_000_000 / 30);
+    /// Redrawing less often in accessible mode keeps screen readers from
+    /// announcing every bit of progress.
+    const ACCESSIBLE_TICK: Duration = Duration::from_millis(500);
+
     pub fn new(term: Terminal<'term>) -> Self {
         Self {
             term,
@@ -88,13 +345,54 @@ impl<'term> App<'term> {
             mux: MultiplexerApp::new(),
             status: StatusApp::new(),
             filter_config: FilterConfigApp::new(),
+            recent: RecentFilesApp::new(),
+            theme: Theme::load(),
+            help: HelpApp::new(),
             keybinds: Keybinding::Hardcoded,
+            accelerator: KeyAccelerator::new(KeyAccelerator::DEFAULT_MAX_DELTA),
+            key_events: false,
             clipboard: Clipboard::new().ok(),
             gutter: true,
+            time_delta: false,
+            line_lengths: false,
+            selection_style: SelectionStyle::Background,
+            dim: false,
+            wrap: false,
+            flash: Self::DEFAULT_FLASH,
+            flash_color: colors::FLASH_BG,
+            json: false,
+            json_fields: Vec::new(),
+            rulers: Vec::new(),
             action_queue: VecDeque::new(),
             regex_cache: None,
             mouse_capture: true,
+            mouse_modes: MouseModes::default(),
+            mouse_captured: false,
+            alt_screen: true,
+            accessible: false,
+            lock_files: false,
+            max_matches: None,
+            stream_memory_limit: None,
+            readahead: Self::DEFAULT_READAHEAD,
+            through: None,
+            closed: HashMap::new(),
+            delimiter: Delimiter::Newline,
+            line_range: None,
+            tail: None,
+            line_offset: 0,
             linked_filters: false,
+            backspace_exits_prompt: true,
+            esc_keeps_search: false,
+            status_layout: StatusLayout::default(),
+            numbers: NumberFormat::default(),
+            histogram: None,
+            count: None,
+            find: None,
+            peek: None,
+            show_messages: false,
+            debug: false,
+            perf: None,
+            filter_strip: false,
             refresh: false,
         }
     }
@@ -102,17 +400,94 @@ impl<'term> App<'term> {
     pub fn open_file(&mut self, path: &Path) -> Result<()> {
         let load_filters = self.mux.is_empty() && self.filter_config.is_persistent();
 
-        let file = std::fs::File::open(path)?;
-        let name = path
-            .file_name()
-            .map(|str| str.to_string_lossy().into_owned())
-            .unwrap_or_else(|| String::from("Unnamed File"));
-        self.push_instance(
-            name,
-            SegBuffer::read_file(file, NonZeroUsize::new(25).unwrap(), false)?,
+        let (name, mut buf, line_offset) = if path == Path::new("-") {
+            (
+                String::from("stdin"),
+                SegBuffer::read_stream_delimited(
+                    Box::new(std::io::stdin()),
+                    self.delimiter,
+                    false,
+                )?,
+                self.line_offset,
+            )
+        } else {
+            // `/dev/fd/N` resolves to the inherited descriptor, which may be a pipe
+            let file = open_path(path)?;
+            let name = if path.starts_with("/dev/fd") {
+                path.display().to_string()
+            } else {
+                path.file_name()
+                    .map(|str| str.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| String::from("Unnamed File"))
+            };
+            let seg_count = NonZeroUsize::new(25).unwrap();
+            let (name, buf, line_offset) = match (&self.through, &self.line_range) {
+                (Some(command), _) => (
+                    format!("{name} | {command}"),
+                    SegBuffer::read_stream_delimited(
+                        Box::new(Through::spawn(command, file)?),
+                        self.delimiter,
+                        false,
+                    )?,
+                    self.line_offset,
+                ),
+                (None, Some(lines)) if file.metadata()?.is_file() => (
+                    name,
+                    SegBuffer::read_file_lines(
+                        file,
+                        seg_count,
+                        self.delimiter,
+                        lines.clone(),
+                        false,
+                        self.lock_files,
+                    )?,
+                    lines.start,
+                ),
+                _ => (
+                    name,
+                    SegBuffer::from_file_delimited(
+                        file,
+                        seg_count,
+                        self.delimiter,
+                        false,
+                        self.lock_files,
+                    )?,
+                    0,
+                ),
+            };
+            if !path.starts_with("/dev/fd") {
+                if let Err(err) = self.recent.push(path) {
+                    self.status.msg(format!("recent: {err}"));
+                }
+            }
+            (name, buf, line_offset)
+        };
+        if let Some(limit) = self.stream_memory_limit {
+            buf.limit_stream_memory(limit)?;
+        }
+        buf.set_readahead(self.readahead);
+        let mut instance = Instance::new(name, buf);
+        instance.set_line_offset(line_offset);
+        if let Some(count) = self.tail {
+            instance.jump_to(PendingJump::Tail(count));
+        }
+
+        // Streams cannot be reopened, and the output of `--through` is not the file
+        let is_file = path != Path::new("-") && !path.starts_with("/dev/fd");
+        instance.set_path(
+            (is_file && self.through.is_none())
+                .then(|| std::fs::canonicalize(path).ok())
+                .flatten(),
         );
+        let closed = instance.path().and_then(|path| self.closed.remove(path));
+        let restored = closed.is_some();
+        if let Some((position, filters)) = closed {
+            instance.import_user_filters(&filters);
+            instance.restore_position(position);
+        }
+        self.push_instance(instance);
 
-        if load_filters {
+        if load_filters && !restored {
             let filter_set = match self.filter_config.get_persistent_filter() {
                 Ok(filters) => filters,
                 Err(err) => {
@@ -121,9 +496,8 @@ impl<'term> App<'term> {
                 }
             };
             let instance = self.mux.active_mut().unwrap();
-            match filter_set {
-                Some(export) => instance.import_user_filters(export),
-                None => {}
+            if let Some(export) = filter_set {
+                instance.import_user_filters(export);
             }
         }
         if self.linked_filters {
@@ -141,39 +515,186 @@ impl<'term> App<'term> {
     }
 
     pub fn open_stream(&mut self, name: String, stream: BoxedStream) -> Result<()> {
-        self.push_instance(name, SegBuffer::read_stream(stream, false)?);
+        let mut buf = SegBuffer::read_stream_delimited(stream, self.delimiter, false)?;
+        if let Some(limit) = self.stream_memory_limit {
+            buf.limit_stream_memory(limit)?;
+        }
+        let mut instance = Instance::new(name, buf);
+        instance.set_line_offset(self.line_offset);
+        self.push_instance(instance);
         Ok(())
     }
 
-    fn push_instance(&mut self, name: String, file: SegBuffer) {
-        self.mux.push(Instance::new(name, file));
+    pub fn set_max_instances(&mut self, max_instances: usize) {
+        self.mux.set_max_instances(max_instances);
+    }
+
+    /// Draws in the main screen instead, leaving the last frame in the
+    /// scrollback on exit.
+    pub fn set_alt_screen(&mut self, alt_screen: bool) {
+        self.alt_screen = alt_screen;
+    }
+
+    pub fn set_mouse_capture(&mut self, mouse_capture: bool) {
+        self.mouse_capture = mouse_capture;
+    }
+
+    pub fn set_mouse_modes(&mut self, mouse_modes: MouseModes) {
+        self.mouse_modes = mouse_modes;
+    }
+
+    pub fn set_lock_files(&mut self, lock_files: bool) {
+        self.lock_files = lock_files;
+    }
+
+    /// Bounds the memory held by streams opened from now on, see
+    /// [SegBuffer::limit_stream_memory].
+    pub fn set_stream_memory_limit(&mut self, limit: Option<u64>) {
+        self.stream_memory_limit = limit;
+    }
+
+    /// Maps this many segments ahead of those read from files opened from now
+    /// on, see [SegBuffer::set_readahead].
+    pub fn set_readahead(&mut self, segments: usize) {
+        self.readahead = segments;
+    }
+
+    /// Pipes files opened from now on through `command`, showing its output
+    /// as a stream instead of the file itself.
+    pub fn set_through(&mut self, command: Option<String>) {
+        self.through = command;
+    }
+
+    /// Splits files and streams opened from now on into lines by `delimiter`.
+    pub fn set_delimiter(&mut self, delimiter: Delimiter) {
+        self.delimiter = delimiter;
+    }
+
+    /// Shows only these zero-based lines of files opened from now on, with
+    /// their line numbers in the whole file. Streams are shown in full.
+    pub fn set_line_range(&mut self, line_range: Option<Range<usize>>) {
+        self.line_range = line_range;
+    }
+
+    /// Opens files at the first of their last `tail` lines.
+    pub fn set_tail(&mut self, tail: Option<usize>) {
+        self.tail = tail;
+    }
+
+    pub fn set_max_matches(&mut self, max_matches: Option<usize>) {
+        self.max_matches = max_matches;
+    }
+
+    /// Caps how many lines a held movement key moves per repeat.
+    pub fn set_max_scroll_delta(&mut self, max_delta: u16) {
+        self.accelerator = KeyAccelerator::new(max_delta);
+    }
+
+    /// Offsets the line numbers of streams opened from now on.
+    pub fn set_line_offset(&mut self, line_offset: usize) {
+        self.line_offset = line_offset;
+    }
+
+    pub fn set_status_layout(&mut self, layout: StatusLayout) {
+        self.status_layout = layout;
+    }
+
+    pub fn set_number_format(&mut self, numbers: NumberFormat) {
+        self.numbers = numbers;
+    }
+
+    pub fn set_flash(&mut self, flash: Duration, color: Option<Color>) {
+        self.flash = flash;
+        self.flash_color = color.unwrap_or(colors::FLASH_BG);
+    }
+
+    /// Turns off bracketed paste and starts with the mouse left to the
+    /// terminal. The screen is drawn without colors, with the selection
+    /// reversed, and lines that are bookmarked or matched by a filter are
+    /// marked with `*` and `+`. Copied lines are not highlighted and the
+    /// screen is redrawn twice a second while idle rather than 30 times.
+    pub fn set_accessible(&mut self, accessible: bool) {
+        self.accessible = accessible;
+        if accessible {
+            self.mouse_capture = false;
+            self.selection_style = SelectionStyle::Reverse;
+            self.flash = Duration::ZERO;
+        }
+    }
+
+    /// Allows `:debug perf`, an overlay of frame times and of the counters
+    /// of the active view, left out of the default interface.
+    pub fn set_debug(&mut self, debug: bool) {
+        self.debug = debug;
+    }
+
+    /// Returns whether the instance is within the limit of open instances.
+    fn push_instance(&mut self, mut instance: Instance) -> bool {
+        instance.compositor_mut().set_max_matches(self.max_matches);
+        let mode = self.mux.mode();
+        if !self.mux.push(instance) {
+            self.status.msg(format!(
+                "{} instances open, more than the limit of {}",
+                self.mux.len(),
+                self.mux.max_instances()
+            ));
+            return false;
+        }
+        if mode != self.mux.mode() {
+            self.status
+                .msg(String::from("mux: too many instances to split, using tabs"));
+        }
+        true
     }
 
     fn enter_terminal(&mut self) -> Result<()> {
         enable_raw_mode()?;
-        crossterm::execute!(
-            self.term.backend_mut(),
-            EnterAlternateScreen,
-            EnableBracketedPaste,
-            EnableMouseCapture,
-        )?;
-        Ok(())
+        if self.alt_screen {
+            crossterm::execute!(self.term.backend_mut(), EnterAlternateScreen)?;
+        } else {
+            // Scroll whatever is on screen into the scrollback instead of drawing over it
+            let (_, rows) = crossterm::terminal::size()?;
+            crossterm::execute!(
+                self.term.backend_mut(),
+                MoveTo(0, rows.saturating_sub(1)),
+                Print("\n".repeat(usize::from(rows))),
+            )?;
+        }
+        if !self.accessible {
+            crossterm::execute!(self.term.backend_mut(), EnableBracketedPaste)?;
+            // Repeats and releases of held keys drive the key accelerator
+            self.key_events = crossterm::terminal::supports_keyboard_enhancement()?;
+            if self.key_events {
+                crossterm::execute!(
+                    self.term.backend_mut(),
+                    PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+                )?;
+            }
+        }
+        self.sync_mouse_capture()
     }
 
     fn exit_terminal(&mut self) -> Result<()> {
         disable_raw_mode()?;
-        if self.mouse_capture {
-            crossterm::execute!(
-                self.term.backend_mut(),
-                DisableMouseCapture,
-                DisableBracketedPaste,
-                LeaveAlternateScreen,
-            )?;
+        if self.mouse_captured {
+            crossterm::execute!(self.term.backend_mut(), DisableMouseCapture)?;
+        }
+        if !self.accessible {
+            crossterm::execute!(self.term.backend_mut(), DisableBracketedPaste)?;
+        }
+        if self.key_events {
+            crossterm::execute!(self.term.backend_mut(), PopKeyboardEnhancementFlags)?;
+            self.key_events = false;
+        }
+        if self.alt_screen {
+            crossterm::execute!(self.term.backend_mut(), LeaveAlternateScreen)?;
         } else {
+            // Leave the last frame in place and continue below it
+            let (_, rows) = crossterm::terminal::size()?;
             crossterm::execute!(
                 self.term.backend_mut(),
-                DisableBracketedPaste,
-                LeaveAlternateScreen,
+                MoveTo(0, rows.saturating_sub(1)),
+                Print("\n"),
             )?;
         }
         Ok(())
@@ -181,10 +702,20 @@ impl<'term> App<'term> {
 
     fn toggle_mouse_capture(&mut self) -> Result<()> {
         self.mouse_capture = !self.mouse_capture;
-        if self.mouse_capture {
-            crossterm::execute!(self.term.backend_mut(), EnableMouseCapture)?;
-        } else {
-            crossterm::execute!(self.term.backend_mut(), DisableMouseCapture)?;
+        self.sync_mouse_capture()
+    }
+
+    /// Captures the mouse if it is enabled for the current mode, and gives
+    /// it back to the terminal otherwise.
+    fn sync_mouse_capture(&mut self) -> Result<()> {
+        let capture = self.mouse_capture && self.mouse_modes.captures(self.mode);
+        if capture != self.mouse_captured {
+            if capture {
+                crossterm::execute!(self.term.backend_mut(), EnableMouseCapture)?;
+            } else {
+                crossterm::execute!(self.term.backend_mut(), DisableMouseCapture)?;
+            }
+            self.mouse_captured = capture;
         }
         Ok(())
     }
@@ -212,6 +743,8 @@ impl<'term> App<'term> {
         let mut mouse_handler = MouseHandler::new();
 
         loop {
+            self.sync_mouse_capture()?;
+            let frame_start = Instant::now();
             let cursor = self.ui(&mut mouse_handler);
             if self.refresh {
                 self.term.clear()?;
@@ -222,18 +755,34 @@ impl<'term> App<'term> {
                     f.set_cursor_position(cursor);
                 }
             })?;
+            if let Some(perf) = &mut self.perf {
+                perf.set_frame_time(frame_start.elapsed());
+            }
 
             let action = match self.action_queue.pop_front() {
                 Some(action) => action,
                 None => match mouse_handler.extract() {
                     Some(action) => action,
                     None => {
-                        if !event::poll(Duration::from_secs_f64(1.0 / 30.0))? {
+                        let tick = if self.accessible {
+                            Self::ACCESSIBLE_TICK
+                        } else {
+                            Self::TICK
+                        };
+                        if !event::poll(tick)? {
                             continue;
                         }
 
                         let mut event = event::read()?;
+                        if let Event::Resize(..) = event {
+                            // Cells outside the new area are not diffed, so
+                            // the whole screen has to be repainted.
+                            self.term.autoresize()?;
+                            self.refresh = true;
+                            continue;
+                        }
                         let key = self.keybinds.map_key(self.mode, &mut event);
+                        let key = self.accelerator.accelerate(&event, key);
                         mouse_handler.publish_event(event);
                         let Some(action) = key else { continue };
                         action
@@ -248,6 +797,13 @@ impl<'term> App<'term> {
         Ok(())
     }
 
+    fn search_wrapped_msg(direction: Direction) -> String {
+        match direction {
+            Direction::Back => String::from("search wrapped to bottom"),
+            Direction::Next => String::from("search wrapped to top"),
+        }
+    }
+
     fn get_target_view(&mut self, target_view: Option<usize>) -> Option<&mut Instance> {
         if let Some(index) = target_view {
             self.mux.instances_mut().get_mut(index)
@@ -258,11 +814,42 @@ impl<'term> App<'term> {
 
     fn process_action(&mut self, action: Action) -> Result<bool> {
         match action {
-            Action::Exit => return Ok(false),
+            Action::Exit => {
+                // Ctrl-C stops a running count rather than quitting
+                if let Some((pattern, count)) = self.count.take() {
+                    count.cancel();
+                    self.status.msg(format!(
+                        "count {pattern}: cancelled at {} lines",
+                        self.numbers.int(count.count())
+                    ));
+                    return Ok(true);
+                }
+                return Ok(false);
+            }
             Action::SwitchMode(new_mode) => {
                 let old_mode = self.mode;
                 self.mode = new_mode;
 
+                if let (
+                    InputMode::Prompt(PromptMode::Search { escaped, edit }),
+                    InputMode::Normal,
+                ) = (old_mode, new_mode)
+                {
+                    if self.esc_keeps_search && !self.prompt.buf().is_empty() {
+                        let pattern = self.prompt.take();
+                        self.process_search(&pattern, escaped, edit);
+                    }
+                }
+
+                // Escape in normal mode dismisses the overlays, then the histogram
+                if old_mode == InputMode::Normal
+                    && new_mode == InputMode::Normal
+                    && self.peek.take().is_none()
+                    && !std::mem::take(&mut self.show_messages)
+                {
+                    self.histogram = None;
+                }
+
                 match new_mode {
                     InputMode::Visual => {
                         if let Some(instance) = self.mux.active_mut() {
@@ -270,6 +857,10 @@ impl<'term> App<'term> {
                             instance.set_follow_output(false);
                         }
                     }
+                    InputMode::Prompt(PromptMode::Help) => {
+                        self.help.reset();
+                        self.prompt.take();
+                    }
                     InputMode::Prompt(PromptMode::Search { edit: true, .. }) => {
                         if let InputMode::Prompt(PromptMode::Search { edit: true, .. }) = old_mode {
                             return Ok(true);
@@ -291,7 +882,11 @@ impl<'term> App<'term> {
                         };
                     }
                     _ => {
-                        if !old_mode.is_prompt_search() || !new_mode.is_prompt_search() {
+                        // Toggling escaping keeps the pattern typed so far
+                        let same_prompt = (old_mode.is_prompt_search()
+                            && new_mode.is_prompt_search())
+                            || (old_mode.is_prompt_find() && new_mode.is_prompt_find());
+                        if !same_prompt {
                             self.prompt.take();
                         }
                     }
@@ -303,8 +898,15 @@ impl<'term> App<'term> {
                     delta,
                     target_view,
                 } => {
-                    if let Some(instance) = self.get_target_view(target_view) {
-                        instance.move_viewport_vertical(direction, delta)
+                    // Large jumps repaint fully to avoid leftover glyphs
+                    if let ViewDelta::Page = delta {
+                        self.refresh = true;
+                    }
+                    let wrapped = self
+                        .get_target_view(target_view)
+                        .is_some_and(|instance| instance.move_viewport_vertical(direction, delta));
+                    if wrapped {
+                        self.status.msg(Self::search_wrapped_msg(direction));
                     }
                 }
                 NormalAction::PanHorizontal {
@@ -321,10 +923,87 @@ impl<'term> App<'term> {
                         instance.set_follow_output(true);
                     }
                 }
+                NormalAction::ToggleFollowOutput => {
+                    if let Some(instance) = self.mux.active_mut() {
+                        instance.toggle_follow_output();
+                    }
+                }
                 NormalAction::SwitchActiveIndex { target_view } => {
                     self.mux.move_active_index(target_view)
                 }
                 NormalAction::SwitchActive(direction) => self.mux.move_active(direction),
+                NormalAction::SwitchActiveWithMatches(direction) => {
+                    if !self.mux.move_active_with_matches(direction) {
+                        self.status.msg(String::from("no other files with matches"));
+                    }
+                }
+                NormalAction::QuickFilter => {
+                    let Some((pattern, literal)) = self
+                        .mux
+                        .active_mut()
+                        .and_then(|instance| instance.quick_filter_pattern())
+                    else {
+                        self.status
+                            .msg(String::from("quick filter: no word under cursor"));
+                        return Ok(true);
+                    };
+                    self.process_search(&pattern, literal, false);
+                }
+                NormalAction::JumpMatchingBracket => {
+                    let Some(instance) = self.mux.active_mut() else {
+                        return Ok(true);
+                    };
+                    match instance.jump_to_matching_bracket() {
+                        Some(true) => {}
+                        Some(false) => self
+                            .status
+                            .msg(String::from("bracket: no matching bracket on the line")),
+                        None => self
+                            .status
+                            .msg(String::from("bracket: no bracket under cursor")),
+                    }
+                }
+                NormalAction::JumpBookmark(direction) => {
+                    let Some(instance) = self.mux.active_mut() else {
+                        return Ok(true);
+                    };
+                    if instance.compositor_mut().filters().bookmarks().is_empty() {
+                        self.status.msg(String::from("bookmark: no bookmarks"));
+                    } else if let Some((position, total)) = instance.jump_to_bookmark(direction) {
+                        self.status
+                            .msg(format!("bookmark {} of {total}", position + 1));
+                    } else {
+                        self.status.msg(match direction {
+                            Direction::Back => String::from("bookmark: no previous bookmark"),
+                            Direction::Next => String::from("bookmark: no next bookmark"),
+                        });
+                    }
+                }
+                NormalAction::JumpToOriginal => {
+                    if let Some(instance) = self.mux.active_mut() {
+                        if !instance.jump_to_original() {
+                            self.status
+                                .msg(String::from("original: view is not filtered"));
+                        }
+                    }
+                }
+                NormalAction::ReturnFromOriginal => {
+                    if let Some(instance) = self.mux.active_mut() {
+                        if !instance.return_from_original() {
+                            self.status
+                                .msg(String::from("original: no filtered view to return to"));
+                        }
+                    }
+                }
+                NormalAction::Peek => {
+                    self.peek = match self.peek.take() {
+                        Some(_) => None,
+                        None => self
+                            .mux
+                            .active_mut()
+                            .and_then(|instance| PeekApp::new(instance, PeekApp::CONTEXT)),
+                    };
+                }
             },
             Action::Visual(action) => match action {
                 VisualAction::Move {
@@ -332,9 +1011,15 @@ impl<'term> App<'term> {
                     select,
                     delta,
                 } => {
-                    if let Some(instance) = self.mux.active_mut() {
-                        instance.move_select(direction, select, delta);
+                    if let ViewDelta::Page = delta {
+                        self.refresh = true;
+                    }
+                    let wrapped = self.mux.active_mut().is_some_and(|instance| {
                         instance.set_follow_output(false);
+                        instance.move_select(direction, select, delta)
+                    });
+                    if wrapped {
+                        self.status.msg(Self::search_wrapped_msg(direction));
                     }
                 }
                 VisualAction::ToggleSelectedLine => {
@@ -342,6 +1027,23 @@ impl<'term> App<'term> {
                         instance.toggle_select_bookmarks();
                     }
                 }
+                VisualAction::InvertSelection => {
+                    if let Some(instance) = self.mux.active_mut() {
+                        instance.invert_bookmarks();
+                    }
+                }
+                VisualAction::SelectRange {
+                    target_view,
+                    anchor,
+                    index,
+                } => {
+                    let Some(instance) = self.mux.instances_mut().get_mut(target_view) else {
+                        return Ok(true);
+                    };
+                    instance.select_range(anchor, index);
+                    self.mux.move_active_index(target_view);
+                    self.mode = InputMode::Visual;
+                }
                 VisualAction::ToggleLine {
                     target_view,
                     line_number,
@@ -375,6 +1077,37 @@ impl<'term> App<'term> {
                         instance.remove_filters(selected_filters);
                     });
                 }
+                actions::FilterAction::CycleStrategy => {
+                    self.mux.demux_mut(self.linked_filters, |instance| {
+                        instance.set_composite_strategy(match instance.compositor().strategy() {
+                            CompositeStrategy::Union => CompositeStrategy::Intersection,
+                            CompositeStrategy::Intersection => CompositeStrategy::Union,
+                        });
+                    });
+                }
+                actions::FilterAction::CopySelectedFilter => {
+                    let Some(clipboard) = self.clipboard.as_mut() else {
+                        self.status
+                            .msg(String::from("copy: clipboard not available"));
+                        return Ok(true);
+                    };
+                    let Some((text, complete)) = self
+                        .mux
+                        .active_mut()
+                        .and_then(|instance| instance.selected_filter_string())
+                    else {
+                        return Ok(true);
+                    };
+                    match clipboard.set_text(text) {
+                        Ok(_) if complete => self
+                            .status
+                            .msg(String::from("copy: copied filter matches to clipboard")),
+                        Ok(_) => self.status.msg(String::from(
+                            "copy: copied the matches found so far, search is still running",
+                        )),
+                        Err(err) => self.status.msg(format!("copy: {err}")),
+                    }
+                }
                 actions::FilterAction::ToggleFilter {
                     target_view,
                     filter_index,
@@ -400,7 +1133,7 @@ impl<'term> App<'term> {
                     };
 
                     self.mux.demux_mut(self.linked_filters, |target| {
-                        target.import_user_filters(&export);
+                        target.import_user_filters(export);
                     });
                 }
                 actions::ConfigAction::RemoveSelectedFilter => {
@@ -421,6 +1154,12 @@ impl<'term> App<'term> {
                         select,
                         match jump {
                             actions::CommandJump::Word => prompt::PromptDelta::Word,
+                            // Only commands have a command word to skip over
+                            actions::CommandJump::Boundary
+                                if self.mode == InputMode::Prompt(PromptMode::Command) =>
+                            {
+                                prompt::PromptDelta::SmartBoundary
+                            }
                             actions::CommandJump::Boundary => prompt::PromptDelta::Boundary,
                             actions::CommandJump::None => prompt::PromptDelta::Number(1),
                         },
@@ -429,7 +1168,7 @@ impl<'term> App<'term> {
                 CommandAction::Type { input } => self.prompt.enter_char(input),
                 CommandAction::Paste { input } => self.prompt.enter_str(&input),
                 CommandAction::Backspace => {
-                    if !self.prompt.delete() {
+                    if !self.prompt.delete() && self.backspace_exits_prompt {
                         self.mode = InputMode::Normal;
                     }
                 }
@@ -445,11 +1184,30 @@ impl<'term> App<'term> {
                             let command = self.prompt.take();
                             Ok(self.process_search(&command, escaped, edit))
                         }
+                        InputMode::Prompt(PromptMode::Find { escaped }) => {
+                            self.mode = InputMode::Normal;
+                            let pattern = self.prompt.take();
+                            Ok(self.process_find(&pattern, escaped))
+                        }
                         InputMode::Prompt(PromptMode::Shell { pipe }) => {
                             self.mode = InputMode::Normal;
                             let command = self.prompt.take();
                             self.process_shell(&command, true, pipe)
                         }
+                        InputMode::Prompt(PromptMode::Help) => {
+                            let query = self.prompt.take();
+                            match self.help.selected(&query) {
+                                Some(HelpEntry::Command(help)) => {
+                                    self.mode = InputMode::Prompt(PromptMode::Command);
+                                    self.prompt.enter_str(help.command);
+                                    if !help.args.is_empty() && !help.command.is_empty() {
+                                        self.prompt.enter_char(' ');
+                                    }
+                                }
+                                Some(HelpEntry::Key(_)) | None => self.mode = InputMode::Normal,
+                            }
+                            Ok(true)
+                        }
                         InputMode::Normal
                         | InputMode::Visual
                         | InputMode::Filter
@@ -458,6 +1216,10 @@ impl<'term> App<'term> {
                     return result;
                 }
                 CommandAction::History { direction } => {
+                    if self.mode == InputMode::Prompt(PromptMode::Help) {
+                        self.help.move_select(direction, self.prompt.buf());
+                        return Ok(true);
+                    }
                     if self.mode != InputMode::Prompt(PromptMode::Command) {
                         return Ok(true);
                     }
@@ -466,9 +1228,16 @@ impl<'term> App<'term> {
                         Direction::Next => self.prompt.forward(),
                     }
                 }
-                CommandAction::Complete => (),
+                CommandAction::Complete { direction } => {
+                    if self.mode == InputMode::Prompt(PromptMode::Command) {
+                        let (mux, recent) = (&self.mux, &self.recent);
+                        self.prompt.complete(direction, |before| {
+                            completion_candidates(before, mux, recent)
+                        });
+                    }
+                }
             },
-            Action::ExportFile(path) => {
+            Action::ExportFile(path, options) => {
                 if let Some(instance) = self.mux.active_mut() {
                     if let Err(err) = OpenOptions::new()
                         .create_new(true)
@@ -476,7 +1245,44 @@ impl<'term> App<'term> {
                         .truncate(true)
                         .open(&path)
                         .map_err(Error::from)
-                        .and_then(|mut file| instance.write_bytes(&mut file))
+                        .and_then(|file| {
+                            let format = options
+                                .format
+                                .as_deref()
+                                .map(LineFormat::parse)
+                                .transpose()
+                                .map_err(|err| {
+                                    std::io::Error::new(std::io::ErrorKind::InvalidInput, err)
+                                })?;
+                            let mut write = |writer: &mut dyn std::io::Write| {
+                                let mut writer = writer;
+                                match &options.markdown {
+                                    Some(language) => write_fenced(
+                                        instance,
+                                        &mut writer,
+                                        format.as_ref(),
+                                        options.all,
+                                        language,
+                                    ),
+                                    None => write_export(
+                                        instance,
+                                        &mut writer,
+                                        format.as_ref(),
+                                        options.all,
+                                    ),
+                                }
+                            };
+                            if options.trim {
+                                let mut writer = TrimWriter::new(BufWriter::new(file), &options);
+                                write(&mut writer)?;
+                                writer.finish()?;
+                            } else {
+                                let mut writer = BufWriter::new(file);
+                                write(&mut writer)?;
+                                writer.into_inner().map_err(|err| err.into_error())?;
+                            }
+                            Ok(())
+                        })
                     {
                         self.status.msg(format!("{}: {err}", path.display()));
                     } else {
@@ -496,7 +1302,7 @@ impl<'term> App<'term> {
         match s {
             "SEL" | "sel" => {
                 if let Some(instance) = self.mux.active_mut() {
-                    match instance.export_string() {
+                    match instance.export_string(false) {
                         Ok(text) => Ok(Some(text.into())),
                         Err(err) => {
                             self.status.msg(format!("selection expansion: {err}"));
@@ -568,7 +1374,7 @@ impl<'term> App<'term> {
         if pipe {
             let mut stdin = child.stdin.take().unwrap();
             if let Some(instance) = self.mux.active_mut() {
-                instance.write_bytes(&mut stdin)?;
+                instance.write_bytes(&mut stdin, false)?;
             }
         }
 
@@ -617,13 +1423,38 @@ impl<'term> App<'term> {
         true
     }
 
+    fn process_find(&mut self, pat: &str, escaped: bool) -> bool {
+        let regex = if escaped {
+            regex_compile(&regex::escape(pat))
+        } else {
+            regex_compile(pat)
+        };
+        let regex = match regex {
+            Ok(regex) => regex,
+            Err(err) => {
+                self.status
+                    .msg(format!("{pat}: {}", regex_error_hint(&err)));
+                return true;
+            }
+        };
+
+        if let Some(instance) = self.mux.active_mut() {
+            match instance.find_backward(regex) {
+                Ok(true) => self.find = Some(pat.to_owned()),
+                Ok(false) => self.status.msg(format!("{pat}: no earlier match")),
+                Err(err) => self.status.msg(format!("{pat}: {err}")),
+            }
+        }
+        true
+    }
+
     fn process_command(&mut self, command: &str) -> bool {
         let mut parts = command.split_whitespace();
 
         match parts.next() {
             Some("quit" | "q") => return false,
             Some("mcap") => {
-                if let Err(_) = self.toggle_mouse_capture() {
+                if self.toggle_mouse_capture().is_err() {
                     self.status.msg("mouse capture toggle failed".to_string());
                 }
                 return true;
@@ -631,22 +1462,66 @@ impl<'term> App<'term> {
             Some("refresh") => {
                 self.refresh = true;
             }
+            Some("help" | "h") => {
+                let query = parts.collect::<Vec<_>>().join(" ");
+                self.help.reset();
+                self.prompt.take();
+                self.prompt.enter_str(&query);
+                self.mode = InputMode::Prompt(PromptMode::Help);
+            }
             Some("open" | "o") => {
-                let path = parts.collect::<PathBuf>();
+                let path = path_argument(split_word(command).1);
                 if let Err(err) = self.open_file(path.as_ref()) {
                     self.status.msg(format!("{}: {err}", path.display()));
                 }
             }
+            Some("debug") if !self.debug => {
+                self.status
+                    .msg(String::from("debug: start bvr with --debug to use it"));
+            }
+            Some("debug") => match parts.next() {
+                Some("perf") => self.perf = self.perf.is_none().then(PerfApp::default),
+                _ => self.status.msg(String::from("debug: expected `perf`")),
+            },
+            Some("messages" | "mes") => match parts.next() {
+                None => self.show_messages = !self.show_messages,
+                Some("clear") => self.status.clear_history(),
+                Some(arg) => self.status.msg(format!("messages {arg}: expected `clear`")),
+            },
             Some("pb" | "pbcopy") => {
                 let Some(clipboard) = self.clipboard.as_mut() else {
                     self.status
                         .msg("pbcopy: clipboard not available".to_string());
                     return true;
                 };
+                let mut whole_file = false;
+                let mut markdown = None;
+                for flag in parts {
+                    match flag {
+                        "--all" => whole_file = true,
+                        "--md" => markdown = Some(""),
+                        flag if flag.starts_with("--md=") => {
+                            markdown = Some(&flag["--md=".len()..])
+                        }
+                        flag => {
+                            self.status.msg(format!("pbcopy {flag}: unknown option"));
+                            return true;
+                        }
+                    }
+                }
                 if let Some(instance) = self.mux.active_mut() {
-                    match instance.export_string() {
+                    let text = instance
+                        .export_string(whole_file)
+                        .map(|text| match markdown {
+                            Some(language) => fence_text(&text, language),
+                            None => text,
+                        });
+                    match text {
                         Ok(text) => match clipboard.set_text(text) {
                             Ok(_) => {
+                                // Every line in view was copied, filtered or not
+                                let lines = 0..instance.file().line_count();
+                                instance.flash(lines, self.flash);
                                 self.status.msg("pbcopy: copied to clipboard".to_string());
                             }
                             Err(err) => {
@@ -659,26 +1534,113 @@ impl<'term> App<'term> {
                     }
                 }
             }
-            Some("close" | "c") => {
-                if self.mux.active_mut().is_some() {
-                    self.mux.close_active()
+            Some(command @ ("close" | "c" | "close!" | "c!")) => {
+                if let Some(instance) = self.mux.active_mut() {
+                    if let Some(path) = instance.path().map(Path::to_path_buf) {
+                        if command.ends_with('!') {
+                            self.closed.remove(&path);
+                        } else if let Some(position) = instance.position() {
+                            let filters = instance.compositor().filters().export(None);
+                            self.closed.insert(path, (position, filters));
+                        }
+                    }
+                    self.mux.close_active();
+                    // Views after the closed one shift down
+                    self.histogram = None;
                 } else {
                     self.status.msg(String::from("No active instances"));
                 }
             }
-            Some("gutter" | "g") => {
-                self.gutter = !self.gutter;
-            }
+            Some("gutter" | "g") => match parts.next() {
+                Some("delta" | "d") => self.time_delta = !self.time_delta,
+                Some("all" | "a") => {
+                    self.gutter = !self.gutter;
+                    for instance in self.mux.instances_mut() {
+                        instance.set_gutter(None);
+                    }
+                }
+                Some(kind) => {
+                    self.status.msg(format!(
+                        "gutter {kind}: invalid gutter, expected `all`, `delta`"
+                    ));
+                }
+                None => {
+                    if let Some(instance) = self.mux.active_mut() {
+                        let gutter = instance.gutter(self.gutter);
+                        instance.set_gutter(Some(!gutter));
+                    }
+                }
+            },
+            Some("selection" | "sel") => match parts.next() {
+                Some("bg" | "background") => self.selection_style = SelectionStyle::Background,
+                Some("reverse" | "rev") => self.selection_style = SelectionStyle::Reverse,
+                Some(style) => {
+                    self.status.msg(format!(
+                        "selection {style}: invalid style, one of `bg`, `reverse`"
+                    ));
+                }
+                None => {
+                    self.selection_style = match self.selection_style {
+                        SelectionStyle::Background => SelectionStyle::Reverse,
+                        SelectionStyle::Reverse => SelectionStyle::Background,
+                    }
+                }
+            },
+            Some("prompt") => match (parts.next(), parts.next()) {
+                (Some("backspace"), Some("exit")) => self.backspace_exits_prompt = true,
+                (Some("backspace"), Some("stay")) => self.backspace_exits_prompt = false,
+                (Some("esc"), Some("discard")) => self.esc_keeps_search = false,
+                (Some("esc"), Some("keep")) => self.esc_keeps_search = true,
+                (Some("backspace"), _) => self
+                    .status
+                    .msg(String::from("prompt backspace: one of `exit`, `stay`")),
+                (Some("esc"), _) => self
+                    .status
+                    .msg(String::from("prompt esc: one of `discard`, `keep`")),
+                _ => self
+                    .status
+                    .msg(String::from("prompt: one of `backspace`, `esc`")),
+            },
+            Some("set") => match (parts.next(), parts.next()) {
+                (None | Some("all"), _) => self.status.msg(options::describe_all(self)),
+                (Some(arg), value) => match options::apply(self, arg, value) {
+                    Ok(Some(msg)) => self.status.msg(msg),
+                    Ok(None) => {}
+                    Err(err) => self.status.msg(format!("set {err}")),
+                },
+            },
+            Some("status") => match parts.next() {
+                Some("reset") | None => self.status_layout = StatusLayout::default(),
+                Some(layout) => match layout.parse() {
+                    Ok(layout) => self.status_layout = layout,
+                    Err(err) => self.status.msg(format!("status: {err}")),
+                },
+            },
             Some("mux" | "m") => match parts.next() {
                 Some("tabs" | "t" | "none") => self.mux.set_mode(MultiplexerMode::Tabs),
+                Some("split" | "s" | "win") if !self.mux.can_split() => {
+                    self.status
+                        .msg(String::from("mux split: too many instances to split"));
+                }
                 Some("split" | "s" | "win") => self.mux.set_mode(MultiplexerMode::Panes),
                 Some(style) => {
                     self.status.msg(format!(
                         "mux {style}: invalid style, one of `tabs`, `split`"
                     ));
                 }
+                None if self.mux.mode() == MultiplexerMode::Tabs && !self.mux.can_split() => {
+                    self.status
+                        .msg(String::from("mux: too many instances to split"));
+                }
                 None => self.mux.set_mode(self.mux.mode().swap()),
             },
+            Some("filters") => match parts.next() {
+                Some("show") => self.filter_strip = !self.filter_strip,
+                Some("hide") => self.filter_strip = false,
+                _ => self.status.msg(String::from(
+                    "filters: requires subcommand, one of `show`, `hide`",
+                )),
+            },
             Some("filter" | "find" | "f") => match parts.next() {
                 Some("link") => {
                     self.linked_filters = !self.linked_filters;
@@ -687,6 +1649,26 @@ impl<'term> App<'term> {
                     }
                     return true;
                 }
+                Some("refresh") => {
+                    // Linked filters are the same on every view
+                    let refreshed = if self.linked_filters {
+                        self.mux
+                            .instances_mut()
+                            .iter_mut()
+                            .map(Instance::refresh_filters)
+                            .max()
+                    } else {
+                        self.mux.active_mut().map(Instance::refresh_filters)
+                    };
+                    match refreshed {
+                        None | Some(0) => self
+                            .status
+                            .msg(String::from("filter refresh: no pattern filters")),
+                        Some(n) => self.status.msg(format!(
+                            "filter refresh: searching again with {n} filter(s)"
+                        )),
+                    }
+                }
                 Some("persist") => {
                     let new_persistence = !self.filter_config.is_persistent();
 
@@ -734,7 +1716,8 @@ impl<'term> App<'term> {
                     let Some(source) = self.mux.active_mut() else {
                         return true;
                     };
-                    let name: String = parts.collect::<Vec<&str>>().join(" ");
+                    let args = split_word(split_word(command).1).1;
+                    let name = text_argument(args).to_owned();
                     let export = source.compositor_mut().filters().export(Some(name));
 
                     if let Err(err) = self.filter_config.add_filter(export) {
@@ -761,6 +1744,25 @@ impl<'term> App<'term> {
                         instance.set_composite_strategy(CompositeStrategy::Intersection);
                     });
                 }
+                Some(_) if query::is_query(split_word(command).1) => {
+                    let query = match query::parse(split_word(command).1) {
+                        Ok(query) => query,
+                        Err(err) => {
+                            self.status.msg(format!("filter: {err}"));
+                            return true;
+                        }
+                    };
+                    let mut result = Ok(());
+                    self.mux.demux_mut(self.linked_filters, |instance| {
+                        if result.is_ok() {
+                            result = instance.apply_query(&query);
+                        }
+                    });
+                    if let Err(err) = result {
+                        self.status
+                            .msg(format!("filter: {}", regex_error_hint(&err)));
+                    }
+                }
                 Some(cmd) => {
                     self.status.msg(format!("filter {cmd}: invalid subcommand"));
                 }
@@ -770,20 +1772,464 @@ impl<'term> App<'term> {
                     );
                 }
             },
+            Some("dim") => self.dim = !self.dim,
+            Some("wrap") => self.wrap = !self.wrap,
+            Some("linelen") => self.line_lengths = !self.line_lengths,
+            Some("raw") => {
+                if let Some(instance) = self.mux.active_mut() {
+                    let raw = instance.toggle_raw();
+                    self.status
+                        .msg(format!("raw: {}", if raw { "on" } else { "off" }));
+                }
+            }
+            Some("ruler") => {
+                let columns = parts.collect::<Vec<_>>().join(",");
+                let rulers = columns
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|column| !column.is_empty() && *column != "off")
+                    .map(|column| column.parse::<usize>().ok().filter(|&c| c > 0))
+                    .collect::<Option<Vec<_>>>();
+                match rulers {
+                    Some(rulers) => self.rulers = rulers,
+                    None => self
+                        .status
+                        .msg(format!("ruler {columns}: columns are numbers from 1")),
+                }
+            }
+            Some("header") => {
+                // Taken verbatim so that the pattern may contain spaces
+                let pattern = command.trim_start()["header".len()..].trim();
+                let regex = match pattern {
+                    "" => None,
+                    pattern => match regex_compile(pattern) {
+                        Ok(regex) => Some(regex),
+                        Err(err) => {
+                            self.status
+                                .msg(format!("header {pattern}: {}", regex_error_hint(&err)));
+                            return true;
+                        }
+                    },
+                };
+                if let Some(instance) = self.mux.active_mut() {
+                    if let Err(err) = instance.set_header(regex) {
+                        self.status.msg(format!("header {pattern}: {err}"));
+                    }
+                }
+            }
+            Some("count") => {
+                // Taken verbatim so that the pattern may contain spaces
+                let pattern = command.trim_start()["count".len()..].trim();
+                if pattern.is_empty() {
+                    self.status.msg(String::from("count: requires a pattern"));
+                    return true;
+                }
+                let regex = match regex_compile(pattern) {
+                    Ok(regex) => regex,
+                    Err(err) => {
+                        self.status
+                            .msg(format!("count {pattern}: {}", regex_error_hint(&err)));
+                        return true;
+                    }
+                };
+                let Some(instance) = self.mux.active_mut() else {
+                    return true;
+                };
+                match instance.count_matches(regex) {
+                    Ok(count) => self.count = Some((pattern.to_owned(), count)),
+                    Err(err) => self.status.msg(format!("count {pattern}: {err}")),
+                }
+            }
+            Some("diff") => match parts.next() {
+                Some("off") => {
+                    for instance in self.mux.instances_mut() {
+                        if instance.compositor_mut().clear_diff_filter() {
+                            instance.invalidate_cache();
+                        }
+                    }
+                }
+                idx => {
+                    let active = self.mux.active_index();
+                    let other = match idx.map(str::parse::<usize>) {
+                        Some(Ok(idx)) => idx.saturating_sub(1),
+                        Some(Err(_)) => {
+                            self.status
+                                .msg(format!("diff {}: invalid index", idx.unwrap_or_default()));
+                            return true;
+                        }
+                        // With two views, the other one is implied
+                        None if self.mux.len() == 2 => 1 - active,
+                        None => {
+                            self.status
+                                .msg(String::from("diff: requires instance index"));
+                            return true;
+                        }
+                    };
+                    if other == active {
+                        self.status.msg(String::from(
+                            "diff: cannot diff the active instance with itself",
+                        ));
+                        return true;
+                    }
+                    let instances = self.mux.instances_mut();
+                    if other >= instances.len() {
+                        self.status
+                            .msg(format!("diff {}: invalid index", other + 1));
+                        return true;
+                    }
+
+                    let old = diff::line_hashes(&instances[active]);
+                    let new = diff::line_hashes(&instances[other]);
+                    let Some((removed, added)) = diff::diff(&old, &new, diff::MAX_EDITS) else {
+                        self.status
+                            .msg(format!("diff: more than {} lines differ", diff::MAX_EDITS));
+                        return true;
+                    };
+
+                    let truncated = old.len() == diff::MAX_LINES || new.len() == diff::MAX_LINES;
+                    let msg = format!(
+                        "diff: {} removed, {} added lines{}",
+                        removed.len(),
+                        added.len(),
+                        if truncated {
+                            format!(" in the first {} lines", diff::MAX_LINES)
+                        } else {
+                            String::new()
+                        }
+                    );
+                    let old_name = instances[active].name().to_owned();
+                    let new_name = instances[other].name().to_owned();
+                    for (index, name, color, lines) in [
+                        (
+                            active,
+                            format!("Removed in {new_name}"),
+                            colors::DIFF_REMOVED,
+                            removed,
+                        ),
+                        (
+                            other,
+                            format!("Added to {old_name}"),
+                            colors::DIFF_ADDED,
+                            added,
+                        ),
+                    ] {
+                        let instance = &mut instances[index];
+                        instance
+                            .compositor_mut()
+                            .set_diff_filter(name, color, lines);
+                        instance.invalidate_cache();
+                    }
+                    self.status.msg(msg);
+                }
+            },
+            Some("hist") => {
+                // Taken verbatim so that the pattern may contain spaces
+                let pattern = command.trim_start()["hist".len()..].trim();
+                if pattern.is_empty() {
+                    self.histogram = None;
+                    return true;
+                }
+                let regex = match regex_compile(pattern) {
+                    Ok(regex) => regex,
+                    Err(err) => {
+                        self.status
+                            .msg(format!("hist {pattern}: {}", regex_error_hint(&err)));
+                        return true;
+                    }
+                };
+                if self.mux.is_empty() {
+                    self.status.msg(String::from("No active instances"));
+                } else {
+                    self.histogram = Some(HistogramApp::new(regex, self.mux.active_index()));
+                }
+            }
+            Some("json") => {
+                let fields = parts.collect::<Vec<_>>().join(",");
+                match fields.as_str() {
+                    "" if self.json_fields.is_empty() => {
+                        self.status
+                            .msg(String::from("json: requires a list of fields"));
+                    }
+                    "" => self.json = !self.json,
+                    "off" => self.json = false,
+                    fields => {
+                        self.json_fields = fields
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|field| !field.is_empty())
+                            .map(String::from)
+                            .collect();
+                        self.json = !self.json_fields.is_empty();
+                    }
+                }
+            }
+            Some("buffer" | "b") => {
+                let query = parts.collect::<Vec<_>>().join(" ");
+                if query.is_empty() {
+                    self.status.msg(String::from("buffer: requires a name"));
+                    return true;
+                }
+                match self.mux.find_by_name(&query)[..] {
+                    [] => self.status.msg(format!("buffer {query}: no match")),
+                    [index] => self.mux.move_active_index(index),
+                    ref candidates => {
+                        let names = candidates
+                            .iter()
+                            .map(|&i| format!("{}:{}", i + 1, self.mux.instances_mut()[i].name()))
+                            .collect::<Vec<_>>();
+                        self.status.msg(format!(
+                            "buffer {query}: ambiguous, candidates are {}",
+                            names.join(", ")
+                        ));
+                    }
+                }
+            }
+            Some("recent") => {
+                let query = parts.collect::<Vec<_>>().join(" ");
+                let files = self.recent.files().collect::<Vec<_>>();
+                let candidates = if query.is_empty() {
+                    (0..files.len()).collect()
+                } else if let Ok(n) = query.parse::<usize>() {
+                    (1..=files.len())
+                        .contains(&n)
+                        .then_some(n - 1)
+                        .into_iter()
+                        .collect()
+                } else {
+                    self.recent.find(&query)
+                };
+                match candidates[..] {
+                    [] if query.is_empty() => {
+                        self.status.msg(String::from("recent: no recent files"))
+                    }
+                    [] => self.status.msg(format!("recent {query}: no match")),
+                    [i] if !query.is_empty() => {
+                        let path = files[i].to_path_buf();
+                        if let Err(err) = self.open_file(&path) {
+                            self.status.msg(format!("{}: {err}", path.display()));
+                        }
+                    }
+                    ref candidates => {
+                        let names = candidates
+                            .iter()
+                            .map(|&i| format!("{}:{}", i + 1, files[i].display()))
+                            .collect::<Vec<_>>();
+                        self.status.msg(format!("recent: {}", names.join(", ")));
+                    }
+                }
+            }
+            Some("marks") => {
+                let (subcommand, rest) = split_word(split_word(command).1);
+                let path = path_argument(rest);
+                if !matches!(subcommand, "save" | "load") {
+                    self.status.msg(String::from(
+                        "marks: requires subcommand, one of `save`, `load`",
+                    ));
+                    return true;
+                } else if path.as_os_str().is_empty() {
+                    self.status
+                        .msg(format!("marks {subcommand}: requires a file name"));
+                    return true;
+                }
+                let Some(instance) = self.mux.active_mut() else {
+                    return true;
+                };
+
+                if subcommand == "save" {
+                    let bookmarks = instance.compositor().filters().bookmarks();
+                    let count = bookmarks.len();
+                    let result = std::fs::File::create(&path)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|file| {
+                            Ok(serde_json::to_writer_pretty(
+                                BufWriter::new(file),
+                                &bookmarks.export(),
+                            )?)
+                        });
+                    self.status.msg(match result {
+                        Ok(()) => format!("marks save: saved {count} bookmarks"),
+                        Err(err) => format!("marks save: {err}"),
+                    });
+                    return true;
+                }
+
+                match load_bookmarks(&path) {
+                    Ok(export) => {
+                        let (added, skipped) = instance.import_bookmarks(&export);
+                        self.status.msg(if skipped == 0 {
+                            format!("marks load: loaded {added} bookmarks")
+                        } else {
+                            format!(
+                                "marks load: loaded {added} bookmarks, \
+                                 skipped {skipped} past the end of the file"
+                            )
+                        });
+                    }
+                    Err(err) => self.status.msg(format!("marks load: {err}")),
+                }
+            }
+            Some("bookmarks") => match parts.next() {
+                Some("only") => {
+                    self.mux.demux_mut(self.linked_filters, |instance| {
+                        instance.show_only_bookmarks();
+                    });
+                }
+                Some("off") => {
+                    let mut restored = false;
+                    self.mux.demux_mut(self.linked_filters, |instance| {
+                        restored |= instance.restore_filters();
+                    });
+                    if !restored {
+                        self.status
+                            .msg(String::from("bookmarks off: not showing only bookmarks"));
+                    }
+                }
+                Some(cmd) => self
+                    .status
+                    .msg(format!("bookmarks {cmd}: invalid subcommand")),
+                None => self.status.msg(String::from(
+                    "bookmarks: requires subcommand, one of `only`, `off`",
+                )),
+            },
+            Some("branch") => {
+                let Some(instance) = self.mux.active_mut() else {
+                    return true;
+                };
+                let branch = match instance.try_branch() {
+                    Ok(branch) => branch,
+                    Err(Error::IndexIncomplete) => {
+                        self.status.msg(String::from(
+                            "branch: cannot branch a stream that is still loading",
+                        ));
+                        return true;
+                    }
+                    Err(err) => {
+                        self.status.msg(format!("branch: {err}"));
+                        return true;
+                    }
+                };
+                if self.push_instance(branch) {
+                    self.mux.move_active_index(self.mux.len() - 1);
+                }
+            }
+            Some("byte") => {
+                let Some(offset) = parts.next().and_then(|part| part.parse::<u64>().ok()) else {
+                    self.status.msg(String::from("byte: expected an offset"));
+                    return true;
+                };
+                if let Some(instance) = self.mux.active_mut() {
+                    instance.jump_to(PendingJump::Byte(offset));
+                }
+            }
+            Some("col") => {
+                let Some(column) = parts
+                    .next()
+                    .and_then(|part| part.parse::<usize>().ok())
+                    .filter(|&column| column > 0)
+                else {
+                    self.status
+                        .msg(String::from("col: expected a column number from 1"));
+                    return true;
+                };
+                let Some(instance) = self.mux.active_mut() else {
+                    return true;
+                };
+                match instance.scroll_to_column(column - 1) {
+                    Some(left) if left + 1 < column => self.status.msg(format!(
+                        "col {column}: the line ends at column {}",
+                        left + 1
+                    )),
+                    Some(_) => {}
+                    None => self.status.msg(format!("col {column}: no line selected")),
+                }
+            }
             Some("export") => {
-                let path = parts.collect::<PathBuf>();
+                let mut options = ExportOptions::default();
+                let mut args = split_word(command).1;
+                loop {
+                    let (flag, rest) = split_word(args);
+                    if !flag.starts_with("--") || flag == "--" {
+                        break;
+                    }
+                    args = rest;
+                    match flag {
+                        "--trim" => options.trim = true,
+                        "--squeeze" => {
+                            options.trim = true;
+                            options.squeeze = true;
+                        }
+                        "--all" => options.all = true,
+                        "--md" => options.markdown = Some(String::new()),
+                        flag if flag.starts_with("--md=") => {
+                            options.markdown = Some(flag["--md=".len()..].to_owned());
+                        }
+                        "--offsets" => options.format = Some(LineFormat::OFFSETS.to_owned()),
+                        "--format" => {
+                            let (format, rest) = split_word(args);
+                            if format.is_empty() {
+                                self.status
+                                    .msg(String::from("export --format: requires a template"));
+                                return true;
+                            }
+                            if let Err(err) = LineFormat::parse(format) {
+                                self.status.msg(format!("export --format {format}: {err}"));
+                                return true;
+                            }
+                            options.format = Some(format.to_owned());
+                            args = rest;
+                        }
+                        _ => {
+                            self.status.msg(format!("export {flag}: unknown option"));
+                            return true;
+                        }
+                    }
+                }
+                let path = path_argument(args);
+                if path.as_os_str().is_empty() {
+                    self.status
+                        .msg(String::from("export: requires a file name"));
+                    return true;
+                }
                 self.status.msg(format!(
                     "{}: export starting (this may take a while...)",
                     path.display()
                 ));
-                self.action_queue.push_back(Action::ExportFile(path));
+                self.action_queue
+                    .push_back(Action::ExportFile(path, options));
+            }
+            Some("w" | "write") => {
+                let path = path_argument(split_word(command).1);
+                if path.as_os_str().is_empty() {
+                    self.status.msg(String::from("write: requires a file name"));
+                    return true;
+                }
+                self.status.msg(format!(
+                    "{}: write starting (this may take a while...)",
+                    path.display()
+                ));
+                // The whole buffer as read, regardless of the filters
+                let options = ExportOptions {
+                    all: true,
+                    ..ExportOptions::default()
+                };
+                self.action_queue
+                    .push_back(Action::ExportFile(path, options));
             }
             Some(cmd) => {
                 if let Ok(line_number) = cmd.parse::<usize>() {
                     if let Some(instance) = self.mux.active_mut() {
-                        if let Some(idx) = instance.nearest_index(line_number) {
-                            instance.viewport_mut().jump_vertically_to(idx);
-                        }
+                        // Lines that are not indexed yet are sought as indexing proceeds
+                        let line_number = line_number.saturating_sub(instance.line_offset());
+                        instance.jump_to(PendingJump::Line(line_number));
+                    }
+                } else if let Some(percent) = cmd
+                    .strip_suffix('%')
+                    .and_then(|percent| percent.parse::<f64>().ok())
+                    .filter(|percent| (0.0..=100.0).contains(percent))
+                {
+                    if let Some(instance) = self.mux.active_mut() {
+                        // Percentages of a growing input are re-resolved until indexing completes
+                        instance.jump_to(PendingJump::Fraction(percent / 100.0));
                     }
                 } else {
                     self.status.msg(format!("{cmd}: Invalid command"))
@@ -796,11 +2242,69 @@ impl<'term> App<'term> {
     }
 
     fn ui(&mut self, handler: &mut MouseHandler) -> Option<(u16, u16)> {
+        match self
+            .mux
+            .active_mut()
+            .and_then(|instance| Some((instance.pending_jump()?, instance.line_offset())))
+        {
+            Some((PendingJump::Line(line_number), line_offset)) => self
+                .status
+                .progress(format!("seeking to line {}…", line_number + line_offset)),
+            Some((PendingJump::Byte(offset), _)) => {
+                self.status.progress(format!("seeking to byte {offset}…"))
+            }
+            Some((PendingJump::Tail(count), _)) => self
+                .status
+                .progress(format!("seeking to the last {count} lines…")),
+            Some((PendingJump::Fraction(_), _)) | None => {}
+        }
+
+        if let Some((pattern, count)) = &self.count {
+            let lines = self.numbers.int(count.count());
+            if count.is_complete() {
+                self.status
+                    .msg(format!("count {pattern}: {lines} matching lines"));
+                self.count = None;
+            } else {
+                self.status.progress(format!(
+                    "count {pattern}: {lines} lines so far… (Ctrl-C to stop)"
+                ));
+            }
+        }
+
+        if let Some(pattern) = &self.find {
+            match self.mux.active_mut() {
+                Some(instance) if instance.is_finding() => match instance.resolve_find() {
+                    Some(true) => self.find = None,
+                    Some(false) => {
+                        self.status.msg(format!("{pattern}: no earlier match"));
+                        self.find = None;
+                    }
+                    None => self.status.progress(format!("{pattern}: searching…")),
+                },
+                // Another view was made active
+                _ => self.find = None,
+            }
+        }
+
+        if let Some(histogram) = &mut self.histogram {
+            match self.mux.instances_mut().get(histogram.view_index()) {
+                Some(instance) => histogram.step(instance),
+                None => self.histogram = None,
+            }
+        }
+
+        if let Some(perf) = &mut self.perf {
+            perf.sample(self.mux.active_mut().map(|v| &*v), self.count.is_some());
+        }
+
         let mut f = self.term.get_frame();
         let [mux_chunk, cmd_chunk] = MultiplexerWidget::split_bottom(f.area(), 1);
 
         match self.mode {
-            InputMode::Prompt(PromptMode::Search { escaped, .. }) => {
+            InputMode::Prompt(
+                PromptMode::Search { escaped, .. } | PromptMode::Find { escaped },
+            ) => {
                 let pattern = self.prompt.buf();
 
                 let pattern_mismatch = self
@@ -815,12 +2319,18 @@ impl<'term> App<'term> {
                     } else {
                         regex_compile(&regex::escape(pattern))
                     }
-                    .ok();
+                    .map_err(|err| regex_error_hint(&err));
+                    // Replacing the cache cancels the count of the last pattern
+                    let count = regex.as_ref().ok().and_then(|regex| {
+                        let instance = self.mux.active_mut()?;
+                        instance.count_matches(regex.clone()).ok()
+                    });
 
                     self.regex_cache = Some(RegexCache {
                         pattern: pattern.to_owned(),
                         escaped,
                         regex,
+                        count,
                     })
                 }
             }
@@ -838,12 +2348,31 @@ impl<'term> App<'term> {
             status: &mut self.status,
             mode: self.mode,
             config: &mut self.filter_config,
+            help: &mut self.help,
+            help_query: self.prompt.buf(),
+            filter_strip: self.filter_strip,
             gutter: self.gutter,
+            time_delta: self.time_delta,
+            line_lengths: self.line_lengths,
+            selection_style: self.selection_style,
+            dim: self.dim,
+            wrap: self.wrap,
+            flash_color: self.flash_color,
+            json_fields: self.json.then_some(self.json_fields.as_slice()),
+            rulers: &self.rulers,
+            text_markers: self.accessible,
             linked_filters: self.linked_filters,
+            status_layout: &self.status_layout,
+            numbers: self.numbers,
+            histogram: self.histogram.as_ref(),
+            peek: self.peek.as_ref(),
+            show_messages: self.show_messages,
+            perf: self.perf.as_ref(),
+            theme: self.theme,
             regex: self
                 .regex_cache
                 .as_ref()
-                .and_then(|cache| cache.regex.as_ref()),
+                .and_then(|cache| cache.regex.as_ref().ok()),
         }
         .render(mux_chunk, f.buffer_mut(), handler);
 
@@ -851,10 +2380,99 @@ impl<'term> App<'term> {
         PromptWidget {
             mode: self.mode,
             inner: &mut self.prompt,
+            error: self
+                .regex_cache
+                .as_ref()
+                .and_then(|cache| cache.regex.as_ref().err())
+                .map(String::as_str),
+            matches: self
+                .regex_cache
+                .as_ref()
+                .and_then(|cache| cache.count.as_ref())
+                .map(|count| {
+                    let lines = self.numbers.int(count.count());
+                    if count.is_complete() {
+                        format!("{lines} matching lines")
+                    } else {
+                        format!("{lines} matching lines so far…")
+                    }
+                }),
             cursor: &mut cursor,
         }
         .render(cmd_chunk, f.buffer_mut());
 
+        if self.accessible {
+            colors::monochrome(f.buffer_mut());
+        }
+
         cursor
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn path_argument_keeps_spaces() {
+        assert_eq!(
+            path_argument(split_word("open my log.txt").1),
+            Path::new("my log.txt")
+        );
+        assert_eq!(
+            path_argument(split_word("o   logs/a  b.log  ").1),
+            Path::new("logs/a  b.log")
+        );
+    }
+
+    #[test]
+    fn path_argument_strips_quotes() {
+        assert_eq!(
+            path_argument(split_word("open \"my log.txt\"").1),
+            Path::new("my log.txt")
+        );
+        assert_eq!(
+            path_argument(split_word("open 'it''s.log'").1),
+            Path::new("it''s.log")
+        );
+        assert_eq!(
+            path_argument(split_word("open \"unbalanced.log").1),
+            Path::new("\"unbalanced.log")
+        );
+    }
+
+    #[test]
+    fn open_path_describes_failures() {
+        let err = |path: &str| open_path(Path::new(path)).unwrap_err().to_string();
+        assert_eq!(err("definitely/not/a/file.log"), "no such file");
+        assert_eq!(err(env!("CARGO_MANIFEST_DIR")), "is a directory");
+        #[cfg(unix)]
+        assert!(open_path(Path::new("/dev/null")).is_ok());
+    }
+
+    #[test]
+    fn complete_path_lists_matching_entries() {
+        let root = env!("CARGO_MANIFEST_DIR");
+        assert_eq!(
+            complete_path(&format!("{root}/cra")),
+            [format!("{root}/crates/")]
+        );
+        assert_eq!(
+            complete_path(&format!("{root}/crates/cli/main")),
+            [format!("{root}/crates/cli/main.rs")]
+        );
+        assert!(complete_path(&format!("{root}/crates/cli/nothing")).is_empty());
+    }
+
+    #[test]
+    fn path_argument_ends_options() {
+        assert_eq!(
+            path_argument(split_word("open -- -weird.log").1),
+            Path::new("-weird.log")
+        );
+        assert_eq!(
+            path_argument(split_word("export -- \"--all\"").1),
+            Path::new("--all")
+        );
+    }
+}
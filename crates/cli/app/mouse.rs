@@ -1,10 +1,18 @@
 use super::actions::Action;
-use crossterm::event::{Event, MouseEvent};
+use crossterm::event::{Event, MouseEvent, MouseEventKind};
 use ratatui::layout::Rect;
 
 pub struct MouseHandler {
     event: Option<Event>,
     action: Option<super::actions::Action>,
+    drag_anchor: Option<DragAnchor>,
+}
+
+/// Where a mouse drag started, as a view index and a row within that view.
+#[derive(Clone, Copy, PartialEq)]
+pub struct DragAnchor {
+    pub view_index: usize,
+    pub index: usize,
 }
 
 impl MouseHandler {
@@ -12,9 +20,20 @@ impl MouseHandler {
         Self {
             event: None,
             action: None,
+            drag_anchor: None,
         }
     }
 
+    #[inline]
+    pub fn drag_anchor(&self) -> Option<DragAnchor> {
+        self.drag_anchor
+    }
+
+    #[inline]
+    pub fn set_drag_anchor(&mut self, anchor: DragAnchor) {
+        self.drag_anchor = Some(anchor);
+    }
+
     #[inline]
     pub fn publish_event(&mut self, event: Event) {
         self.event = Some(event);
@@ -41,6 +60,13 @@ impl MouseHandler {
 
     #[inline]
     pub fn extract(&mut self) -> Option<Action> {
+        if let Some(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Up(_),
+            ..
+        })) = self.event
+        {
+            self.drag_anchor = None;
+        }
         self.event = None;
         self.action.take()
     }
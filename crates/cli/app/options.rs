@@ -0,0 +1,254 @@
+use super::{control::SelectionStyle, App};
+use crate::numbers::NumberFormat;
+use anyhow::{anyhow, Result};
+
+/// A setting that can be read and changed at runtime with `:set`.
+pub(super) struct SetOption {
+    name: &'static str,
+    kind: OptionKind,
+}
+
+enum OptionKind {
+    Bool {
+        get: fn(&App<'_>) -> bool,
+        set: fn(&mut App<'_>, bool) -> Result<()>,
+    },
+    Choice {
+        values: &'static [&'static str],
+        get: fn(&App<'_>) -> &'static str,
+        set: fn(&mut App<'_>, &str),
+    },
+}
+
+/// Every option known to `:set`, new runtime settings should be added here.
+pub(super) const OPTIONS: &[SetOption] = &[
+    SetOption {
+        name: "gutter",
+        kind: OptionKind::Bool {
+            get: |app| app.gutter,
+            set: |app, value| {
+                app.gutter = value;
+                for instance in app.mux.instances_mut() {
+                    instance.set_gutter(None);
+                }
+                Ok(())
+            },
+        },
+    },
+    SetOption {
+        name: "delta",
+        kind: OptionKind::Bool {
+            get: |app| app.time_delta,
+            set: |app, value| {
+                app.time_delta = value;
+                Ok(())
+            },
+        },
+    },
+    SetOption {
+        name: "dim",
+        kind: OptionKind::Bool {
+            get: |app| app.dim,
+            set: |app, value| {
+                app.dim = value;
+                Ok(())
+            },
+        },
+    },
+    SetOption {
+        name: "linelen",
+        kind: OptionKind::Bool {
+            get: |app| app.line_lengths,
+            set: |app, value| {
+                app.line_lengths = value;
+                Ok(())
+            },
+        },
+    },
+    SetOption {
+        name: "wrap",
+        kind: OptionKind::Bool {
+            get: |app| app.wrap,
+            set: |app, value| {
+                app.wrap = value;
+                Ok(())
+            },
+        },
+    },
+    SetOption {
+        name: "mouse",
+        kind: OptionKind::Bool {
+            get: |app| app.mouse_capture,
+            set: |app, value| {
+                if app.mouse_capture != value {
+                    app.toggle_mouse_capture()?;
+                }
+                Ok(())
+            },
+        },
+    },
+    SetOption {
+        name: "link",
+        kind: OptionKind::Bool {
+            get: |app| app.linked_filters,
+            set: |app, value| {
+                if !app.linked_filters && value {
+                    app.replicate_filters_on_all_instances();
+                }
+                app.linked_filters = value;
+                Ok(())
+            },
+        },
+    },
+    SetOption {
+        name: "selection",
+        kind: OptionKind::Choice {
+            values: &["bg", "reverse"],
+            get: |app| match app.selection_style {
+                SelectionStyle::Background => "bg",
+                SelectionStyle::Reverse => "reverse",
+            },
+            set: |app, value| {
+                app.selection_style = match value {
+                    "bg" => SelectionStyle::Background,
+                    _ => SelectionStyle::Reverse,
+                }
+            },
+        },
+    },
+    SetOption {
+        name: "backspace",
+        kind: OptionKind::Choice {
+            values: &["exit", "stay"],
+            get: |app| {
+                if app.backspace_exits_prompt {
+                    "exit"
+                } else {
+                    "stay"
+                }
+            },
+            set: |app, value| app.backspace_exits_prompt = value == "exit",
+        },
+    },
+    SetOption {
+        name: "esc",
+        kind: OptionKind::Choice {
+            values: &["discard", "keep"],
+            get: |app| {
+                if app.esc_keeps_search {
+                    "keep"
+                } else {
+                    "discard"
+                }
+            },
+            set: |app, value| app.esc_keeps_search = value == "keep",
+        },
+    },
+    SetOption {
+        name: "thousands",
+        kind: OptionKind::Choice {
+            values: NumberFormat::NAMES,
+            get: |app| app.numbers.name(),
+            set: |app, value| {
+                if let Ok(numbers) = value.parse() {
+                    app.numbers = numbers;
+                }
+            },
+        },
+    },
+];
+
+impl SetOption {
+    fn find(name: &str) -> Result<&'static Self> {
+        OPTIONS
+            .iter()
+            .find(|option| option.name == name)
+            .ok_or_else(|| anyhow!("{name}: unknown option"))
+    }
+
+    fn value(&self, app: &App<'_>) -> String {
+        match self.kind {
+            OptionKind::Bool { get, .. } => String::from(if get(app) { "on" } else { "off" }),
+            OptionKind::Choice { get, .. } => String::from(get(app)),
+        }
+    }
+
+    fn set(&self, app: &mut App<'_>, value: &str) -> Result<()> {
+        match self.kind {
+            OptionKind::Bool { set, .. } => {
+                let value = match value {
+                    "on" | "true" | "yes" | "1" => true,
+                    "off" | "false" | "no" | "0" => false,
+                    _ => return Err(anyhow!("{}={value}: expected `on` or `off`", self.name)),
+                };
+                set(app, value)
+            }
+            OptionKind::Choice { values, set, .. } => {
+                if !values.contains(&value) {
+                    return Err(anyhow!(
+                        "{}={value}: one of `{}`",
+                        self.name,
+                        values.join("`, `")
+                    ));
+                }
+                set(app, value);
+                Ok(())
+            }
+        }
+    }
+
+    fn toggle(&self, app: &mut App<'_>) -> Result<()> {
+        match self.kind {
+            OptionKind::Bool { get, set } => set(app, !get(app)),
+            OptionKind::Choice { .. } => Err(anyhow!("{}!: only on/off options toggle", self.name)),
+        }
+    }
+}
+
+/// Every option and its current value, as listed by `:set all`.
+pub(super) fn describe_all(app: &App<'_>) -> String {
+    OPTIONS
+        .iter()
+        .map(|option| format!("{}={}", option.name, option.value(app)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Applies one `:set` argument in the style of Vim, returning a message to
+/// show if there is one:
+/// - `name` turns an option on, or shows the value of a multiple choice one
+/// - `noname` turns an option off, `name!` toggles it
+/// - `name?` shows the value
+/// - `name=value` or `name value` sets the value
+pub(super) fn apply(app: &mut App<'_>, arg: &str, next: Option<&str>) -> Result<Option<String>> {
+    if let Some((name, value)) = arg.split_once('=') {
+        SetOption::find(name)?.set(app, value)?;
+        return Ok(None);
+    }
+    if let Some(name) = arg.strip_suffix('?') {
+        let option = SetOption::find(name)?;
+        return Ok(Some(format!("{name}={}", option.value(app))));
+    }
+    if let Some(name) = arg.strip_suffix('!') {
+        SetOption::find(name)?.toggle(app)?;
+        return Ok(None);
+    }
+    if let Some(value) = next {
+        SetOption::find(arg)?.set(app, value)?;
+        return Ok(None);
+    }
+
+    let negated = arg
+        .strip_prefix("no")
+        .and_then(|name| SetOption::find(name).ok());
+    if let Some(option) = negated.filter(|option| matches!(option.kind, OptionKind::Bool { .. })) {
+        option.set(app, "off")?;
+        return Ok(None);
+    }
+
+    let option = SetOption::find(arg)?;
+    match option.kind {
+        OptionKind::Bool { set, .. } => set(app, true).map(|_| None),
+        OptionKind::Choice { .. } => Ok(Some(format!("{arg}={}", option.value(app)))),
+    }
+}
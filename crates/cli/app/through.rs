@@ -0,0 +1,66 @@
+use anyhow::{anyhow, bail, Result};
+use std::{
+    fs::File,
+    io::Read,
+    process::{Child, ChildStdout, Command, Stdio},
+};
+
+/// Output of a command that a file is piped through, read as a stream.
+///
+/// The file is written to the command from a thread of its own while the
+/// output is read by the indexer, so that neither side waits on the other
+/// however much the command buffers.
+pub struct Through {
+    child: Child,
+    stdout: ChildStdout,
+}
+
+impl Through {
+    /// Starts `command`, split like a shell would split it, with the contents
+    /// of `file` as its input.
+    pub fn spawn(command: &str, mut file: File) -> Result<Self> {
+        let mut shl = shlex::Shlex::new(command);
+        let Some(program) = shl.next() else {
+            bail!("no command provided");
+        };
+        let args = shl.by_ref().collect::<Vec<_>>();
+        if shl.had_error {
+            bail!("lexing `{command}` failed");
+        }
+
+        // The screen is not ours to write to, so whatever the command
+        // complains about is lost
+        let mut child = Command::new(&program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| anyhow!("{program}: {err}"))?;
+
+        let mut stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        std::thread::spawn(move || {
+            // The command may exit before reading all of its input, which is
+            // not an error here; its output is what is shown either way
+            std::io::copy(&mut file, &mut stdin).ok();
+        });
+
+        Ok(Self { child, stdout })
+    }
+}
+
+impl Read for Through {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.stdout.read(buf)
+    }
+}
+
+impl Drop for Through {
+    fn drop(&mut self) {
+        // Stops a command still running when its view is closed, and reaps it
+        // either way
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
@@ -83,7 +83,7 @@ impl ConfigViewerWidget<'_> {
                 .for_each(|(y, filter)| {
                     FilterLineWidget {
                         color: filter.color(),
-                        name: filter.name(),
+                        name: &filter.name(),
                         enabled: filter.is_enabled(),
                     }
                     .render(
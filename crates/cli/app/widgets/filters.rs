@@ -7,6 +7,7 @@ use crate::{
     components::{cursor::Cursor, filters::Mask, instance::Instance},
 };
 use bitflags::bitflags;
+use bvr_core::matches::CompositeStrategy;
 use crossterm::event::MouseEventKind;
 use ratatui::{prelude::*, widgets::*};
 use std::sync::OnceLock;
@@ -14,15 +15,40 @@ use std::sync::OnceLock;
 pub struct FilterViewerWidget<'a> {
     pub(super) view_index: usize,
     pub(super) instance: &'a mut Instance,
+    /// Lay out every filter on a single row, without the header or the
+    /// selection, for showing the filters outside of filter mode.
+    pub(super) compact: bool,
 }
 
 impl FilterViewerWidget<'_> {
-    pub fn render(self, area: Rect, buf: &mut Buffer, handle: &mut MouseHandler) {
+    pub fn render(self, mut area: Rect, buf: &mut Buffer, handle: &mut MouseHandler) {
         static WIDGET_BLOCK: OnceLock<Block> = OnceLock::new();
         WIDGET_BLOCK
             .get_or_init(|| Block::new().style(Style::new().bg(colors::STATUS_BAR)))
             .render(area, buf);
 
+        if self.compact {
+            self.render_strip(area, buf, handle);
+            return;
+        }
+
+        if area.height > 1 {
+            let mut header_area = area;
+            header_area.height = 1;
+            area.y += 1;
+            area.height -= 1;
+
+            let strategy = match self.instance.compositor().strategy() {
+                CompositeStrategy::Union => "union",
+                CompositeStrategy::Intersection => "intersection",
+            };
+            Paragraph::new(Line::from(vec![
+                Span::from("   Filters").fg(colors::FILTER_ACCENT),
+                Span::from(format!(" ({strategy})")).fg(colors::TEXT_INACTIVE),
+            ]))
+            .render(header_area, buf);
+        }
+
         let cursor_state = self.instance.compositor_mut().cursor().state();
 
         let view = self
@@ -39,6 +65,7 @@ impl FilterViewerWidget<'_> {
                     name: filter.mask(),
                     color: filter.color(),
                     len: filter.len(),
+                    capped: filter.is_capped(),
                     ty: match cursor_state {
                         Cursor::Singleton(i) => {
                             if index == i {
@@ -69,12 +96,61 @@ impl FilterViewerWidget<'_> {
     }
 }
 
+impl FilterViewerWidget<'_> {
+    /// Each filter as its enabled state, name and count, one after another
+    /// until the row runs out. Clicking a filter toggles it.
+    fn render_strip(self, area: Rect, buf: &mut Buffer, handle: &mut MouseHandler) {
+        let mut x = area.x;
+        for (index, filter) in self.instance.compositor().filters().iter().enumerate() {
+            let mut spans = vec![
+                Span::from(if filter.is_enabled() {
+                    " ● "
+                } else {
+                    " ◯ "
+                })
+                .fg(filter.color()),
+                Span::raw(filter.mask().name()).fg(filter.color()),
+            ];
+            if let Some(len) = filter.len() {
+                spans.push(
+                    Span::from(if filter.is_capped() {
+                        format!(" {len}+")
+                    } else {
+                        format!(" {len}")
+                    })
+                    .fg(colors::TEXT_INACTIVE),
+                );
+            }
+            spans.push(Span::raw(" "));
+
+            let line = Line::from(spans);
+            let width = (line.width() as u16).min(area.right().saturating_sub(x));
+            if width == 0 {
+                break;
+            }
+            let chunk = Rect::new(x, area.y, width, 1.min(area.height));
+            line.render(chunk, buf);
+            x += width;
+
+            let view_index = self.view_index;
+            handle.on_mouse(chunk, |event| match event.kind {
+                MouseEventKind::Down(_) => Some(Action::Filter(FilterAction::ToggleFilter {
+                    target_view: view_index,
+                    filter_index: index,
+                })),
+                _ => None,
+            });
+        }
+    }
+}
+
 struct FilterLineWidget<'a> {
     view_index: usize,
     index: usize,
     name: &'a Mask,
     color: Color,
     len: Option<usize>,
+    capped: bool,
     ty: FilterType,
 }
 
@@ -120,7 +196,14 @@ impl FilterLineWidget<'_> {
         v.push(Span::raw(self.name.name()).fg(self.color));
 
         if let Some(len) = self.len {
-            v.push(Span::from(format!(" {}", len)).fg(colors::TEXT_INACTIVE));
+            v.push(
+                Span::from(if self.capped {
+                    format!(" {}+ (capped)", len)
+                } else {
+                    format!(" {}", len)
+                })
+                .fg(colors::TEXT_INACTIVE),
+            );
         }
 
         Paragraph::new(Line::from(v)).render(area, buf);
@@ -0,0 +1,76 @@
+use super::super::mouse::MouseHandler;
+use crate::{
+    colors,
+    components::help::{HelpApp, HelpEntry},
+};
+use ratatui::{prelude::*, widgets::*};
+use std::sync::OnceLock;
+
+pub struct HelpViewerWidget<'a> {
+    pub(super) app: &'a mut HelpApp,
+    pub(super) query: &'a str,
+}
+
+impl HelpViewerWidget<'_> {
+    const USAGE_WIDTH: usize = 28;
+
+    pub fn render(self, area: Rect, buf: &mut Buffer, handle: &mut MouseHandler) {
+        static WIDGET_BLOCK: OnceLock<Block> = OnceLock::new();
+        WIDGET_BLOCK
+            .get_or_init(|| Block::new().style(Style::new().bg(colors::STATUS_BAR)))
+            .render(area, buf);
+
+        let view = self.app.update_and_view(area.height as usize, self.query);
+        let selected = self.app.selected_index();
+
+        (area.y..area.bottom())
+            .zip(view)
+            .for_each(|(y, (index, help))| {
+                HelpLineWidget {
+                    help,
+                    selected: index == selected,
+                }
+                .render(Rect::new(area.x, y, area.width, 1), buf, handle);
+            });
+    }
+}
+
+struct HelpLineWidget {
+    help: HelpEntry,
+    selected: bool,
+}
+
+impl HelpLineWidget {
+    pub fn render(self, area: Rect, buf: &mut Buffer, _: &mut MouseHandler) {
+        let (usage, description) = match self.help {
+            HelpEntry::Command(help) if help.args.is_empty() => {
+                (format!(":{}", help.command), help.description)
+            }
+            HelpEntry::Command(help) if help.command.is_empty() => {
+                (format!(":{}", help.args), help.description)
+            }
+            HelpEntry::Command(help) => {
+                (format!(":{} {}", help.command, help.args), help.description)
+            }
+            HelpEntry::Key(bind) => (
+                format!("{} ({})", bind.label, bind.mode.name()),
+                bind.description,
+            ),
+        };
+
+        let spans = vec![
+            Span::from(if self.selected { " ▶ " } else { "   " }).fg(colors::COMMAND_ACCENT),
+            Span::raw(format!(
+                "{usage:<width$} ",
+                width = HelpViewerWidget::USAGE_WIDTH
+            ))
+            .fg(if self.selected {
+                colors::COMMAND_ACCENT
+            } else {
+                colors::WHITE
+            }),
+            Span::raw(description).fg(colors::STATUS_BAR_TEXT),
+        ];
+        Paragraph::new(Line::from(spans)).render(area, buf);
+    }
+}
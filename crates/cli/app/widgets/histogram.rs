@@ -0,0 +1,66 @@
+use crate::{colors, components::histogram::HistogramApp, numbers::NumberFormat};
+use ratatui::{prelude::*, widgets::*};
+use std::sync::OnceLock;
+
+pub struct HistogramWidget<'a> {
+    pub(super) app: &'a HistogramApp,
+    pub(super) numbers: NumberFormat,
+}
+
+impl HistogramWidget<'_> {
+    const MAX_LABEL_WIDTH: usize = 32;
+
+    pub fn render(self, area: Rect, buf: &mut Buffer) {
+        static WIDGET_BLOCK: OnceLock<Block> = OnceLock::new();
+        WIDGET_BLOCK
+            .get_or_init(|| Block::new().style(Style::new().bg(colors::STATUS_BAR)))
+            .render(area, buf);
+
+        let status = if self.app.is_done() {
+            format!(
+                "{} distinct in {} lines",
+                self.numbers.int(self.app.distinct()),
+                self.numbers.int(self.app.scanned())
+            )
+        } else {
+            format!(
+                "scanning… {} lines so far",
+                self.numbers.int(self.app.scanned())
+            )
+        };
+        Paragraph::new(Line::from(vec![
+            Span::raw(" hist ")
+                .fg(colors::WHITE)
+                .bg(colors::COMMAND_ACCENT),
+            Span::raw(format!(" {} ", self.app.pattern())).fg(colors::COMMAND_ACCENT),
+            Span::raw(status).fg(colors::STATUS_BAR_TEXT),
+        ]))
+        .render(Rect::new(area.x, area.y, area.width, 1), buf);
+
+        let rows = usize::from(area.height.saturating_sub(1));
+        let entries = self.app.top(rows);
+        let Some(&(_, max_count)) = entries.first() else {
+            return;
+        };
+
+        let label_width = entries
+            .iter()
+            .map(|(value, _)| value.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(Self::MAX_LABEL_WIDTH);
+        let count_width = max_count.to_string().len();
+        let bar_width = usize::from(area.width).saturating_sub(label_width + count_width + 6);
+
+        for (y, (value, count)) in (area.y + 1..area.bottom()).zip(entries) {
+            let label = value.chars().take(label_width).collect::<String>();
+            let bar = "█".repeat((count * bar_width).div_ceil(max_count));
+            Paragraph::new(Line::from(vec![
+                Span::raw(format!(" {label:<label_width$} ")).fg(colors::WHITE),
+                Span::raw(format!("{count:>count_width$} ")).fg(colors::STATUS_BAR_TEXT),
+                Span::raw(bar).fg(colors::COMMAND_ACCENT),
+            ]))
+            .render(Rect::new(area.x, y, area.width, 1), buf);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{colors, components::status::StatusApp};
+use ratatui::{prelude::*, widgets::*};
+use std::time::UNIX_EPOCH;
+
+pub struct MessagesWidget<'a> {
+    pub(super) status: &'a StatusApp,
+}
+
+impl MessagesWidget<'_> {
+    pub fn render(self, area: Rect, buf: &mut Buffer) {
+        let rows = usize::from(area.height.saturating_sub(2));
+        let history = self.status.history();
+        // The latest messages stay at the bottom, next to the status bar
+        let lines = history
+            .iter()
+            .skip(history.len().saturating_sub(rows))
+            .map(|(time, message)| {
+                let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+                Line::from(vec![
+                    Span::raw(format!(
+                        "{:02}:{:02}:{:02} ",
+                        secs / 3600 % 24,
+                        secs / 60 % 60,
+                        secs % 60
+                    ))
+                    .fg(colors::GUTTER_TEXT),
+                    Span::raw(message.as_str()).fg(colors::TEXT_ACTIVE),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let padding = rows.saturating_sub(lines.len());
+
+        Clear.render(area, buf);
+        Paragraph::new(
+            std::iter::repeat_n(Line::default(), padding)
+                .chain(lines)
+                .collect::<Vec<_>>(),
+        )
+        .block(
+            Block::bordered()
+                .border_style(Style::new().fg(colors::COMMAND_ACCENT))
+                .title(format!(" messages ({}, UTC) ", history.len()))
+                .title_bottom(" Esc to close "),
+        )
+        .bg(colors::STATUS_BAR)
+        .render(area, buf);
+    }
+}
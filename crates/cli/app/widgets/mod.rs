@@ -1,9 +1,15 @@
 mod config;
 mod filters;
+mod help;
+mod histogram;
+mod messages;
+mod peek;
+mod perf;
 mod viewer;
 
 use super::{
     actions::{Action, NormalAction},
+    control::{SelectionStyle, StatusLayout, StatusSegment},
     mouse::MouseHandler,
     InputMode, PromptMode,
 };
@@ -11,35 +17,74 @@ use crate::{
     app::widgets::{filters::FilterViewerWidget, viewer::LineViewerWidget},
     colors,
     components::{
-        config::filter::FilterConfigApp,
+        config::{filter::FilterConfigApp, theme::Theme},
         cursor::{Cursor, SelectionOrigin},
+        help::HelpApp,
+        histogram::HistogramApp,
         instance::Instance,
         mux::{MultiplexerApp, MultiplexerMode},
+        peek::PeekApp,
+        perf::PerfApp,
         prompt::PromptApp,
         status::StatusApp,
     },
+    numbers::NumberFormat,
 };
 use config::ConfigViewerWidget;
 use crossterm::event::MouseEventKind;
+use help::HelpViewerWidget;
+use histogram::HistogramWidget;
+use messages::MessagesWidget;
+use peek::PeekWidget;
+use perf::PerfWidget;
 use ratatui::{prelude::*, widgets::*};
 use regex::bytes::Regex;
-use std::sync::OnceLock;
+use std::{
+    borrow::Cow,
+    ops::Range,
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 pub struct StatusWidget<'a> {
     input_mode: InputMode,
     instance: Option<&'a Instance>,
     message: Option<&'a str>,
+    layout: &'a StatusLayout,
+    numbers: NumberFormat,
 }
 
-impl<'a> Widget for StatusWidget<'a> {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        const STATUS_BAR_STYLE: Style = Style::new()
-            .fg(colors::STATUS_BAR_TEXT)
-            .bg(colors::STATUS_BAR);
+impl<'a> StatusWidget<'a> {
+    /// Cuts `text` down to `max_width` columns, ending it with `…` if cut.
+    fn truncate(text: &str, max_width: usize) -> Cow<'_, str> {
+        if text.width() <= max_width {
+            return Cow::Borrowed(text);
+        }
+        let mut width = 0;
+        let end = text
+            .char_indices()
+            .find(|&(_, c)| {
+                width += c.width().unwrap_or(0);
+                width > max_width.saturating_sub(1)
+            })
+            .map_or(text.len(), |(i, _)| i);
+        Cow::Owned(format!("{}…", &text[..end]))
+    }
 
-        let (accent_color, mode_name) = match self.input_mode {
+    fn mode_style(&self) -> (Color, &'static str) {
+        match self.input_mode {
             InputMode::Prompt(PromptMode::Command) => (colors::COMMAND_ACCENT, " COMMAND "),
             InputMode::Prompt(PromptMode::Shell { .. }) => (colors::SHELL_ACCENT, " SHELL "),
+            InputMode::Prompt(PromptMode::Help) => (colors::COMMAND_ACCENT, " HELP "),
+            InputMode::Prompt(PromptMode::Find { escaped }) => (
+                colors::FILTER_ACCENT,
+                if escaped {
+                    " FIND (ESCAPED) "
+                } else {
+                    " FIND "
+                },
+            ),
             InputMode::Prompt(PromptMode::Search { escaped, edit }) => (
                 colors::FILTER_ACCENT,
                 match (escaped, edit) {
@@ -53,64 +98,154 @@ impl<'a> Widget for StatusWidget<'a> {
             InputMode::Visual => (colors::SELECT_ACCENT, " VISUAL "),
             InputMode::Filter => (colors::FILTER_ACCENT, " FILTER "),
             InputMode::Config => (colors::CONFIG_ACCENT, " CONFIG "),
-        };
+        }
+    }
 
+    /// Spans of a single segment, empty when it has nothing to show.
+    fn segment(&self, segment: StatusSegment, area: Rect) -> Vec<Span<'a>> {
+        let (accent_color, mode_name) = self.mode_style();
         let mut v = Vec::new();
 
-        v.push(Span::from(mode_name).fg(colors::WHITE).bg(accent_color));
-        v.push(Span::raw(" "));
+        match segment {
+            StatusSegment::Mode => {
+                v.push(Span::from(mode_name).fg(colors::WHITE).bg(accent_color));
+            }
+            StatusSegment::Name => {
+                if let Some(instance) = self.instance {
+                    v.push(Span::raw(instance.name()).fg(colors::STATUS_BAR_TEXT));
+                    // Stays up for as long as the file is gone, unlike messages
+                    if instance.file().is_deleted() {
+                        v.push(Span::raw(" (file deleted)").fg(colors::ERROR_ACCENT));
+                    }
+                } else {
+                    v.push(Span::raw("Empty").fg(colors::STATUS_BAR_TEXT));
+                }
+            }
+            StatusSegment::Info => {
+                if let Some(message) = self.message {
+                    v.push(Span::raw(message));
+                } else if let Some(instance) = self.instance {
+                    let ln_cnt = instance.file().line_count();
+                    let ln_vis = instance.visible_line_count();
+                    v.push(
+                        Span::raw(format!("{} lines", self.numbers.int(ln_cnt))).fg(accent_color),
+                    );
+                    if ln_vis < ln_cnt {
+                        v.push(
+                            Span::raw(format!(" ({} visible)", self.numbers.int(ln_vis)))
+                                .fg(colors::STATUS_BAR_TEXT),
+                        );
+                    }
+                    if instance.compositor().needs_composite() && !instance.is_view_complete() {
+                        v.push(Span::raw(" filtering…").fg(colors::STATUS_BAR_TEXT));
+                    }
+                    v.push(Span::raw(" │ ").fg(accent_color));
+                    v.push(Span::raw(instance.name()).fg(accent_color));
+                } else {
+                    v.push(Span::raw(":open [file name]").fg(accent_color));
+                    v.push(Span::raw(" to view a file").fg(colors::STATUS_BAR_TEXT));
+                }
+            }
+            StatusSegment::Filter => {
+                if self.input_mode == InputMode::Filter {
+                    if let Some(filter) = self
+                        .instance
+                        .and_then(|instance| instance.compositor().selected_filter())
+                    {
+                        let max_width = usize::from(area.width / 3).max(8);
+                        v.push(
+                            Span::raw(Self::truncate(filter.mask().name(), max_width))
+                                .fg(filter.color()),
+                        );
+                    }
+                }
+            }
+            StatusSegment::Position => {
+                if let Some(instance) = self.instance {
+                    if instance.is_following_output() {
+                        v.push(Span::raw("Follow").fg(colors::STATUS_BAR_TEXT));
+                    } else {
+                        let row = instance.viewport().top();
+                        let col = instance.viewport().left();
+                        v.push(
+                            Span::raw(format!("{}:{}", row + 1, col + 1))
+                                .fg(colors::STATUS_BAR_TEXT),
+                        );
+                    }
+                }
+            }
+            StatusSegment::Percent => {
+                if let Some(instance) = self.instance.filter(|i| !i.is_following_output()) {
+                    let bottom = instance.viewport().bottom();
+                    let ln_vis = instance.visible_line_count();
+                    let percentage = if ln_vis == 0 {
+                        1.0
+                    } else {
+                        bottom as f64 / ln_vis as f64
+                    }
+                    .clamp(0.0, 1.0);
 
-        if let Some(instance) = self.instance {
-            v.push(Span::raw(instance.name()).fg(colors::STATUS_BAR_TEXT));
-        } else {
-            v.push(Span::raw("Empty").fg(colors::STATUS_BAR_TEXT));
+                    v.push(
+                        Span::raw(format!("{:.0}%", percentage * 100.0))
+                            .fg(colors::STATUS_BAR_TEXT),
+                    );
+                }
+            }
+            StatusSegment::Clock => {
+                let secs = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |since| since.as_secs());
+                v.push(
+                    Span::raw(format!("{:02}:{:02} UTC", secs / 3600 % 24, secs / 60 % 60))
+                        .fg(colors::STATUS_BAR_TEXT),
+                );
+            }
         }
-        v.push(Span::raw(" │ ").fg(colors::STATUS_BAR_TEXT));
-
-        if let Some(message) = self.message {
-            v.push(Span::raw(message));
-        } else if let Some(instance) = self.instance {
-            let ln_cnt = instance.file().line_count();
-            let ln_vis = instance.visible_line_count();
-            v.push(Span::raw(format!("{} lines", ln_cnt)).fg(accent_color));
-            if ln_vis < ln_cnt {
-                v.push(Span::raw(format!(" ({} visible)", ln_vis)).fg(colors::STATUS_BAR_TEXT));
+
+        v
+    }
+}
+
+impl Widget for StatusWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        const STATUS_BAR_STYLE: Style = Style::new()
+            .fg(colors::STATUS_BAR_TEXT)
+            .bg(colors::STATUS_BAR);
+
+        let mut left = Vec::new();
+        let mut previous = None;
+        for &segment in &self.layout.left {
+            let spans = self.segment(segment, area);
+            if spans.is_empty() {
+                continue;
             }
-            v.push(Span::raw(" │ ").fg(accent_color));
-            v.push(Span::raw(instance.name()).fg(accent_color));
-        } else {
-            v.push(Span::raw(":open [file name]").fg(accent_color));
-            v.push(Span::raw(" to view a file").fg(colors::STATUS_BAR_TEXT));
+            match previous {
+                None => {}
+                // The mode badge is padded rather than separated
+                Some(StatusSegment::Mode) => left.push(Span::raw(" ")),
+                Some(_) => left.push(Span::raw(" │ ").fg(colors::STATUS_BAR_TEXT)),
+            }
+            left.extend(spans);
+            previous = Some(segment);
         }
 
-        Paragraph::new(Line::from(v))
+        Paragraph::new(Line::from(left))
             .style(STATUS_BAR_STYLE)
             .render(area, buf);
 
-        if let Some(instance) = self.instance {
-            if instance.is_following_output() {
-                Paragraph::new(Span::raw("Follow  ").fg(colors::STATUS_BAR_TEXT))
-            } else {
-                let bottom = instance.viewport().bottom();
-                let ln_vis = instance.visible_line_count();
-                let percentage = if ln_vis == 0 {
-                    1.0
-                } else {
-                    bottom as f64 / ln_vis as f64
-                }
-                .clamp(0.0, 1.0);
-
-                let row = instance.viewport().top();
-                let col = instance.viewport().left();
-
-                Paragraph::new(Line::from(vec![
-                    Span::raw(format!("{}:{}", row + 1, col + 1)).fg(colors::STATUS_BAR_TEXT),
-                    Span::raw(format!("  {:.0}%  ", percentage * 100.0))
-                        .fg(colors::STATUS_BAR_TEXT),
-                ]))
+        let mut right = Vec::new();
+        for &segment in &self.layout.right {
+            let spans = self.segment(segment, area);
+            if !spans.is_empty() {
+                right.extend(spans);
+                right.push(Span::raw("  "));
             }
-            .alignment(Alignment::Right)
-            .render(area, buf)
+        }
+
+        if !right.is_empty() {
+            Paragraph::new(Line::from(right))
+                .alignment(Alignment::Right)
+                .render(area, buf)
         }
     }
 }
@@ -118,16 +253,35 @@ impl<'a> Widget for StatusWidget<'a> {
 pub struct PromptWidget<'a> {
     pub inner: &'a mut PromptApp,
     pub mode: InputMode,
+    /// Why the pattern being typed is invalid, shown while it stays invalid.
+    pub error: Option<&'a str>,
+    /// How many lines the pattern being typed matches, while it is valid.
+    pub matches: Option<String>,
     pub cursor: &'a mut Option<(u16, u16)>,
 }
 
 impl PromptWidget<'_> {
+    /// Bytes of the input that are drawn from byte `left` on, no more than
+    /// `width` characters. Only these are laid out, so that drawing stays
+    /// cheap however long the input is.
+    fn visible_range(input: &str, left: usize, width: usize) -> Range<usize> {
+        let start = (0..=left.min(input.len()))
+            .rev()
+            .find(|&i| input.is_char_boundary(i))
+            .unwrap_or(0);
+        let len = input[start..]
+            .char_indices()
+            .nth(width)
+            .map_or(input.len() - start, |(i, _)| i);
+        start..start + len
+    }
+
     pub fn split_prompt(area: Rect) -> [Rect; 2] {
         let mut indicator_chunk = area;
         indicator_chunk.width = 1;
 
         let mut data_chunk = area;
-        data_chunk.width -= 1;
+        data_chunk.width = data_chunk.width.saturating_sub(1);
         data_chunk.x += 1;
 
         [indicator_chunk, data_chunk]
@@ -146,45 +300,102 @@ impl PromptWidget<'_> {
 
         match mode {
             PromptMode::Command => Span::raw(":").fg(colors::COMMAND_ACCENT),
+            PromptMode::Search { .. } if self.error.is_some() => {
+                Span::raw("/").fg(colors::ERROR_ACCENT)
+            }
             PromptMode::Search { .. } => Span::raw("/").fg(colors::FILTER_ACCENT),
+            PromptMode::Find { .. } if self.error.is_some() => {
+                Span::raw("?").fg(colors::ERROR_ACCENT)
+            }
+            PromptMode::Find { .. } => Span::raw("?").fg(colors::FILTER_ACCENT),
             PromptMode::Shell { pipe: true } => Span::raw("|").fg(colors::SHELL_ACCENT),
             PromptMode::Shell { pipe: false } => Span::raw("!").fg(colors::SHELL_ACCENT),
+            PromptMode::Help => Span::raw("?").fg(colors::COMMAND_ACCENT),
         }
         .render(indicator_area, buf);
 
         let cursor = self.inner.cursor();
+        self.inner.view_and_update(usize::from(data_area.width));
+        let completion = self.inner.completion();
+        let cmd_buf = self.inner.buf();
         let left = self.inner.viewport().left();
-        let cmd_buf = self.inner.view_and_update(usize::from(area.width));
 
-        Paragraph::new(cmd_buf)
+        let range = Self::visible_range(cmd_buf, left, usize::from(data_area.width));
+        let (start, visible) = (range.start, &cmd_buf[range]);
+        let column = |i: usize| {
+            let end = i.clamp(start, start + visible.len());
+            cmd_buf.get(start..end).map_or(0, |s| s.chars().count()) as u16
+        };
+
+        Paragraph::new(visible)
             .bg(colors::BG)
-            .scroll((0, left as u16))
             .render(data_area, buf);
 
-        match cursor {
-            Cursor::Selection(start, end, _) => {
-                let start = start.saturating_sub(left);
-                let end = end.saturating_sub(left);
-                let mut span_area = data_area;
-                span_area.x += start as u16;
-                span_area.width = (end - start) as u16;
-
-                static HIGHLIGHT_BLOCK: OnceLock<Block> = OnceLock::new();
-                HIGHLIGHT_BLOCK
-                    .get_or_init(|| Block::new().style(Style::new().bg(colors::COMMAND_BAR_SELECT)))
-                    .render(span_area, buf);
+        // The hint is only shown if it does not cover the pattern
+        let hint = match (self.error, self.matches) {
+            (Some(error), _) => Some(Span::raw(format!(" {error} ")).fg(colors::ERROR_ACCENT)),
+            (None, Some(matches)) => {
+                Some(Span::raw(format!(" {matches} ")).fg(colors::TEXT_INACTIVE))
+            }
+            (None, None) => None,
+        };
+        if let Some(hint) = hint {
+            let hint_width = hint.width();
+            let used = visible.chars().count() + 1;
+            if used + hint_width <= usize::from(data_area.width) {
+                let mut hint_area = data_area;
+                hint_area.x = data_area.right() - hint_width as u16;
+                hint_area.width = hint_width as u16;
+                hint.render(hint_area, buf);
+            }
+        }
+
+        // Candidates are listed from the one in place of the word, in the
+        // room left after the input
+        if let Some((candidates, selected)) = completion {
+            let used = visible.chars().count() + 1;
+            let room = usize::from(data_area.width).saturating_sub(used);
+            let mut width = 0;
+            let mut spans = Vec::new();
+            for (i, candidate) in candidates.iter().enumerate().skip(selected.unwrap_or(0)) {
+                let span = Span::raw(format!(" {candidate} "));
+                if width + span.width() > room {
+                    break;
+                }
+                width += span.width();
+                spans.push(if Some(i) == selected {
+                    span.bg(colors::COMMAND_BAR_SELECT)
+                } else {
+                    span.fg(colors::TEXT_INACTIVE)
+                });
+            }
+            if !spans.is_empty() {
+                let mut list_area = data_area;
+                list_area.x = data_area.right() - width as u16;
+                list_area.width = width as u16;
+                Paragraph::new(Line::from(spans)).render(list_area, buf);
             }
-            _ => {}
+        }
+
+        if let Cursor::Selection(start, end, _) = cursor {
+            let start = column(start);
+            let end = column(end);
+            let mut span_area = data_area;
+            span_area.x += start;
+            span_area.width = end - start;
+
+            static HIGHLIGHT_BLOCK: OnceLock<Block> = OnceLock::new();
+            HIGHLIGHT_BLOCK
+                .get_or_init(|| Block::new().style(Style::new().bg(colors::COMMAND_BAR_SELECT)))
+                .render(span_area, buf);
         }
 
         let i = match cursor {
             Cursor::Singleton(i)
             | Cursor::Selection(_, i, SelectionOrigin::Right)
-            | Cursor::Selection(i, _, SelectionOrigin::Left) => {
-                cmd_buf[..i.saturating_sub(left)].chars().count()
-            }
+            | Cursor::Selection(i, _, SelectionOrigin::Left) => column(i),
         };
-        *self.cursor = Some((data_area.x + i as u16, data_area.y));
+        *self.cursor = Some((data_area.x + i, data_area.y));
     }
 }
 
@@ -192,29 +403,44 @@ pub struct TabWidget<'a> {
     view_index: usize,
     name: &'a str,
     active: bool,
+    /// Match count of the selected filter.
+    badge: Option<Span<'a>>,
 }
 
 impl TabWidget<'_> {
+    fn badge(instance: &Instance, numbers: NumberFormat) -> Option<Span<'static>> {
+        let filter = instance.compositor().selected_filter()?;
+        let count = if filter.is_complete() {
+            numbers.int(filter.len()?)
+        } else {
+            String::from("…")
+        };
+        Some(Span::raw(format!(" {count}")).fg(filter.color()))
+    }
+
     fn render(self, area: Rect, buf: &mut Buffer, handle: &mut MouseHandler) {
-        Paragraph::new(Line::from(vec![
+        let mut spans = vec![
             if self.active {
                 Span::from("▍ ").fg(colors::TAB_SIDE_ACTIVE)
             } else {
                 Span::from("▏ ").fg(colors::TAB_SIDE_INACTIVE)
             },
             Span::from(self.name),
-        ]))
-        .bg(if self.active {
-            colors::TAB_ACTIVE
-        } else {
-            colors::TAB_INACTIVE
-        })
-        .fg(if self.active {
-            colors::TEXT_ACTIVE
-        } else {
-            colors::TEXT_INACTIVE
-        })
-        .render(area, buf);
+        ];
+        spans.extend(self.badge);
+
+        Paragraph::new(Line::from(spans))
+            .bg(if self.active {
+                colors::TAB_ACTIVE
+            } else {
+                colors::TAB_INACTIVE
+            })
+            .fg(if self.active {
+                colors::TEXT_ACTIVE
+            } else {
+                colors::TEXT_INACTIVE
+            })
+            .render(area, buf);
 
         handle.on_mouse(area, |event| match event.kind {
             MouseEventKind::Down(_) => Some(Action::Normal(NormalAction::SwitchActiveIndex {
@@ -229,13 +455,28 @@ pub struct MultiplexerPane<'a> {
     view_index: usize,
     instance: &'a mut Instance,
     show_filter_on_pane: bool,
+    /// Show the filters on a row under the view outside of filter mode.
+    filter_strip: bool,
     show_selection: bool,
     gutter: bool,
+    time_delta: bool,
+    line_lengths: bool,
+    numbers: NumberFormat,
+    selection_style: SelectionStyle,
+    dim: bool,
+    wrap: bool,
+    flash_color: Color,
+    json_fields: Option<&'a [String]>,
+    rulers: &'a [usize],
+    text_markers: bool,
     regex: Option<&'a Regex>,
+    match_style: Style,
+    truncation_marker: Style,
 }
 
 impl MultiplexerPane<'_> {
     const FILTER_MAX_HEIGHT: u16 = 10;
+    const FILTER_STRIP_HEIGHT: u16 = 1;
 
     fn filter_area(area: &mut Rect, f: impl FnOnce(Rect)) {
         let [view_chunk, filter_chunk] =
@@ -255,6 +496,7 @@ impl MultiplexerPane<'_> {
             FilterViewerWidget {
                 view_index,
                 instance,
+                compact: false,
             }
             .render(area, buf, handler);
         });
@@ -263,6 +505,16 @@ impl MultiplexerPane<'_> {
     pub fn render(self, mut area: Rect, buf: &mut Buffer, handler: &mut MouseHandler) {
         if self.show_filter_on_pane {
             Self::render_filter_pane(&mut area, buf, self.view_index, self.instance, handler);
+        } else if self.filter_strip && area.height > Self::FILTER_STRIP_HEIGHT {
+            let [view_chunk, strip_chunk] =
+                MultiplexerWidget::split_bottom(area, Self::FILTER_STRIP_HEIGHT);
+            FilterViewerWidget {
+                view_index: self.view_index,
+                instance: self.instance,
+                compact: true,
+            }
+            .render(strip_chunk, buf, handler);
+            area = view_chunk;
         }
 
         LineViewerWidget {
@@ -270,7 +522,19 @@ impl MultiplexerPane<'_> {
             show_selection: self.show_selection,
             instance: self.instance,
             gutter: self.gutter,
+            time_delta: self.time_delta,
+            line_lengths: self.line_lengths,
+            numbers: self.numbers,
+            selection_style: self.selection_style,
+            dim: self.dim,
+            wrap: self.wrap,
+            flash_color: self.flash_color,
+            json_fields: self.json_fields,
+            rulers: self.rulers,
+            text_markers: self.text_markers,
             regex: self.regex,
+            truncation_marker: self.truncation_marker,
+            match_style: self.match_style,
         }
         .render(area, buf, handler);
     }
@@ -280,10 +544,33 @@ pub struct MultiplexerWidget<'a> {
     pub mux: &'a mut MultiplexerApp,
     pub status: &'a mut StatusApp,
     pub config: &'a mut FilterConfigApp,
+    pub help: &'a mut HelpApp,
+    pub help_query: &'a str,
     pub mode: InputMode,
+    /// Show the filters of each pane on a row under it, see
+    /// [FilterViewerWidget::compact].
+    pub filter_strip: bool,
     pub gutter: bool,
+    pub time_delta: bool,
+    pub line_lengths: bool,
+    pub selection_style: SelectionStyle,
+    pub dim: bool,
+    pub wrap: bool,
+    pub flash_color: Color,
+    pub json_fields: Option<&'a [String]>,
+    /// Columns at which guides are drawn down the view.
+    pub rulers: &'a [usize],
+    /// Mark bookmarked and matched lines with text rather than color.
+    pub text_markers: bool,
     pub regex: Option<&'a Regex>,
     pub linked_filters: bool,
+    pub status_layout: &'a StatusLayout,
+    pub numbers: NumberFormat,
+    pub histogram: Option<&'a HistogramApp>,
+    pub peek: Option<&'a PeekApp>,
+    pub show_messages: bool,
+    pub perf: Option<&'a PerfApp>,
+    pub theme: Theme,
 }
 
 impl MultiplexerWidget<'_> {
@@ -319,6 +606,8 @@ impl MultiplexerWidget<'_> {
 
         let show_filter_on_pane = self.mode == InputMode::Filter && !self.linked_filters;
         let show_filter_on_mux = self.mode == InputMode::Filter && self.linked_filters;
+        // The full filter pane takes its place in filter mode
+        let filter_strip = self.filter_strip && self.mode != InputMode::Filter;
 
         if show_filter_on_mux {
             MultiplexerPane::render_filter_pane(
@@ -349,12 +638,18 @@ impl MultiplexerWidget<'_> {
                 view_index,
                 name: instance.name(),
                 active: active == view_index,
+                // Shows which files have matches when the filters are shared
+                badge: self
+                    .linked_filters
+                    .then(|| TabWidget::badge(instance, self.numbers))
+                    .flatten(),
             }
             .render(chunk, buf, handler);
         }
 
         match self.mux.mode() {
             MultiplexerMode::Panes => {
+                let pane_count = self.mux.len();
                 for (view_index, (pane_chunk, instance)) in split_chunks
                     .iter()
                     .map(|&chunk| view_chunk.intersection(chunk))
@@ -365,9 +660,29 @@ impl MultiplexerWidget<'_> {
                         view_index,
                         instance,
                         show_filter_on_pane,
+                        filter_strip,
                         show_selection: self.mode == InputMode::Visual,
                         gutter: self.gutter,
+                        time_delta: self.time_delta,
+                        line_lengths: self.line_lengths,
+                        numbers: self.numbers,
+                        selection_style: self.selection_style,
+                        dim: self.dim,
+                        wrap: self.wrap,
+                        flash_color: self.flash_color,
+                        json_fields: self.json_fields,
+                        rulers: self.rulers,
+                        text_markers: self.text_markers,
                         regex: self.regex,
+                        // Tells the focused pane apart when filters are linked
+                        truncation_marker: self.theme.truncation_marker,
+                        match_style: if pane_count < 2 {
+                            Style::new()
+                        } else if view_index == active {
+                            self.theme.match_focused
+                        } else {
+                            self.theme.match_unfocused
+                        },
                     }
                     .render(pane_chunk, buf, handler);
                 }
@@ -380,9 +695,22 @@ impl MultiplexerWidget<'_> {
                     view_index: active,
                     instance,
                     show_filter_on_pane,
+                    filter_strip,
                     show_selection: self.mode == InputMode::Visual,
                     gutter: self.gutter,
+                    time_delta: self.time_delta,
+                    line_lengths: self.line_lengths,
+                    numbers: self.numbers,
+                    selection_style: self.selection_style,
+                    dim: self.dim,
+                    wrap: self.wrap,
+                    flash_color: self.flash_color,
+                    json_fields: self.json_fields,
+                    rulers: self.rulers,
+                    text_markers: self.text_markers,
                     regex: self.regex,
+                    truncation_marker: self.theme.truncation_marker,
+                    match_style: Style::new(),
                 }
                 .render(pane_chunk, buf, handler);
             }
@@ -390,22 +718,140 @@ impl MultiplexerWidget<'_> {
     }
 
     pub fn render(mut self, area: Rect, buf: &mut Buffer, handler: &mut MouseHandler) {
-        let [mux_chunk, status_chunk] = Self::split_bottom(area, 1);
+        let [mut mux_chunk, status_chunk] = Self::split_bottom(area, 1);
+
+        if self.mode == InputMode::Prompt(PromptMode::Help) {
+            MultiplexerPane::filter_area(&mut mux_chunk, |area| {
+                HelpViewerWidget {
+                    app: &mut *self.help,
+                    query: self.help_query,
+                }
+                .render(area, buf, handler);
+            });
+        } else if let Some(app) = self.histogram {
+            MultiplexerPane::filter_area(&mut mux_chunk, |area| {
+                HistogramWidget {
+                    app,
+                    numbers: self.numbers,
+                }
+                .render(area, buf);
+            });
+        }
 
         if !self.mux.is_empty() {
             self.render_mux(mux_chunk, buf, handler);
+            if let Some(app) = self.peek {
+                PeekWidget { app }.render(mux_chunk, buf);
+            }
         } else {
-            const BG_BLOCK: OnceLock<Block> = OnceLock::new();
+            static BG_BLOCK: OnceLock<Block> = OnceLock::new();
             BG_BLOCK
                 .get_or_init(|| Block::new().style(Style::new().bg(colors::BG)))
                 .render(mux_chunk, buf);
         }
 
+        if self.show_messages {
+            MessagesWidget {
+                status: &*self.status,
+            }
+            .render(mux_chunk, buf);
+        }
+
+        if let Some(app) = self.perf {
+            PerfWidget { app }.render(mux_chunk, buf);
+        }
+
         StatusWidget {
             input_mode: self.mode,
             instance: self.mux.active_mut().map(|v| &*v),
             message: self.status.get_message_update().as_deref(),
+            layout: self.status_layout,
+            numbers: self.numbers,
         }
         .render(status_chunk, buf);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn status_truncates_by_display_width() {
+        assert_eq!(StatusWidget::truncate("error", 5), "error");
+        assert_eq!(StatusWidget::truncate("errors", 5), "erro…");
+        // Wide characters take up two columns each
+        assert_eq!(StatusWidget::truncate("错误日志", 8), "错误日志");
+        assert_eq!(StatusWidget::truncate("错误日志", 7), "错误日…");
+        assert_eq!(StatusWidget::truncate("错误日志", 6), "错误…");
+    }
+
+    #[test]
+    fn prompt_lays_out_only_the_visible_input() {
+        let input = "é".repeat(50_000);
+        // However long the input, a window of the width is laid out
+        let range = PromptWidget::visible_range(&input, 1_001, 40);
+        assert_eq!(range, 1_000..1_080);
+        assert_eq!(input[range].chars().count(), 40);
+        let range = PromptWidget::visible_range(&input, input.len() - 10, 40);
+        assert_eq!(range, input.len() - 10..input.len());
+        assert_eq!(PromptWidget::visible_range("abc", 10, 40), 3..3);
+    }
+
+    #[test]
+    fn prompt_keeps_the_cursor_in_view_of_long_input() {
+        let mut prompt = PromptApp::new();
+        let input = "é".repeat(50_000);
+        prompt.enter_str(&input);
+        assert!(matches!(prompt.cursor(), Cursor::Singleton(i) if i == input.len()));
+
+        let area = Rect::new(0, 0, 40, 1);
+        let mut buf = Buffer::empty(area);
+        for _ in 0..100 {
+            prompt.enter_char('x');
+            let mut cursor = None;
+            PromptWidget {
+                inner: &mut prompt,
+                mode: InputMode::Prompt(PromptMode::Command),
+                error: None,
+                matches: None,
+                cursor: &mut cursor,
+            }
+            .render(area, &mut buf);
+            assert!(cursor.is_some_and(|(x, y)| x < area.right() && y == area.y));
+        }
+
+        // The end of the input stays in view
+        let row = (area.x..area.right())
+            .map(|x| buf[(x, area.y)].symbol())
+            .collect::<String>();
+        assert!(row.trim_end().ends_with('x'));
+    }
+
+    #[test]
+    fn prompt_shows_the_match_count_unless_the_pattern_is_invalid() {
+        let area = Rect::new(0, 0, 40, 1);
+        let row = |error: Option<&str>| {
+            let mut prompt = PromptApp::new();
+            prompt.enter_str("err");
+            let mut buf = Buffer::empty(area);
+            PromptWidget {
+                inner: &mut prompt,
+                mode: InputMode::Prompt(PromptMode::Search {
+                    escaped: false,
+                    edit: false,
+                }),
+                error,
+                matches: Some(String::from("3 matching lines so far…")),
+                cursor: &mut None,
+            }
+            .render(area, &mut buf);
+            (area.x..area.right())
+                .map(|x| buf[(x, area.y)].symbol())
+                .collect::<String>()
+        };
+
+        assert!(row(None).ends_with(" 3 matching lines so far… "));
+        assert!(row(Some("unclosed group")).ends_with(" unclosed group "));
+    }
+}
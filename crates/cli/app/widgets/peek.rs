@@ -0,0 +1,65 @@
+use crate::{colors, components::peek::PeekApp};
+use ratatui::{prelude::*, widgets::*};
+
+pub struct PeekWidget<'a> {
+    pub(super) app: &'a PeekApp,
+}
+
+impl PeekWidget<'_> {
+    /// Centers a popup over `area`, sized to the lines of the peek.
+    fn popup_area(&self, area: Rect) -> Rect {
+        let width = area.width.saturating_sub(4);
+        let height = (self.app.lines().len() as u16 + 2).min(area.height);
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+
+    pub fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = self.popup_area(area);
+        let offset = self.app.line_offset();
+        let gutter_width = self
+            .app
+            .lines()
+            .last()
+            .map_or(1, |line| (line.line_number + offset + 1).to_string().len());
+
+        let lines = self
+            .app
+            .lines()
+            .iter()
+            .map(|line| {
+                let style = if line.line_number == self.app.line_number() {
+                    Style::new().fg(colors::TEXT_ACTIVE).bg(colors::SELECT_BG)
+                } else if line.visible {
+                    Style::new().fg(colors::TEXT_ACTIVE)
+                } else {
+                    Style::new().fg(colors::STATUS_BAR_TEXT)
+                };
+                Line::from(vec![
+                    Span::raw(format!("{:>gutter_width$} ", line.line_number + offset + 1))
+                        .fg(colors::GUTTER_TEXT),
+                    Span::raw(line.text.as_str()),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<_>>();
+
+        Clear.render(area, buf);
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_style(Style::new().fg(colors::NORMAL_ACCENT))
+                    .title(format!(
+                        " peek line {} ",
+                        self.app.line_number() + offset + 1
+                    ))
+                    .title_bottom(" Esc to close "),
+            )
+            .bg(colors::STATUS_BAR)
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,46 @@
+use crate::{colors, components::perf::PerfApp};
+use ratatui::{prelude::*, widgets::*};
+
+pub struct PerfWidget<'a> {
+    pub(super) app: &'a PerfApp,
+}
+
+impl PerfWidget<'_> {
+    const WIDTH: u16 = 34;
+
+    pub fn render(self, area: Rect, buf: &mut Buffer) {
+        // In the top right corner, out of the way of the start of the lines
+        let width = Self::WIDTH.min(area.width);
+        let height = (self.app.rows().len() as u16 + 2).min(area.height);
+        let area = Rect::new(area.right() - width, area.y, width, height);
+
+        let label_width = self
+            .app
+            .rows()
+            .iter()
+            .map(|(label, _)| label.len())
+            .max()
+            .unwrap_or(0);
+        let lines = self
+            .app
+            .rows()
+            .iter()
+            .map(|(label, value)| {
+                Line::from(vec![
+                    Span::raw(format!("{label:<label_width$} ")).fg(colors::GUTTER_TEXT),
+                    Span::raw(value.as_str()).fg(colors::TEXT_ACTIVE),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        Clear.render(area, buf);
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_style(Style::new().fg(colors::COMMAND_ACCENT))
+                    .title(" perf "),
+            )
+            .bg(colors::STATUS_BAR)
+            .render(area, buf);
+    }
+}
@@ -1,31 +1,61 @@
 use super::super::{
     actions::{Action, NormalAction},
-    control::ViewDelta,
-    mouse::MouseHandler,
+    control::{SelectionStyle, ViewDelta},
+    mouse::{DragAnchor, MouseHandler},
 };
 use crate::{
     app::actions::VisualAction,
+    cells::{self, Cells},
     colors,
     components::{cursor::Cursor, instance::Instance},
     direction::Direction,
+    json,
+    numbers::NumberFormat,
+    timestamp,
 };
 use bitflags::bitflags;
-use crossterm::event::MouseEventKind;
+use crossterm::event::{MouseButton, MouseEventKind};
 use ratatui::{prelude::*, widgets::*};
 use regex::bytes::Regex;
+use std::ops::Range;
 
 pub struct LineViewerWidget<'a> {
     pub(super) view_index: usize,
     pub(super) instance: &'a mut Instance,
     pub(super) show_selection: bool,
     pub(super) gutter: bool,
+    pub(super) time_delta: bool,
+    /// Show the length in bytes of each line, line ending included.
+    pub(super) line_lengths: bool,
+    pub(super) numbers: NumberFormat,
+    pub(super) selection_style: SelectionStyle,
+    pub(super) dim: bool,
+    /// Break long lines across rows instead of cutting them off at the edge.
+    pub(super) wrap: bool,
+    /// Background of lines that were just copied.
+    pub(super) flash_color: Color,
+    pub(super) json_fields: Option<&'a [String]>,
+    /// Columns of the guides drawn down the view, counted from one.
+    pub(super) rulers: &'a [usize],
+    /// Mark bookmarked and matched lines in the selection column, for when
+    /// colors are not shown.
+    pub(super) text_markers: bool,
     pub(super) regex: Option<&'a Regex>,
+    /// Patched onto the style of lines matched by a search filter.
+    pub(super) match_style: Style,
+    /// Style of the markers of lines cut off at the edges.
+    pub(super) truncation_marker: Style,
 }
 
+#[derive(Clone)]
 struct LineRenderData<'a> {
+    index: usize,
     line_number: usize,
     data: &'a str,
+    delta: Option<String>,
+    length: Option<String>,
     color: Color,
+    matcher: Option<&'a Regex>,
     ty: LineType,
 }
 
@@ -38,65 +68,284 @@ bitflags! {
         const OriginEnd = 1 << 2;
         const Within = 1 << 3;
         const Bookmarked = 1 << 4;
+        const Flashed = 1 << 5;
     }
 }
 
 impl LineViewerWidget<'_> {
-    pub fn render(self, area: Rect, buf: &mut Buffer, handle: &mut MouseHandler) {
+    const DELTA_SIZE: u16 = 8;
+    /// Narrowest the length column gets, so that it does not jump around as
+    /// short lines scroll by.
+    const MIN_LENGTH_SIZE: usize = 5;
+
+    /// Number of rows a line takes up when wrapped to `width` columns, which
+    /// is at least one even for an empty line. The terminator takes up none.
+    fn wrapped_rows(data: &str, width: usize, raw: bool) -> usize {
+        let text = data.trim_end_matches(['\r', '\n']);
+        let columns = if raw {
+            cells::raw_width(text)
+        } else {
+            cells::width(text)
+        };
+        columns.div_ceil(width.max(1)).max(1)
+    }
+
+    /// The logical line to draw on each row, paired with which of its wrapped
+    /// rows it is. When wrapping pushes the cursor line below the
+    /// bottom, lines are dropped from the top until all of its rows fit.
+    fn layout_rows(
+        lines: &[LineRenderData],
+        height: usize,
+        width: Option<usize>,
+        raw: bool,
+    ) -> Vec<(usize, usize)> {
+        let Some(width) = width else {
+            return (0..lines.len().min(height)).map(|i| (i, 0)).collect();
+        };
+        let rows = lines
+            .iter()
+            .map(|line| Self::wrapped_rows(line.data, width, raw))
+            .collect::<Vec<_>>();
+
+        let mut first = 0;
+        if let Some(cursor) = lines
+            .iter()
+            .rposition(|line| line.ty.contains(LineType::Origin))
+        {
+            let mut used = rows[..=cursor].iter().sum::<usize>();
+            while used > height && first < cursor {
+                used -= rows[first];
+                first += 1;
+            }
+        }
+
+        (first..lines.len())
+            .flat_map(|i| (0..rows[i]).map(move |row| (i, row)))
+            .take(height)
+            .collect()
+    }
+
+    /// Timestamp of the visible line preceding the top of the view, so that the
+    /// first rendered line also has a delta.
+    fn timestamp_before_view(instance: &Instance) -> Option<f64> {
+        let top = instance.cached_view().next()?.index;
+        let line = instance.line_at_view_index(top.checked_sub(1)?)?;
+        timestamp::parse_leading(line.as_str())
+    }
+
+    pub fn render(self, mut area: Rect, buf: &mut Buffer, handle: &mut MouseHandler) {
+        let header = if self.instance.has_header() {
+            // The header depends on the top line after the view is fit, to
+            // the rows left under the header
+            self.instance.update_view(
+                area.height.saturating_sub(1).max(1) as usize,
+                area.width as usize,
+            );
+            self.instance.sticky_header()
+        } else {
+            None
+        };
+        let header_area = header.as_ref().filter(|_| area.height > 1).map(|_| {
+            let mut header_area = area;
+            header_area.height = 1;
+            area.y += 1;
+            area.height -= 1;
+            header_area
+        });
+
+        // Checked before anything else, so that raw lines skip every transform
+        let raw = self.instance.is_raw();
+        let regex = self.regex.filter(|_| !raw);
+        let rulers = if raw { &[] } else { self.rulers };
+
         let left = self.instance.viewport().left();
         let search_color = self.instance.color_selector().peek_color();
-        let gutter_size = self
-            .gutter
-            .then(|| (self.instance.visible_line_count().max(1).ilog10() as u16 + 1).max(4));
+        let line_offset = self.instance.line_offset();
+        let gutter_size = self.instance.gutter(self.gutter).then(|| {
+            let last_line = self.instance.visible_line_count().max(1) + line_offset;
+            (last_line.ilog10() as u16 + 1).max(4)
+        });
 
         let mut itoa_buf = itoa::Buffer::new();
 
         let cursor_state = self.instance.cursor().state();
+        let flashing = self.instance.flashing_lines();
+
+        let index = self.instance.file().index().clone();
+        let top = self.instance.viewport().top();
+        self.instance
+            .update_view(area.height as usize, area.width as usize);
+        let view = self.instance.cached_view().collect::<Vec<_>>();
+
+        let mut prev_timestamp = if self.time_delta {
+            Self::timestamp_before_view(self.instance)
+        } else {
+            None
+        };
+        let delta_size = if self.time_delta { Self::DELTA_SIZE } else { 0 };
+
+        // Only the visible lines are parsed, so columns align within the window
+        let json = self.json_fields.filter(|_| !raw).map(|paths| {
+            json::align_columns(
+                view.iter()
+                    .map(|line| json::extract_fields(line.data.as_str(), paths))
+                    .collect(),
+            )
+        });
+
+        let lines = view
+            .iter()
+            .enumerate()
+            .map(|(i, line)| LineRenderData {
+                index: line.index,
+                line_number: line.line_number,
+                data: json
+                    .as_ref()
+                    .and_then(|rows| rows[i].as_deref())
+                    .unwrap_or(line.data.as_str()),
+                delta: if self.time_delta {
+                    let timestamp = timestamp::parse_leading(line.data.as_str());
+                    let delta = timestamp
+                        .zip(prev_timestamp)
+                        .map(|(curr, prev)| timestamp::format_delta(curr - prev, self.numbers));
+                    prev_timestamp = timestamp.or(prev_timestamp);
+                    delta
+                } else {
+                    None
+                },
+                // From the index alone, so that even huge lines are not read
+                length: self
+                    .line_lengths
+                    .then(|| {
+                        let start = index.data_of_line(line.line_number)?;
+                        let end = index.data_of_line(line.line_number + 1)?;
+                        Some(self.numbers.int((end - start) as usize))
+                    })
+                    .flatten(),
+                color: if raw { colors::TEXT_ACTIVE } else { line.color },
+                // Columns no longer line up with the matches of the raw line
+                matcher: line.matcher.as_ref().filter(|_| json.is_none() && !raw),
+                ty: match cursor_state {
+                    Cursor::Singleton(i) => {
+                        if line.index == i {
+                            LineType::Origin
+                        } else {
+                            LineType::None
+                        }
+                    }
+                    Cursor::Selection(start, end, _) => {
+                        if !(start..=end).contains(&line.index) {
+                            LineType::None
+                        } else if line.index == start {
+                            LineType::Origin | LineType::OriginStart
+                        } else if line.index == end {
+                            LineType::Origin | LineType::OriginEnd
+                        } else {
+                            LineType::Within
+                        }
+                    }
+                } | if line.bookmarked {
+                    LineType::Bookmarked
+                } else {
+                    LineType::None
+                } | if flashing
+                    .as_ref()
+                    .is_some_and(|lines| lines.contains(&line.line_number))
+                {
+                    LineType::Flashed
+                } else {
+                    LineType::None
+                },
+            })
+            .collect::<Vec<_>>();
+
+        // Wide enough for the longest visible line, with a space after it
+        let length_size = if self.line_lengths {
+            let widest = lines
+                .iter()
+                .filter_map(|line| line.length.as_ref())
+                .map(String::len);
+            let widest = widest.max().unwrap_or(0).max(Self::MIN_LENGTH_SIZE);
+            u16::try_from(widest).unwrap_or(u16::MAX).saturating_add(1)
+        } else {
+            0
+        };
+
+        if let Some(((line_number, data), header_area)) = header.as_ref().zip(header_area) {
+            ViewerLineWidget {
+                view_index: self.view_index,
+                start: left,
+                search_color,
+                line: Some(LineRenderData {
+                    index: top,
+                    line_number: *line_number,
+                    data: data.as_str(),
+                    delta: None,
+                    length: None,
+                    color: colors::TEXT_ACTIVE,
+                    matcher: None,
+                    ty: LineType::None,
+                }),
+                continuation: false,
+                wrap: false,
+                flash_color: self.flash_color,
+                show_selection: false,
+                selection_style: self.selection_style,
+                dim: false,
+                itoa_buf: &mut itoa_buf,
+                gutter_size,
+                line_offset,
+                delta_size,
+                length_size,
+                rulers: &[],
+                text_markers: false,
+                raw,
+                regex,
+                truncation_marker: self.truncation_marker,
+                match_style: self.match_style,
+            }
+            .render(header_area, buf, handle);
+            buf.set_style(header_area, Style::new().add_modifier(Modifier::UNDERLINED));
+        }
 
-        let view = self
-            .instance
-            .update_and_view(area.height as usize, area.width as usize);
+        let reserved =
+            length_size + delta_size + gutter_size.unwrap_or(0) + ViewerLineWidget::SPECIAL_SIZE;
+        let data_width = area.width.saturating_sub(reserved) as usize;
+        let rows = Self::layout_rows(
+            &lines,
+            area.height as usize,
+            self.wrap.then_some(data_width),
+            raw,
+        );
 
         (area.y..area.bottom())
-            .zip(view.map(Some).chain(std::iter::repeat(None)))
-            .for_each(|(y, line)| {
+            .zip(rows.into_iter().map(Some).chain(std::iter::repeat(None)))
+            .for_each(|(y, row)| {
                 ViewerLineWidget {
                     view_index: self.view_index,
-                    start: left,
+                    start: match row {
+                        Some((_, row)) if self.wrap => row * data_width,
+                        _ => left,
+                    },
                     search_color,
-                    line: line.map(|line| LineRenderData {
-                        line_number: line.line_number,
-                        data: line.data.as_str(),
-                        color: line.color,
-                        ty: match cursor_state {
-                            Cursor::Singleton(i) => {
-                                if line.index == i {
-                                    LineType::Origin
-                                } else {
-                                    LineType::None
-                                }
-                            }
-                            Cursor::Selection(start, end, _) => {
-                                if !(start..=end).contains(&line.index) {
-                                    LineType::None
-                                } else if line.index == start {
-                                    LineType::Origin | LineType::OriginStart
-                                } else if line.index == end {
-                                    LineType::Origin | LineType::OriginEnd
-                                } else {
-                                    LineType::Within
-                                }
-                            }
-                        } | if line.bookmarked {
-                            LineType::Bookmarked
-                        } else {
-                            LineType::None
-                        },
-                    }),
+                    line: row.map(|(i, _)| lines[i].clone()),
+                    continuation: row.is_some_and(|(_, row)| row > 0),
+                    wrap: self.wrap,
+                    flash_color: self.flash_color,
                     show_selection: self.show_selection,
+                    selection_style: self.selection_style,
+                    dim: self.dim,
                     itoa_buf: &mut itoa_buf,
                     gutter_size,
-                    regex: self.regex,
+                    line_offset,
+                    delta_size,
+                    length_size,
+                    rulers,
+                    text_markers: self.text_markers,
+                    raw,
+                    regex,
+                    truncation_marker: self.truncation_marker,
+                    match_style: self.match_style,
                 }
                 .render(Rect::new(area.x, y, area.width, 1), buf, handle);
             });
@@ -117,18 +366,49 @@ impl LineViewerWidget<'_> {
 struct ViewerLineWidget<'a> {
     view_index: usize,
     line: Option<LineRenderData<'a>>,
+    /// Whether this row continues the line of the row above it.
+    continuation: bool,
+    wrap: bool,
+    flash_color: Color,
 
     search_color: Color,
     itoa_buf: &'a mut itoa::Buffer,
     show_selection: bool,
+    selection_style: SelectionStyle,
+    dim: bool,
     gutter_size: Option<u16>,
+    /// Added to the line numbers shown in the gutter.
+    line_offset: usize,
+    delta_size: u16,
+    length_size: u16,
+    /// Column of the laid out line at the left edge of the row.
     start: usize,
+    rulers: &'a [usize],
+    text_markers: bool,
+    /// Lay out the line with [Cells::layout_raw].
+    raw: bool,
     regex: Option<&'a Regex>,
+    match_style: Style,
+    truncation_marker: Style,
 }
 
 impl ViewerLineWidget<'_> {
-    fn gutter_selection(line: &LineRenderData) -> &'static str {
-        if line.ty.contains(LineType::Origin) {
+    /// Columns between the gutter and the data, holding the selection marker.
+    const SPECIAL_SIZE: u16 = 3;
+
+    /// Shown in the gutter instead of the line number on the rows that
+    /// continue a wrapped line.
+    const CONTINUATION: &'static str = "↪";
+
+    fn gutter_selection(&self, line: &LineRenderData) -> &'static str {
+        if self.continuation {
+            // The selection runs down every row of the line
+            if line.ty.intersects(LineType::Origin | LineType::Within) {
+                "│"
+            } else {
+                ""
+            }
+        } else if line.ty.contains(LineType::Origin) {
             if line.ty.contains(LineType::OriginStart) {
                 "┌ "
             } else if line.ty.contains(LineType::OriginEnd) {
@@ -143,8 +423,91 @@ impl ViewerLineWidget<'_> {
         }
     }
 
-    fn split_line(&self, area: Rect) -> [Rect; 3] {
-        const SPECIAL_SIZE: u16 = 3;
+    /// Stands in for the colors of bookmarked and matched lines, on the first
+    /// row of the line.
+    fn text_marker(&self, line: &LineRenderData) -> &'static str {
+        if self.continuation {
+            ""
+        } else if line.ty.contains(LineType::Bookmarked) {
+            "*"
+        } else if line.matcher.is_some() {
+            "+"
+        } else {
+            ""
+        }
+    }
+
+    /// The match color always stays visible; the selection is layered on top
+    /// of it according to the selection style.
+    fn line_style(&self, line: &LineRenderData) -> Style {
+        let mut style = Style::new().fg(line.color);
+        if line.matcher.is_some() {
+            style = style.patch(self.match_style);
+        }
+        if self.show_selection && line.ty.intersects(LineType::Origin | LineType::Within) {
+            style = match self.selection_style {
+                SelectionStyle::Background => style.bg(colors::SELECT_BG),
+                SelectionStyle::Reverse => style.add_modifier(Modifier::REVERSED),
+            };
+        }
+        // The flash goes over the selection, which is usually what was copied
+        if line.ty.contains(LineType::Flashed) {
+            style = style
+                .bg(self.flash_color)
+                .remove_modifier(Modifier::REVERSED);
+        }
+        style
+    }
+
+    /// Splits the visible data into spans at the boundaries of the matches,
+    /// dimming everything outside of `matches` if given, and highlighting the
+    /// search preview. All ranges are relative to `data`.
+    fn data_spans<'b>(
+        &self,
+        data: &'b str,
+        matches: Option<&[Range<usize>]>,
+        highlight: Option<Range<usize>>,
+    ) -> Vec<Span<'b>> {
+        let mut bounds = vec![0, data.len()];
+        for range in matches.into_iter().flatten().chain(highlight.as_ref()) {
+            bounds.extend([range.start, range.end]);
+        }
+        bounds.retain(|&i| data.is_char_boundary(i));
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        bounds
+            .windows(2)
+            .map(|window| {
+                let (start, end) = (window[0], window[1]);
+                let within = |range: &Range<usize>| range.start <= start && end <= range.end;
+                let mut style = Style::new();
+                if matches.is_some_and(|matches| !matches.iter().any(within)) {
+                    style = style.add_modifier(Modifier::DIM);
+                }
+                if highlight.as_ref().is_some_and(within) {
+                    style = style.bg(self.search_color);
+                }
+                Span::styled(&data[start..end], style)
+            })
+            .collect()
+    }
+
+    fn split_line(&self, area: Rect) -> [Rect; 5] {
+        let mut length_chunk = area;
+        length_chunk.width = self.length_size.saturating_sub(1).min(area.width);
+
+        let mut area = area;
+        let length_width = self.length_size.min(area.width);
+        area.x += length_width;
+        area.width -= length_width;
+
+        let mut delta_chunk = area;
+        delta_chunk.width = self.delta_size.min(area.width);
+
+        area.x += delta_chunk.width;
+        area.width -= delta_chunk.width;
+
         let gutter_size = self.gutter_size.unwrap_or(0);
         let mut gutter_chunk = area;
         gutter_chunk.width = gutter_size;
@@ -154,14 +517,27 @@ impl ViewerLineWidget<'_> {
         type_chunk.width = 1;
 
         let mut data_chunk = area;
-        data_chunk.x += gutter_size + SPECIAL_SIZE;
-        data_chunk.width = data_chunk.width.saturating_sub(gutter_size + SPECIAL_SIZE);
+        data_chunk.x += gutter_size + Self::SPECIAL_SIZE;
+        data_chunk.width = data_chunk
+            .width
+            .saturating_sub(gutter_size + Self::SPECIAL_SIZE);
 
-        [gutter_chunk, type_chunk, data_chunk]
+        [
+            length_chunk,
+            delta_chunk,
+            gutter_chunk,
+            type_chunk,
+            data_chunk,
+        ]
     }
 
     pub fn render(self, area: Rect, buf: &mut Buffer, handle: &mut MouseHandler) {
-        let [gutter_chunk, type_chunk, data_chunk] = self.split_line(area);
+        let [length_chunk, delta_chunk, gutter_chunk, type_chunk, data_chunk] =
+            self.split_line(area);
+
+        // Reset the whole row so that a shorter line does not leave behind
+        // glyphs from whatever was previously drawn there.
+        Clear.render(area, buf);
 
         let Some(line) = &self.line else {
             let ln = Paragraph::new("~")
@@ -172,8 +548,15 @@ impl ViewerLineWidget<'_> {
             return;
         };
 
-        if self.gutter_size.is_some() {
-            let ln_str = self.itoa_buf.format(line.line_number + 1);
+        if self.gutter_size.is_some() && self.continuation {
+            Paragraph::new(Self::CONTINUATION)
+                .alignment(Alignment::Right)
+                .fg(colors::GUTTER_TEXT)
+                .render(gutter_chunk, buf);
+        } else if self.gutter_size.is_some() {
+            let ln_str = self
+                .itoa_buf
+                .format(line.line_number + self.line_offset + 1);
             let ln = Paragraph::new(ln_str).alignment(Alignment::Right).fg(
                 if line.ty.contains(LineType::Bookmarked) {
                     colors::SELECT_ACCENT
@@ -185,42 +568,508 @@ impl ViewerLineWidget<'_> {
             ln.render(gutter_chunk, buf);
         }
 
-        if self.show_selection {
-            Paragraph::new(Self::gutter_selection(line))
-                .fg(colors::SELECT_ACCENT)
-                .render(type_chunk, buf);
+        if let Some(length) = line.length.as_ref().filter(|_| !self.continuation) {
+            Paragraph::new(length.as_str())
+                .alignment(Alignment::Right)
+                .fg(colors::GUTTER_TEXT)
+                .render(length_chunk, buf);
         }
 
-        let mut chars = line.data.chars();
-        for _ in 0..self.start {
-            chars.next();
+        if let Some(delta) = line.delta.as_ref().filter(|_| !self.continuation) {
+            Paragraph::new(delta.as_str())
+                .alignment(Alignment::Right)
+                .fg(colors::GUTTER_TEXT)
+                .render(delta_chunk, buf);
         }
-        let data = &chars.as_str()
-            [..(data_chunk.width as usize).min(line.data.len().saturating_sub(self.start))];
 
-        if let Some(m) = self.regex.and_then(|r| r.find(data.as_bytes())) {
-            let start = m.start();
-            let end = m.end();
-            let spans = vec![
-                Span::raw(&data[..start]),
-                Span::raw(&data[start..end]).bg(self.search_color),
-                Span::raw(&data[end..]),
-            ];
-            Paragraph::new(Line::from(spans))
+        let selection = if self.show_selection {
+            self.gutter_selection(line)
         } else {
-            Paragraph::new(data)
+            ""
+        };
+        if !selection.is_empty() {
+            Paragraph::new(selection)
+                .fg(colors::SELECT_ACCENT)
+                .render(type_chunk, buf);
+        } else if self.text_markers {
+            Paragraph::new(self.text_marker(line)).render(type_chunk, buf);
         }
-        .fg(line.color)
+
+        // The terminator is not drawn, nor does it count as part of the line
+        let text = line.data.trim_end_matches(['\r', '\n']);
+        let cells = if self.raw {
+            Cells::layout_raw(text, self.start, usize::from(data_chunk.width))
+        } else {
+            Cells::layout(text, self.start, usize::from(data_chunk.width))
+        };
+        // Nothing is cut off when wrapping, the rest is on the next row
+        let hidden_left = !self.wrap && cells.hidden_left;
+        let hidden_right = !self.wrap && cells.hidden_right;
+        let data = cells.text.as_str();
+
+        // Matches are found on the whole line so that they are not cut short
+        // by the edges of the view, then carried over to where they are drawn
+        let matches = line.matcher.filter(|_| self.dim).map(|matcher| {
+            matcher
+                .find_iter(text.as_bytes())
+                .map(|m| cells.map(m.range()))
+                .filter(|range| !range.is_empty())
+                .collect::<Vec<_>>()
+        });
+        let highlight = self.regex.and_then(|r| {
+            r.find_iter(text.as_bytes())
+                .map(|m| cells.map(m.range()))
+                .find(|range| !range.is_empty())
+        });
+
+        Paragraph::new(Line::from(self.data_spans(
+            data,
+            matches.as_deref(),
+            highlight,
+        )))
+        .style(self.line_style(line))
         .render(data_chunk, buf);
 
+        // Rulers are at the columns of the laid out line, which is scrolled by
+        // columns too. They only show through blank cells.
+        if !self.rulers.is_empty() {
+            let guide = Style::new()
+                .fg(colors::GUTTER_TEXT)
+                .add_modifier(Modifier::DIM);
+            for column in self
+                .rulers
+                .iter()
+                .filter_map(|c| c.checked_sub(1 + self.start))
+            {
+                if column >= usize::from(data_chunk.width) {
+                    continue;
+                }
+                let cell = &mut buf[(data_chunk.x + column as u16, data_chunk.y)];
+                if cell.symbol() == " " {
+                    cell.set_symbol("│").set_style(guide);
+                }
+            }
+        }
+
+        if data_chunk.width > 0 {
+            if hidden_left {
+                buf.set_string(data_chunk.x, data_chunk.y, "<", self.truncation_marker);
+            }
+            if hidden_right {
+                let x = data_chunk.right() - 1;
+                buf.set_string(x, data_chunk.y, ">", self.truncation_marker);
+            }
+        }
+
         if let Some(line) = self.line {
+            let here = DragAnchor {
+                view_index: self.view_index,
+                index: line.index,
+            };
+            let anchor = handle
+                .drag_anchor()
+                .filter(|anchor| anchor.view_index == self.view_index);
+            let mut pressed = false;
+
             handle.on_mouse(area, |event| match event.kind {
-                MouseEventKind::Down(_) => Some(Action::Visual(VisualAction::ToggleLine {
-                    line_number: line.line_number,
-                    target_view: self.view_index,
-                })),
+                MouseEventKind::Down(MouseButton::Left) => {
+                    pressed = true;
+                    None
+                }
+                // A click without dragging toggles the bookmark
+                MouseEventKind::Down(_) | MouseEventKind::Up(MouseButton::Left)
+                    if anchor.is_none_or(|anchor| anchor == here) =>
+                {
+                    Some(Action::Visual(VisualAction::ToggleLine {
+                        line_number: line.line_number,
+                        target_view: self.view_index,
+                    }))
+                }
+                MouseEventKind::Drag(MouseButton::Left) | MouseEventKind::Up(MouseButton::Left) => {
+                    anchor.map(|anchor| {
+                        Action::Visual(VisualAction::SelectRange {
+                            target_view: self.view_index,
+                            anchor: anchor.index,
+                            index: line.index,
+                        })
+                    })
+                }
                 _ => None,
             });
+
+            if pressed {
+                handle.set_drag_anchor(here);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::config::theme::Theme;
+
+    fn render_row(area: Rect, buf: &mut Buffer, data: &str, start: usize) {
+        let mut itoa_buf = itoa::Buffer::new();
+        row_widget(data, start, &mut itoa_buf).render(area, buf, &mut MouseHandler::new());
+    }
+
+    /// A plain row of `data` scrolled to `start`, for the tests to adjust.
+    fn row_widget<'a>(
+        data: &'a str,
+        start: usize,
+        itoa_buf: &'a mut itoa::Buffer,
+    ) -> ViewerLineWidget<'a> {
+        ViewerLineWidget {
+            view_index: 0,
+            line: Some(LineRenderData {
+                index: 0,
+                line_number: 0,
+                data,
+                delta: None,
+                length: None,
+                color: Color::White,
+                matcher: None,
+                ty: LineType::None,
+            }),
+            continuation: false,
+            wrap: false,
+            flash_color: Color::Reset,
+            search_color: Color::Reset,
+            itoa_buf,
+            show_selection: false,
+            selection_style: SelectionStyle::Background,
+            dim: false,
+            gutter_size: None,
+            line_offset: 0,
+            delta_size: 0,
+            length_size: 0,
+            start,
+            rulers: &[],
+            text_markers: false,
+            raw: false,
+            regex: None,
+            truncation_marker: Theme::default().truncation_marker,
+            match_style: Style::new(),
         }
     }
+
+    fn row_symbols(buf: &Buffer, area: Rect) -> String {
+        (area.x..area.right())
+            .map(|x| buf[(x, area.y)].symbol())
+            .collect()
+    }
+
+    #[test]
+    fn render_clears_trailing_cells() {
+        let area = Rect::new(0, 0, 20, 1);
+        let mut buf = Buffer::empty(area);
+
+        render_row(area, &mut buf, "a much longer line of text", 0);
+        render_row(area, &mut buf, "short", 0);
+
+        assert_eq!(row_symbols(&buf, area), format!("{:<20}", "   short"));
+    }
+
+    #[test]
+    fn render_clears_stale_cells() {
+        let area = Rect::new(0, 0, 12, 1);
+        let mut buf = Buffer::empty(area);
+        buf.set_string(0, 0, "x".repeat(12), Style::new());
+
+        render_row(area, &mut buf, "", 0);
+
+        assert_eq!(row_symbols(&buf, area), " ".repeat(12));
+    }
+
+    #[test]
+    fn render_marks_truncated_lines() {
+        let area = Rect::new(0, 0, 10, 1);
+        let mut buf = Buffer::empty(area);
+
+        render_row(area, &mut buf, "0123456789", 0);
+        assert_eq!(row_symbols(&buf, area), "   012345>");
+
+        render_row(area, &mut buf, "0123456789", 2);
+        assert_eq!(row_symbols(&buf, area), "   <34567>");
+        assert_eq!(buf[(3, 0)].fg, colors::TRUNCATION_MARKER);
+        assert_eq!(buf[(9, 0)].fg, colors::TRUNCATION_MARKER);
+
+        render_row(area, &mut buf, "0123456789", 4);
+        assert_eq!(row_symbols(&buf, area), "   <56789 ");
+
+        // A line that just fits is not cut off by its terminator
+        render_row(area, &mut buf, "0123456\n", 0);
+        assert_eq!(row_symbols(&buf, area), "   0123456");
+        render_row(area, &mut buf, "01234567\n", 0);
+        assert_eq!(row_symbols(&buf, area), "   012345>");
+    }
+
+    #[test]
+    fn render_drops_line_terminators() {
+        let area = Rect::new(0, 0, 10, 1);
+        let mut buf = Buffer::empty(area);
+
+        render_row(area, &mut buf, "abc\n", 0);
+        assert_eq!(row_symbols(&buf, area), "   abc    ");
+
+        render_row(area, &mut buf, "abc\r\n", 0);
+        assert_eq!(row_symbols(&buf, area), "   abc    ");
+    }
+
+    /// The symbol of each cell of the data and whether it is dimmed.
+    fn dimmed_cells(buf: &Buffer, area: Rect) -> Vec<(&str, bool)> {
+        (area.x + ViewerLineWidget::SPECIAL_SIZE..area.right())
+            .map(|x| {
+                let cell = &buf[(x, area.y)];
+                (cell.symbol(), cell.modifier.contains(Modifier::DIM))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn render_dims_outside_matches() {
+        let area = Rect::new(0, 0, 12, 1);
+        let mut buf = Buffer::empty(area);
+        let matcher = Regex::new("ab").unwrap();
+        let mut itoa_buf = itoa::Buffer::new();
+        let mut widget = row_widget("abXYab", 0, &mut itoa_buf);
+        widget.dim = true;
+        widget.line.as_mut().unwrap().matcher = Some(&matcher);
+        widget.render(area, &mut buf, &mut MouseHandler::new());
+
+        // Matches at the start and the end of the line are kept
+        let cells = dimmed_cells(&buf, area);
+        assert_eq!(
+            &cells[..6],
+            [
+                ("a", false),
+                ("b", false),
+                ("X", true),
+                ("Y", true),
+                ("a", false),
+                ("b", false)
+            ]
+        );
+        // The filter color stays under the dimming
+        assert!((3..9).all(|x| buf[(x, 0)].fg == Color::White));
+    }
+
+    #[test]
+    fn render_dims_around_cut_off_and_wide_matches() {
+        let area = Rect::new(0, 0, 10, 1);
+        let mut buf = Buffer::empty(area);
+        let matcher = Regex::new("日本|cd").unwrap();
+        let mut itoa_buf = itoa::Buffer::new();
+        // Scrolled past the `c` of `cd`, which is still matched
+        let mut widget = row_widget("abcdé日本x", 2, &mut itoa_buf);
+        widget.dim = true;
+        widget.line.as_mut().unwrap().matcher = Some(&matcher);
+        widget.render(area, &mut buf, &mut MouseHandler::new());
+
+        let cells = dimmed_cells(&buf, area);
+        assert_eq!(cells[0].0, "<");
+        assert_eq!(cells[1], ("d", false));
+        assert_eq!(cells[2], ("é", true));
+        assert_eq!(cells[3], ("日", false));
+        assert_eq!(cells[5], ("本", false));
+        assert_eq!(cells[6].0, ">");
+    }
+
+    #[test]
+    fn render_dim_composes_with_the_search_highlight() {
+        let area = Rect::new(0, 0, 12, 1);
+        let mut buf = Buffer::empty(area);
+        let matcher = Regex::new("ab").unwrap();
+        let search = Regex::new("bX").unwrap();
+        let mut itoa_buf = itoa::Buffer::new();
+        let mut widget = row_widget("abXY", 0, &mut itoa_buf);
+        widget.dim = true;
+        widget.search_color = Color::Blue;
+        widget.regex = Some(&search);
+        widget.line.as_mut().unwrap().matcher = Some(&matcher);
+        widget.render(area, &mut buf, &mut MouseHandler::new());
+
+        let cell = |x: u16| {
+            let cell = &buf[(x, 0)];
+            (cell.bg, cell.modifier.contains(Modifier::DIM))
+        };
+        assert_eq!(cell(3), (Color::Reset, false));
+        // The highlight spans the end of the match and the dimmed text after it
+        assert_eq!(cell(4), (Color::Blue, false));
+        assert_eq!(cell(5), (Color::Blue, true));
+        assert_eq!(cell(6), (Color::Reset, true));
+    }
+
+    fn line(data: &str, ty: LineType) -> LineRenderData<'_> {
+        LineRenderData {
+            index: 0,
+            line_number: 0,
+            data,
+            delta: None,
+            length: None,
+            color: Color::White,
+            matcher: None,
+            ty,
+        }
+    }
+
+    #[test]
+    fn layout_wraps_long_lines() {
+        let lines = [
+            line("0123456789", LineType::None),
+            line("", LineType::None),
+            line("abcd", LineType::None),
+        ];
+        assert_eq!(
+            LineViewerWidget::layout_rows(&lines, 10, Some(4), false),
+            [(0, 0), (0, 1), (0, 2), (1, 0), (2, 0)]
+        );
+        assert_eq!(
+            LineViewerWidget::layout_rows(&lines, 2, None, false),
+            [(0, 0), (1, 0)]
+        );
+    }
+
+    #[test]
+    fn layout_wraps_by_columns() {
+        // A line of exactly the width needs no second row for its terminator
+        assert_eq!(LineViewerWidget::wrapped_rows("abcd\n", 4, false), 1);
+        assert_eq!(LineViewerWidget::wrapped_rows("abcd\r\n", 4, false), 1);
+        assert_eq!(LineViewerWidget::wrapped_rows("abcde\n", 4, false), 2);
+        // Wide characters and tabs take up more than one column
+        assert_eq!(LineViewerWidget::wrapped_rows("日本語\n", 4, false), 2);
+        assert_eq!(LineViewerWidget::wrapped_rows("a\tb\n", 4, false), 3);
+        assert_eq!(LineViewerWidget::wrapped_rows("a\tb\n", 4, true), 1);
+    }
+
+    #[test]
+    fn layout_keeps_cursor_line_visible() {
+        let lines = [
+            line("0123456789", LineType::None),
+            line("0123456789", LineType::None),
+            line("0123", LineType::Origin),
+            line("abcd", LineType::None),
+        ];
+        assert_eq!(
+            LineViewerWidget::layout_rows(&lines, 4, Some(4), false),
+            [(1, 0), (1, 1), (1, 2), (2, 0)]
+        );
+    }
+
+    #[test]
+    fn render_marks_continuation_rows() {
+        let area = Rect::new(0, 0, 10, 1);
+        let mut buf = Buffer::empty(area);
+        let mut itoa_buf = itoa::Buffer::new();
+        ViewerLineWidget {
+            view_index: 0,
+            line: Some(line("0123456789", LineType::Origin)),
+            continuation: true,
+            wrap: true,
+            flash_color: Color::Reset,
+            search_color: Color::Reset,
+            itoa_buf: &mut itoa_buf,
+            show_selection: true,
+            selection_style: SelectionStyle::Background,
+            dim: false,
+            gutter_size: Some(4),
+            line_offset: 0,
+            delta_size: 0,
+            length_size: 0,
+            start: 3,
+            rulers: &[],
+            text_markers: false,
+            raw: false,
+            regex: None,
+            truncation_marker: Style::new(),
+            match_style: Style::new(),
+        }
+        .render(area, &mut buf, &mut MouseHandler::new());
+
+        assert_eq!(row_symbols(&buf, area), "   ↪ │ 345");
+    }
+
+    #[test]
+    fn render_rulers_follow_scroll() {
+        let area = Rect::new(0, 0, 12, 1);
+        let mut buf = Buffer::empty(area);
+        let mut itoa_buf = itoa::Buffer::new();
+        let mut render = |data: &str, start: usize| {
+            ViewerLineWidget {
+                view_index: 0,
+                line: Some(line(data, LineType::None)),
+                continuation: false,
+                wrap: false,
+                flash_color: Color::Reset,
+                search_color: Color::Reset,
+                itoa_buf: &mut itoa_buf,
+                show_selection: false,
+                selection_style: SelectionStyle::Background,
+                dim: false,
+                gutter_size: None,
+                line_offset: 0,
+                delta_size: 0,
+                length_size: 0,
+                start,
+                rulers: &[3, 6],
+                text_markers: false,
+                raw: false,
+                regex: None,
+                truncation_marker: Style::new(),
+                match_style: Style::new(),
+            }
+            .render(area, &mut buf, &mut MouseHandler::new());
+            row_symbols(&buf, area)
+        };
+
+        assert_eq!(render("ab", 0), "   ab│  │   ");
+        assert_eq!(render("abcd", 0), "   abcd │   ");
+        assert_eq!(render("abcd", 2), "   <d │     ");
+        // Columns are those on screen, so a wide character takes up two
+        assert_eq!(render("日", 0), "   日 │  │   ");
+    }
+
+    #[test]
+    fn render_time_deltas_from_the_line_before_the_view() {
+        let data: &[u8] = b"12:00:00 a\n12:00:01.5 b\nno time\n12:00:04 c\n";
+        let buf =
+            bvr_core::SegBuffer::read_stream(Box::new(std::io::Cursor::new(data)), true).unwrap();
+        let colors = crate::colors::ColorSelector::Color256 { index: 0 };
+        let mut instance = Instance::with_color_selector(String::from("test"), buf, colors);
+        instance.viewport_mut().top_to(1);
+
+        let area = Rect::new(0, 0, 24, 3);
+        let mut buf = Buffer::empty(area);
+        LineViewerWidget {
+            view_index: 0,
+            instance: &mut instance,
+            show_selection: false,
+            gutter: false,
+            time_delta: true,
+            line_lengths: false,
+            numbers: NumberFormat::default(),
+            selection_style: SelectionStyle::Background,
+            dim: false,
+            wrap: false,
+            flash_color: Color::Reset,
+            json_fields: None,
+            rulers: &[],
+            text_markers: false,
+            regex: None,
+            truncation_marker: Style::new(),
+            match_style: Style::new(),
+        }
+        .render(area, &mut buf, &mut MouseHandler::new());
+
+        let delta = |y| {
+            let row = Rect::new(0, y, LineViewerWidget::DELTA_SIZE, 1);
+            row_symbols(&buf, row)
+        };
+        // The first line in view is timed from the line above it
+        assert_eq!(delta(0), "  +1.50s");
+        assert_eq!(delta(1), "        ");
+        // Lines without a time are skipped over
+        assert_eq!(delta(2), "  +2.50s");
+    }
 }
@@ -0,0 +1,257 @@
+use ratatui::text::Span;
+use std::ops::Range;
+
+/// Columns between tab stops.
+pub const TAB_WIDTH: usize = 8;
+
+/// What a character of a line is drawn as.
+enum Glyph {
+    /// The character itself, taking up this many columns.
+    Char(char, usize),
+    /// Plain ASCII standing in for a character that cannot be drawn, one
+    /// column per byte, such as the spaces of a tab or `^A`.
+    Ascii(String),
+}
+
+impl Glyph {
+    /// Tabs are expanded to the next tab stop after `column`. Other control
+    /// characters are shown in caret notation, or as their code when there
+    /// is none, except for the `\r` of a `\r\n` line ending.
+    fn of(c: char, column: usize, last: bool) -> Self {
+        match c {
+            '\t' => Self::Ascii(" ".repeat(TAB_WIDTH - column % TAB_WIDTH)),
+            '\r' if last => Self::Ascii(String::new()),
+            '\x7f' => Self::Ascii(String::from("^?")),
+            c if c < ' ' => Self::Ascii(format!("^{}", char::from(c as u8 + b'@'))),
+            c if c.is_control() => Self::Ascii(format!("<{:02x}>", u32::from(c))),
+            c => Self::Char(c, char_width(c)),
+        }
+    }
+
+    /// Every character as itself, except that control characters, which
+    /// would move the terminal cursor, are drawn like invalid bytes.
+    fn raw(c: char, _column: usize, _last: bool) -> Self {
+        if c.is_control() {
+            Self::Char(char::REPLACEMENT_CHARACTER, 1)
+        } else {
+            Self::Char(c, char_width(c))
+        }
+    }
+
+    fn width(&self) -> usize {
+        match self {
+            Self::Char(_, width) => *width,
+            Self::Ascii(text) => text.len(),
+        }
+    }
+}
+
+/// Measured the way ratatui measures it, so that the layout agrees with what
+/// it draws.
+fn char_width(c: char) -> usize {
+    Span::raw(c.encode_utf8(&mut [0; 4]) as &str).width()
+}
+
+/// Byte offset, first column and width of each character of a line once
+/// laid out.
+fn columns(
+    line: &str,
+    glyph: fn(char, usize, bool) -> Glyph,
+) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+    let mut column = 0;
+    let mut chars = line.char_indices().peekable();
+    std::iter::from_fn(move || {
+        let (i, c) = chars.next()?;
+        let from = column;
+        column += glyph(c, column, chars.peek().is_none()).width();
+        Some((i, from, column - from))
+    })
+}
+
+/// Columns a line takes up once laid out.
+pub fn width(line: &str) -> usize {
+    columns(line, Glyph::of)
+        .last()
+        .map_or(0, |(_, from, width)| from + width)
+}
+
+/// Columns a line takes up once laid out with [Cells::layout_raw].
+pub fn raw_width(line: &str) -> usize {
+    columns(line, Glyph::raw)
+        .last()
+        .map_or(0, |(_, from, width)| from + width)
+}
+
+/// Column of the character at byte `offset` of a line once laid out.
+pub fn column_of(line: &str, offset: usize) -> usize {
+    columns(line, Glyph::of)
+        .find(|&(i, ..)| i >= offset)
+        .map_or_else(|| width(line), |(_, from, _)| from)
+}
+
+/// Byte offset of the character drawn at `column` of a line, if any.
+pub fn offset_at(line: &str, column: usize) -> Option<usize> {
+    columns(line, Glyph::of)
+        .find(|&(_, from, width)| column < from + width)
+        .map(|(i, ..)| i)
+}
+
+/// The part of a line that falls in a window of columns, laid out with
+/// [Glyph::of], along with where each of its bytes ended up. Match spans
+/// found on the raw line are carried over with [Cells::map].
+pub struct Cells {
+    /// What to draw, no wider than the window.
+    pub text: String,
+    /// Bytes of the line laid out in the window.
+    range: Range<usize>,
+    /// Offset into `text` of each byte in `range`, then of its end.
+    offsets: Vec<usize>,
+    /// Whether some of the line is cut off on the left of the window.
+    pub hidden_left: bool,
+    /// Whether some of the line is cut off on the right of the window.
+    pub hidden_right: bool,
+}
+
+impl Cells {
+    /// Lays out the `width` columns of the line after the first `skip`.
+    /// Characters cut by the edges of the window show the part of them
+    /// inside it, or blanks for those that cannot be cut.
+    pub fn layout(line: &str, skip: usize, width: usize) -> Self {
+        Self::layout_with(line, skip, width, Glyph::of)
+    }
+
+    /// Like [Cells::layout], but with no transforms, for when one of them
+    /// gets in the way: tabs and other control characters are drawn as
+    /// replacement characters, like invalid bytes.
+    pub fn layout_raw(line: &str, skip: usize, width: usize) -> Self {
+        Self::layout_with(line, skip, width, Glyph::raw)
+    }
+
+    fn layout_with(
+        line: &str,
+        skip: usize,
+        width: usize,
+        glyph: fn(char, usize, bool) -> Glyph,
+    ) -> Self {
+        let end = skip + width;
+        let mut cells = Self {
+            text: String::new(),
+            range: line.len()..line.len(),
+            offsets: Vec::new(),
+            hidden_left: false,
+            hidden_right: false,
+        };
+
+        let mut column = 0;
+        // Characters without width go with the one before them
+        let mut placed = false;
+        let mut chars = line.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let glyph = glyph(c, column, chars.peek().is_none());
+            let glyph_width = glyph.width();
+            let (from, to) = (column, column + glyph_width);
+            column = to;
+
+            if glyph_width == 0 {
+                if !placed {
+                    continue;
+                }
+            } else if to <= skip {
+                cells.hidden_left = true;
+                continue;
+            } else if from >= end || to > end && from >= skip {
+                // Nothing is drawn cut off on the right
+                cells.hidden_right = true;
+                break;
+            }
+            placed = true;
+            if cells.offsets.is_empty() {
+                cells.range.start = i;
+            }
+
+            let visible = from.max(skip)..to.min(end);
+            cells.hidden_left |= from < skip;
+            cells
+                .offsets
+                .extend(std::iter::repeat_n(cells.text.len(), c.len_utf8()));
+            match glyph {
+                Glyph::Char(c, _) if visible.len() == glyph_width => cells.text.push(c),
+                Glyph::Char(..) => cells.text.push_str(&" ".repeat(visible.len())),
+                Glyph::Ascii(text) => {
+                    cells
+                        .text
+                        .push_str(&text[visible.start - from..visible.end - from]);
+                }
+            }
+            cells.range.end = i + c.len_utf8();
+        }
+        cells.offsets.push(cells.text.len());
+        cells
+    }
+
+    /// Where a range of bytes of the line is in [Cells::text], cut down to
+    /// the window.
+    pub fn map(&self, range: Range<usize>) -> Range<usize> {
+        let offset =
+            |i: usize| self.offsets[i.clamp(self.range.start, self.range.end) - self.range.start];
+        offset(range.start)..offset(range.end)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn layout_expands_tabs_to_stops() {
+        let cells = Cells::layout("a\tb", 0, 20);
+        assert_eq!(cells.text, "a       b");
+        assert_eq!(cells.map(2..3), 8..9);
+        assert_eq!(width("a\tb"), 9);
+
+        // A tab cut by the window keeps the columns left of its stop
+        let cells = Cells::layout("a\tb", 4, 20);
+        assert_eq!(cells.text, "    b");
+        assert!(cells.hidden_left);
+    }
+
+    #[test]
+    fn columns_of_offsets() {
+        let line = "a\t日b";
+        assert_eq!(column_of(line, 2), 8);
+        assert_eq!(column_of(line, 5), 10);
+        assert_eq!(offset_at(line, 5), Some(1));
+        assert_eq!(offset_at(line, 9), Some(2));
+        assert_eq!(offset_at(line, 11), None);
+    }
+
+    #[test]
+    fn layout_shows_control_characters() {
+        let cells = Cells::layout("a\x01b\x1b[0m\r", 0, 20);
+        assert_eq!(cells.text, "a^Ab^[[0m");
+        // Spans after a control character move over by its extra column
+        assert_eq!(cells.map(2..3), 3..4);
+        assert_eq!(cells.map(1..2), 1..3);
+        assert_eq!(Cells::layout("\u{85}x", 0, 20).text, "<85>x");
+
+        let cells = Cells::layout_raw("a\tb\x1b[0m\r", 0, 20);
+        assert_eq!(cells.text, "a\u{fffd}b\u{fffd}[0m\u{fffd}");
+        assert_eq!(cells.map(2..3), 4..5);
+        assert_eq!(raw_width("a\tb"), 3);
+    }
+
+    #[test]
+    fn layout_clips_to_window() {
+        let cells = Cells::layout("0123456789", 2, 5);
+        assert_eq!(cells.text, "23456");
+        assert!(cells.hidden_left && cells.hidden_right);
+        assert_eq!(cells.map(0..4), 0..2);
+        assert_eq!(cells.map(6..10), 4..5);
+        assert_eq!(cells.map(8..10), 5..5);
+
+        // Caret notation is cut like any other text, wide characters are not
+        assert_eq!(Cells::layout("\x01b", 1, 5).text, "Ab");
+        assert_eq!(Cells::layout("日本", 1, 5).text, " 本");
+        assert_eq!(Cells::layout("a日", 0, 2).text, "a");
+    }
+}
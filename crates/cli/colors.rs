@@ -1,4 +1,8 @@
-use ratatui::{palette::Hsl, style::Color};
+use ratatui::{
+    buffer::Buffer,
+    palette::Hsl,
+    style::{Color, Modifier, Style},
+};
 
 pub const WHITE: Color = Color::Indexed(255);
 pub const BLACK: Color = Color::Indexed(16);
@@ -7,8 +11,8 @@ pub const BG: Color = Color::Reset;
 pub const TEXT_ACTIVE: Color = Color::Indexed(253);
 pub const TEXT_INACTIVE: Color = Color::Indexed(238);
 
-pub const GUTTER_BG: Color = BG;
 pub const GUTTER_TEXT: Color = Color::Indexed(241);
+pub const TRUNCATION_MARKER: Color = Color::Indexed(203);
 
 pub const TAB_INACTIVE: Color = Color::Indexed(235);
 pub const TAB_ACTIVE: Color = Color::Indexed(239);
@@ -23,11 +27,36 @@ pub const COMMAND_BAR_SELECT: Color = Color::Indexed(69);
 pub const NORMAL_ACCENT: Color = Color::Indexed(75);
 pub const COMMAND_ACCENT: Color = Color::Indexed(48);
 pub const SELECT_ACCENT: Color = Color::Indexed(170);
+pub const SELECT_BG: Color = Color::Indexed(237);
+/// Behind lines that were just copied.
+pub const FLASH_BG: Color = Color::Indexed(58);
 pub const FILTER_ACCENT: Color = Color::Indexed(178);
 pub const CONFIG_ACCENT: Color = Color::Indexed(213);
 
 pub const SHELL_ACCENT: Color = Color::Indexed(161);
+pub const ERROR_ACCENT: Color = Color::Indexed(203);
 
+pub const DIFF_REMOVED: Color = Color::Indexed(167);
+pub const DIFF_ADDED: Color = Color::Indexed(114);
+
+/// Default of [Theme::match_focused](crate::components::config::theme::Theme).
+pub const MATCH_FOCUSED: Style = Style::new().add_modifier(Modifier::BOLD);
+/// Default of [Theme::match_unfocused](crate::components::config::theme::Theme).
+pub const MATCH_UNFOCUSED: Style = Style::new().add_modifier(Modifier::DIM);
+
+/// Takes the colors out of a drawn frame. Cells with a background, such as
+/// the bars and highlights, are reversed instead so that they stand out.
+pub fn monochrome(buf: &mut Buffer) {
+    for cell in &mut buf.content {
+        if cell.bg != Color::Reset {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+    }
+}
+
+#[derive(Clone, Copy)]
 pub enum ColorSelector {
     Color256 { index: u8 },
     TrueColor { hue: f32 },
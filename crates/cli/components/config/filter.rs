@@ -45,7 +45,7 @@ impl FilterConfigApp {
             .and_then(|path| std::fs::File::open(path).ok())
             .map(std::io::BufReader::new)
             .and_then(|reader| serde_json::from_reader::<_, LoadedFilterData>(reader).ok())
-            .unwrap_or_else(LoadedFilterData::default)
+            .unwrap_or_default()
     }
 
     fn load_read_save<F, R>(&mut self, f: F) -> Result<Option<R>>
@@ -116,12 +116,6 @@ impl FilterConfigApp {
         })
     }
 
-    pub fn remove_filter(&mut self, index: usize) -> Result<()> {
-        self.load_and_save(|data| {
-            data.filters.remove(index);
-        })
-    }
-
     pub fn update_and_filter_view(
         &mut self,
         viewport_height: usize,
@@ -157,13 +151,6 @@ impl FilterConfigApp {
         self.viewport.jump_vertically_to(i);
     }
 
-    pub fn clear_filters(&mut self) -> Result<()> {
-        self.cursor = CursorState::new();
-        self.load_and_save(|data| {
-            data.filters.clear();
-        })
-    }
-
     pub fn selected_filter(&self) -> Option<&FilterExportSet> {
         match self.cursor.state() {
             Cursor::Singleton(i) => self.filters().get(i),
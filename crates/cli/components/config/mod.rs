@@ -1,4 +1,6 @@
 pub mod filter;
+pub mod recent;
+pub mod theme;
 
 use anyhow::Result;
 use std::path::PathBuf;
@@ -8,6 +10,8 @@ const APP_ID: &str = "bvr";
 #[allow(dead_code)]
 const CONFIG_FILE: &str = "config.toml";
 const FILTER_FILE: &str = "filters.json";
+const RECENT_FILE: &str = "recent.json";
+const THEME_FILE: &str = "theme.json";
 
 fn storage_dir(app_id: &str) -> Option<PathBuf> {
     directories_next::ProjectDirs::from("", "", app_id)
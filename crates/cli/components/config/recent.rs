@@ -0,0 +1,63 @@
+use super::{storage_dir_create, APP_ID, RECENT_FILE};
+use crate::components::mux::find_matches;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Most recently opened files, newest first, kept across sessions.
+pub struct RecentFilesApp {
+    path: Option<PathBuf>,
+    files: Vec<PathBuf>,
+}
+
+impl RecentFilesApp {
+    const MAX_ENTRIES: usize = 20;
+
+    pub fn new() -> Self {
+        let path = storage_dir_create(APP_ID)
+            .map(|path| path.join(RECENT_FILE))
+            .ok();
+        let files = path
+            .as_ref()
+            .and_then(|path| std::fs::File::open(path).ok())
+            .map(std::io::BufReader::new)
+            .and_then(|reader| serde_json::from_reader(reader).ok())
+            .unwrap_or_default();
+        Self { path, files }
+    }
+
+    /// The recent files that still exist, newest first.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .map(PathBuf::as_path)
+            .filter(|path| path.exists())
+    }
+
+    /// Moves `path` to the front of the list and saves it.
+    pub fn push(&mut self, path: &Path) -> Result<()> {
+        let path = path.canonicalize()?;
+        self.files.retain(|file| *file != path);
+        self.files.insert(0, path);
+        self.files.truncate(Self::MAX_ENTRIES);
+
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)?;
+        serde_json::to_writer(std::io::BufWriter::new(file), &self.files)?;
+        Ok(())
+    }
+
+    /// Returns the positions in [Self::files] of the recent files whose
+    /// paths best match the query, see [find_matches].
+    pub fn find(&self, query: &str) -> Vec<usize> {
+        find_matches(
+            query,
+            self.files().map(|path| path.to_str().unwrap_or_default()),
+        )
+    }
+}
@@ -0,0 +1,73 @@
+use super::{storage_dir, APP_ID, THEME_FILE};
+use crate::colors;
+use ratatui::style::Style;
+use serde::Deserialize;
+
+/// Styles read from the theme file in the data directory, each falling back
+/// to its default if it is left out, or if the file cannot be read.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Applied to the matched lines of the focused pane when several are shown.
+    pub match_focused: Style,
+    /// Applied to the matched lines of the other panes.
+    pub match_unfocused: Style,
+    /// The `<` and `>` marking lines cut off at the edges of the view.
+    pub truncation_marker: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            match_focused: colors::MATCH_FOCUSED,
+            match_unfocused: colors::MATCH_UNFOCUSED,
+            truncation_marker: Style::new().fg(colors::TRUNCATION_MARKER),
+        }
+    }
+}
+
+impl Theme {
+    pub fn load() -> Self {
+        storage_dir(APP_ID)
+            .and_then(|path| std::fs::File::open(path.join(THEME_FILE)).ok())
+            .map(std::io::BufReader::new)
+            .and_then(|reader| serde_json::from_reader(reader).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratatui::style::{Color, Modifier};
+
+    #[test]
+    fn theme_defaults_what_is_left_out() {
+        let theme = serde_json::from_str::<Theme>(r#"{}"#).unwrap();
+        assert_eq!(theme, Theme::default());
+
+        let theme = serde_json::from_str::<Theme>(
+            r#"{
+                "match_unfocused": {
+                    "fg": "DarkGray",
+                    "add_modifier": "ITALIC",
+                    "sub_modifier": ""
+                },
+                "truncation_marker": {
+                    "fg": "Blue",
+                    "add_modifier": "",
+                    "sub_modifier": ""
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(theme.match_focused, colors::MATCH_FOCUSED);
+        assert_eq!(
+            theme.match_unfocused,
+            Style::new()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC)
+        );
+        assert_eq!(theme.truncation_marker, Style::new().fg(Color::Blue));
+    }
+}
@@ -52,6 +52,11 @@ impl CursorState {
         self.state = Cursor::Singleton(i);
     }
 
+    /// Selects the range between the anchor and the head, inclusive.
+    pub fn select(&mut self, anchor: usize, head: usize) {
+        self.state = Cursor::new_range(anchor, head, SelectionOrigin::Right);
+    }
+
     pub fn clamp(&mut self, bound: usize) {
         self.state = match self.state {
             Cursor::Singleton(i) => Cursor::Singleton(i.min(bound)),
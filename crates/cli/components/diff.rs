@@ -0,0 +1,130 @@
+use super::instance::Instance;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Only this many leading lines of each file are compared.
+pub const MAX_LINES: usize = 100_000;
+/// Files that differ by more lines than this are not diffed, as the edit
+/// script takes memory quadratic in the number of edits.
+pub const MAX_EDITS: usize = 2_000;
+
+/// Hashes of the leading lines of the file of `instance`, up to [MAX_LINES].
+pub fn line_hashes(instance: &Instance) -> Vec<u64> {
+    let file = instance.file();
+    (0..file.line_count().min(MAX_LINES))
+        .map(|line_number| {
+            let mut hasher = DefaultHasher::new();
+            if let Some(data) = file.get_bytes(line_number) {
+                data.as_bytes().hash(&mut hasher);
+            }
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// The lines removed from `old` and the lines added to `new` in a shortest
+/// edit script between them, in ascending order, or `None` if it takes more
+/// than `max_edits` edits. Changed lines are both removed and added.
+pub fn diff(old: &[u64], new: &[u64], max_edits: usize) -> Option<(Vec<usize>, Vec<usize>)> {
+    // Files often share most of their start and end, which need no search
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let trace = myers_trace(a, b, max_edits)?;
+
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let (mut x, mut y) = (a.len() as isize, b.len() as isize);
+    for (d, v) in trace.iter().enumerate().skip(1).rev() {
+        let d = d as isize;
+        let at = |k: isize| v[(k + d) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        // Walk back along the diagonal of equal lines
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+        }
+        if x == prev_x {
+            added.push(prefix + prev_y as usize);
+        } else {
+            removed.push(prefix + prev_x as usize);
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+
+    removed.reverse();
+    added.reverse();
+    Some((removed, added))
+}
+
+/// Runs the greedy forward search of Myers' diff algorithm, returning the
+/// furthest reaching x of every diagonal `k` in `-d..=d` at the start of each
+/// round `d`, which is enough to recover the edit script.
+fn myers_trace(a: &[u64], b: &[u64], max_edits: usize) -> Option<Vec<Vec<isize>>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (a.len() + b.len()).min(max_edits);
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let idx = |k: isize| (k + offset) as usize;
+
+    let mut trace = Vec::new();
+    for d in 0..=max as isize {
+        trace.push(v[idx(-d)..=idx(d)].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[idx(k - 1)] < v[idx(k + 1)]) {
+                v[idx(k + 1)]
+            } else {
+                v[idx(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx(k)] = x;
+            if x >= n && y >= m {
+                return Some(trace);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::diff;
+
+    #[test]
+    fn diff_marks_changed_lines() {
+        let old = [1, 2, 3, 4, 5, 6];
+        let new = [1, 3, 4, 9, 5, 6, 7];
+        let (removed, added) = diff(&old, &new, usize::MAX).unwrap();
+        assert_eq!(removed, vec![1]);
+        assert_eq!(added, vec![3, 6]);
+
+        let (removed, added) = diff(&old, &old, 0).unwrap();
+        assert!(removed.is_empty() && added.is_empty());
+
+        let (removed, added) = diff(&[], &[1, 2], usize::MAX).unwrap();
+        assert!(removed.is_empty());
+        assert_eq!(added, vec![0, 1]);
+    }
+
+    #[test]
+    fn diff_gives_up_past_max_edits() {
+        assert!(diff(&[1, 2, 3], &[4, 5, 6], 5).is_none());
+        assert!(diff(&[1, 2, 3], &[4, 5, 6], 6).is_some());
+    }
+}
@@ -1,7 +1,13 @@
-use std::str::FromStr;
+use std::{
+    collections::VecDeque,
+    hash::{DefaultHasher, Hash, Hasher},
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use super::{
     cursor::{Cursor, CursorState, SelectionOrigin},
+    query::Query,
     viewport::Viewport,
 };
 use crate::{
@@ -26,30 +32,108 @@ pub enum Mask {
     All,
     Bookmarks,
     Regex(Regex),
+    /// Lines that the regex does not match, named `NOT <regex>`.
+    NotRegex {
+        regex: Regex,
+        name: String,
+    },
+    /// Lines that differ from another view, named after it.
+    Diff(String),
 }
 
 impl Mask {
     pub fn build(pattern: &str, literal: bool) -> Result<(Self, Regex), regex::Error> {
         let regex = if literal {
-            regex_compile(&regex::escape(pattern))
+            regex_compile(&Self::literal_pattern(pattern)?)
         } else {
             regex_compile(pattern)
         }?;
         Ok((Self::Regex(regex.clone()), regex))
     }
 
+    /// Escapes a literal pattern, except that a leading `^` anchors the
+    /// literal to the start of the line and a trailing `$` anchors it to the
+    /// end of the line. Doubling the character (`^^`, `$$`) matches it literally.
+    ///
+    /// A lone anchor would match every line, so it is rejected; `^$` is kept
+    /// to match the empty lines.
+    fn literal_pattern(pattern: &str) -> Result<String, regex::Error> {
+        let (start, rest) = match pattern.strip_prefix('^') {
+            Some(rest) if rest.starts_with('^') => (false, rest),
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let (end, rest) = match rest.strip_suffix('$') {
+            Some(rest) if rest.ends_with('$') => (false, rest),
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        if !start && !end {
+            return Ok(regex::escape(rest));
+        }
+        if rest.is_empty() && start != end {
+            return Err(regex::Error::Syntax(String::from(
+                "an anchor alone matches every line",
+            )));
+        }
+
+        // Searches run over whole segments, so the anchors must be line-based.
+        let mut out = String::from("(?mR)");
+        if start {
+            out.push('^');
+        }
+        out.push_str(&regex::escape(rest));
+        if end {
+            out.push('$');
+        }
+        Ok(out)
+    }
+
+    /// A regex matching lines that are exactly `line`, without the anchor
+    /// sugar of [Self::literal_pattern], since `line` is taken as it is.
+    pub fn line_pattern(line: &str) -> String {
+        format!("(?mR)^{}$", regex::escape(line))
+    }
+
+    fn not_regex(regex: Regex) -> Self {
+        Self::NotRegex {
+            name: format!("NOT {regex}"),
+            regex,
+        }
+    }
+
+    /// Only the regex of a pattern filter, since an inverted filter does not
+    /// highlight anything.
     pub fn regex(&self) -> Option<Regex> {
         match self {
-            Self::All | Self::Bookmarks => None,
+            Self::All | Self::Bookmarks | Self::NotRegex { .. } | Self::Diff(_) => None,
             Self::Regex(regex) => Some(regex.clone()),
         }
     }
 
+    fn search(&self, file: &SegBuffer, max_matches: Option<usize>) -> Option<LineSet> {
+        match self {
+            Self::All | Self::Bookmarks | Self::Diff(_) => None,
+            Self::Regex(regex) => Some(LineSet::search_with_limit(
+                file.segment_iter().unwrap(),
+                regex.clone(),
+                max_matches,
+            )),
+            Self::NotRegex { regex, .. } => Some(LineSet::search_inverted(
+                file.segment_iter().unwrap(),
+                regex.clone(),
+                max_matches,
+            )),
+        }
+    }
+
     pub fn name(&self) -> &str {
         match self {
             Mask::All => "All Lines",
             Mask::Bookmarks => "Bookmarks",
             Mask::Regex(regex) => regex.as_str(),
+            Mask::NotRegex { name, .. } => name,
+            Mask::Diff(name) => name,
         }
     }
 }
@@ -79,6 +163,10 @@ pub enum MaskExport {
     Regex {
         regex: String,
     },
+    #[serde(rename = "not_regex")]
+    NotRegex {
+        regex: String,
+    },
 }
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
@@ -92,11 +180,12 @@ impl FilterExport {
         ratatui::style::Color::from_str(&self.color).unwrap_or_default()
     }
 
-    pub fn name(&self) -> &str {
+    pub fn name(&self) -> String {
         match &self.mask {
-            MaskExport::All => "All",
-            MaskExport::Bookmarks => "Bookmarks",
-            MaskExport::Regex { regex } => regex.as_str(),
+            MaskExport::All => String::from("All"),
+            MaskExport::Bookmarks => String::from("Bookmarks"),
+            MaskExport::Regex { regex } => regex.clone(),
+            MaskExport::NotRegex { regex } => format!("NOT {regex}"),
         }
     }
 
@@ -111,6 +200,11 @@ pub struct Filter {
     enabled: bool,
     color: Color,
     data: FilterSet,
+    /// Identifies the filter for as long as it exists, whatever its data.
+    id: usize,
+    /// Changes whenever `data` is replaced, since searching the same pattern
+    /// again gives a different set of lines.
+    data_id: usize,
 }
 
 impl Filter {
@@ -120,6 +214,8 @@ impl Filter {
             data: FilterSet::All,
             enabled: true,
             color: Color::White,
+            id: Self::next_id(),
+            data_id: Self::next_id(),
         }
     }
 
@@ -129,6 +225,8 @@ impl Filter {
             enabled: true,
             color: colors::SELECT_ACCENT,
             data: FilterSet::Bookmarks(Bookmarks::new()),
+            id: Self::next_id(),
+            data_id: Self::next_id(),
         }
     }
 
@@ -138,41 +236,77 @@ impl Filter {
             enabled: true,
             color,
             data: repr,
+            id: Self::next_id(),
+            data_id: Self::next_id(),
+        }
+    }
+
+    fn next_id() -> usize {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn set_data(&mut self, data: FilterSet) {
+        self.data = data;
+        self.data_id = Self::next_id();
+    }
+
+    /// Hashes what the lines of this filter are made of, so that filters
+    /// with equal hashes have the same lines once their searches complete.
+    fn hash_data(&self, state: &mut impl Hasher) {
+        self.data_id.hash(state);
+        // Bookmarks are changed in place
+        if let FilterSet::Bookmarks(bookmarks) = &self.data {
+            bookmarks.lines.hash(state);
         }
     }
 
-    pub fn to_export(&self) -> FilterExport {
-        FilterExport {
+    /// Diffs are tied to the views they were computed from, so they are not
+    /// exported.
+    pub fn to_export(&self) -> Option<FilterExport> {
+        Some(FilterExport {
             mask: match &self.mask {
                 Mask::All => MaskExport::All,
                 Mask::Bookmarks => MaskExport::Bookmarks,
                 Mask::Regex(regex) => MaskExport::Regex {
                     regex: regex.to_string(),
                 },
+                Mask::NotRegex { regex, .. } => MaskExport::NotRegex {
+                    regex: regex.to_string(),
+                },
+                Mask::Diff(_) => return None,
             },
             enabled: self.enabled,
             color: self.color.to_string().to_ascii_lowercase(),
-        }
+        })
     }
 
-    pub fn from_export(file: &SegBuffer, export: &FilterExport) -> Self {
+    pub fn from_export(
+        file: &SegBuffer,
+        export: &FilterExport,
+        max_matches: Option<usize>,
+    ) -> Self {
         let mask = match export.mask {
             MaskExport::All | MaskExport::Bookmarks => {
                 unreachable!("should have been processed before")
             }
             MaskExport::Regex { ref regex } => Mask::Regex(regex_compile(regex).unwrap()),
+            MaskExport::NotRegex { ref regex } => Mask::not_regex(regex_compile(regex).unwrap()),
         };
         Self {
-            data: FilterSet::Search(LineSet::search(
-                file.segment_iter().unwrap(),
-                mask.regex().unwrap(),
-            )),
+            data: FilterSet::Search(mask.search(file, max_matches).unwrap()),
+            id: Self::next_id(),
+            data_id: Self::next_id(),
             mask,
             enabled: export.enabled,
             color: ratatui::style::Color::from_str(&export.color).unwrap_or_default(),
         }
     }
 
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
     pub fn color(&self) -> Color {
         self.color
     }
@@ -185,6 +319,10 @@ impl Filter {
         self.enabled = !self.enabled;
     }
 
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
     pub fn mask(&self) -> &Mask {
         &self.mask
     }
@@ -213,6 +351,22 @@ impl Filter {
         }
     }
 
+    pub fn first(&self) -> Option<usize> {
+        match &self.data {
+            FilterSet::All => None,
+            FilterSet::Bookmarks(mask) => mask.lines.first().copied(),
+            FilterSet::Search(mask) => mask.get(0),
+        }
+    }
+
+    pub fn last(&self) -> Option<usize> {
+        match &self.data {
+            FilterSet::All => None,
+            FilterSet::Bookmarks(mask) => mask.lines.last().copied(),
+            FilterSet::Search(mask) => mask.len().checked_sub(1).and_then(|i| mask.get(i)),
+        }
+    }
+
     pub fn nearest_forward(&self, line_number: usize) -> Option<usize> {
         match &self.data {
             FilterSet::All => None,
@@ -229,6 +383,14 @@ impl Filter {
         }
     }
 
+    /// Whether the search stopped at the match limit before finding every match.
+    pub fn is_capped(&self) -> bool {
+        match &self.data {
+            FilterSet::Search(lines) => lines.is_capped(),
+            _ => false,
+        }
+    }
+
     pub fn is_complete(&self) -> bool {
         match &self.data {
             FilterSet::All => true,
@@ -243,6 +405,23 @@ pub struct Bookmarks {
     lines: Vec<usize>,
 }
 
+/// Bookmarks as saved by `:marks save`.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct BookmarksExport {
+    /// Bumped whenever the format changes in a way older versions cannot read.
+    version: u32,
+    /// One-based line numbers, in ascending order.
+    lines: Vec<usize>,
+}
+
+impl BookmarksExport {
+    pub const VERSION: u32 = 1;
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
 impl Bookmarks {
     fn new() -> Bookmarks {
         Bookmarks { lines: Vec::new() }
@@ -271,6 +450,22 @@ impl Bookmarks {
         };
     }
 
+    /// Toggles every line of `line_numbers`, which must be ascending.
+    pub fn toggle_all(&mut self, line_numbers: impl Iterator<Item = usize>) {
+        let mut lines = Vec::with_capacity(self.lines.len());
+        let mut existing = self.lines.iter().copied().peekable();
+        for line_number in line_numbers {
+            while let Some(bookmark) = existing.next_if(|&bookmark| bookmark < line_number) {
+                lines.push(bookmark);
+            }
+            if existing.next_if_eq(&line_number).is_none() {
+                lines.push(line_number);
+            }
+        }
+        lines.extend(existing);
+        self.lines = lines;
+    }
+
     pub fn has_line(&self, line_number: usize) -> bool {
         let slice = self.lines.as_slice();
         match *slice {
@@ -282,10 +477,19 @@ impl Bookmarks {
         }
     }
 
-    fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.lines.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Position of the bookmarked line among all bookmarks.
+    pub fn position(&self, line_number: usize) -> Option<usize> {
+        self.lines.binary_search(&line_number).ok()
+    }
+
     pub fn nearest_forward(&self, line_number: usize) -> Option<usize> {
         let slice = self.lines.as_slice();
         match *slice {
@@ -317,6 +521,33 @@ impl Bookmarks {
     fn clear(&mut self) {
         self.lines.clear()
     }
+
+    pub fn export(&self) -> BookmarksExport {
+        BookmarksExport {
+            version: BookmarksExport::VERSION,
+            lines: self
+                .lines
+                .iter()
+                .map(|line_number| line_number + 1)
+                .collect(),
+        }
+    }
+
+    /// Adds the saved bookmarks that are within the first `line_count` lines.
+    /// Returns how many were added and how many are past those lines.
+    pub fn import(&mut self, export: &BookmarksExport, line_count: usize) -> (usize, usize) {
+        let mut skipped = 0;
+        for &line in &export.lines {
+            match line
+                .checked_sub(1)
+                .filter(|&line_number| line_number < line_count)
+            {
+                Some(line_number) => self.add(line_number),
+                None => skipped += 1,
+            }
+        }
+        (export.lines.len() - skipped, skipped)
+    }
 }
 
 #[derive(Clone)]
@@ -345,6 +576,12 @@ impl Filters {
             .chain(self.user_filters.iter())
     }
 
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Filter> {
+        std::iter::once(&mut self.all)
+            .chain(std::iter::once(&mut self.bookmarks))
+            .chain(self.user_filters.iter_mut())
+    }
+
     pub fn iter_active(&self) -> impl Iterator<Item = &Filter> {
         self.iter().filter(|filter| filter.is_enabled())
     }
@@ -365,6 +602,10 @@ impl Filters {
         }
     }
 
+    pub fn all_mut(&mut self) -> &mut Filter {
+        &mut self.all
+    }
+
     pub fn bookmarks(&self) -> &Bookmarks {
         // Safety: by construction
         match &self.bookmarks.data {
@@ -384,11 +625,16 @@ impl Filters {
     pub fn export(&self, name: Option<String>) -> FilterExportSet {
         FilterExportSet {
             name,
-            filters: self.iter().map(Filter::to_export).collect(),
+            filters: self.iter().filter_map(Filter::to_export).collect(),
         }
     }
 
-    pub fn import_user_filters(&mut self, file: &SegBuffer, import: &FilterExportSet) {
+    pub fn import_user_filters(
+        &mut self,
+        file: &SegBuffer,
+        import: &FilterExportSet,
+        max_matches: Option<usize>,
+    ) {
         self.user_filters.clear();
 
         for filter in import.filters.iter() {
@@ -396,9 +642,9 @@ impl Filters {
             match filter.mask {
                 MaskExport::All => self.all.enabled = filter.enabled,
                 MaskExport::Bookmarks => self.bookmarks.enabled = filter.enabled,
-                MaskExport::Regex { .. } => {
-                    self.user_filters.push(Filter::from_export(file, filter))
-                }
+                MaskExport::Regex { .. } | MaskExport::NotRegex { .. } => self
+                    .user_filters
+                    .push(Filter::from_export(file, filter, max_matches)),
             }
         }
     }
@@ -409,27 +655,74 @@ impl Filters {
     }
 }
 
+/// Composites of the most recently active sets of filters of one view, so
+/// that turning its filters off and on again reuses the composite instead of
+/// composing it again. The cache is not shared between views: each searches
+/// its own buffer, so even linked filters never have the same lines.
+#[derive(Default)]
+struct CompositeCache {
+    entries: VecDeque<(u64, LineSet)>,
+}
+
+impl CompositeCache {
+    const CAPACITY: usize = 8;
+
+    fn get(&mut self, key: u64) -> Option<LineSet> {
+        let i = self.entries.iter().position(|(k, _)| *k == key)?;
+        let entry = self.entries.remove(i)?;
+        let lines = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(lines)
+    }
+
+    fn insert(&mut self, key: u64, lines: LineSet) {
+        if self.entries.len() >= Self::CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, lines));
+    }
+}
+
 pub struct Compositor {
     all_composite: LineSet,
+    composite_cache: CompositeCache,
     strategy: CompositeStrategy,
     viewport: Viewport,
     cursor: CursorState,
     filters: Filters,
+    max_matches: Option<usize>,
     pub(super) color_selector: ColorSelector,
 }
 
 impl Compositor {
-    pub fn new(buf: &SegBuffer) -> Self {
+    /// Picks the colors of new filters with `color_selector`, see
+    /// [ColorSelector::new] for one suited to the terminal.
+    pub(super) fn with_color_selector(buf: &SegBuffer, color_selector: ColorSelector) -> Self {
         Self {
             all_composite: buf.all_line_matches(),
+            composite_cache: CompositeCache::default(),
             viewport: Viewport::new(),
             cursor: CursorState::new(),
             filters: Filters::new(),
             strategy: CompositeStrategy::Union,
-            color_selector: ColorSelector::new(),
+            max_matches: None,
+            color_selector,
         }
     }
 
+    pub fn max_matches(&self) -> Option<usize> {
+        self.max_matches
+    }
+
+    /// Limits the number of matches of searches started after this is set.
+    pub fn set_max_matches(&mut self, max_matches: Option<usize>) {
+        self.max_matches = max_matches;
+    }
+
+    pub fn strategy(&self) -> CompositeStrategy {
+        self.strategy
+    }
+
     pub fn set_strategy(&mut self, strategy: CompositeStrategy) {
         self.strategy = strategy;
     }
@@ -462,15 +755,36 @@ impl Compositor {
 
     pub fn create_composite(&mut self) -> LineSet {
         if self.filters.all.is_enabled() {
-            self.all_composite.clone()
-        } else {
-            let filters = self
-                .filters
-                .iter_active()
-                .map(|filter| filter.as_line_matches())
-                .collect();
-            LineSet::compose(filters, false, self.strategy).unwrap()
+            return self.all_composite.clone();
         }
+
+        let key = self.composite_key();
+        if let Some(composite) = self.composite_cache.get(key) {
+            return composite;
+        }
+
+        let filters = self
+            .filters
+            .iter_active()
+            .map(|filter| filter.as_line_matches())
+            .collect::<Vec<_>>();
+        // Lines still being searched would be missing from a cached composite
+        let cacheable = filters.iter().all(LineSet::is_complete);
+        let composite = LineSet::compose(filters, false, self.strategy).unwrap();
+        if cacheable {
+            self.composite_cache.insert(key, composite.clone());
+        }
+        composite
+    }
+
+    /// Hashes everything a composite is made of, see [Filter::hash_data].
+    fn composite_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.strategy.hash(&mut hasher);
+        for filter in self.filters.iter_active() {
+            filter.hash_data(&mut hasher);
+        }
+        hasher.finish()
     }
 
     pub fn move_select(&mut self, dir: Direction, select: bool, delta: ViewDelta) {
@@ -552,11 +866,84 @@ impl Compositor {
         self.filters.user_filters.push(Filter::new(
             mask,
             self.color_selector.next_color(),
-            FilterSet::Search(LineSet::search(file.segment_iter().unwrap(), regex)),
+            FilterSet::Search(LineSet::search_with_limit(
+                file.segment_iter().unwrap(),
+                regex,
+                self.max_matches,
+            )),
         ));
         Ok(())
     }
 
+    /// Searches the data again for every pattern filter, so that lines read
+    /// since they were added are covered, keeping their colors, states and
+    /// order. Returns how many filters are being searched again.
+    pub fn refresh_filters(&mut self, file: &SegBuffer) -> usize {
+        let max_matches = self.max_matches;
+        let mut refreshed = 0;
+        for filter in &mut self.filters.user_filters {
+            if let Some(lines) = filter.mask.search(file, max_matches) {
+                filter.set_data(FilterSet::Search(lines));
+                refreshed += 1;
+            }
+        }
+        refreshed
+    }
+
+    /// Replaces the filters with one for every term of the query, combined
+    /// with the query's strategy. Nothing changes if a regex is invalid.
+    pub fn apply_query(&mut self, file: &SegBuffer, query: &Query) -> Result<(), regex::Error> {
+        let masks = query
+            .terms
+            .iter()
+            .map(|term| {
+                let (mask, regex) = Mask::build(&term.pattern, term.literal)?;
+                Ok(if term.negated {
+                    Mask::not_regex(regex)
+                } else {
+                    mask
+                })
+            })
+            .collect::<Result<Vec<_>, regex::Error>>()?;
+
+        self.filters.all.set_enabled(false);
+        self.filters.bookmarks.set_enabled(false);
+        for filter in &mut self.filters.user_filters {
+            filter.set_enabled(false);
+        }
+        for mask in masks {
+            let lines = mask.search(file, self.max_matches).unwrap();
+            self.filters.user_filters.push(Filter::new(
+                mask,
+                self.color_selector.next_color(),
+                FilterSet::Search(lines),
+            ));
+        }
+        self.strategy = query.strategy;
+        Ok(())
+    }
+
+    /// Shows the lines that differ from another view as a filter, replacing
+    /// the previous diff if there is one.
+    pub fn set_diff_filter(&mut self, name: String, color: Color, lines: Vec<usize>) {
+        self.clear_diff_filter();
+        self.filters.user_filters.push(Filter::new(
+            Mask::Diff(name),
+            color,
+            FilterSet::Search(LineSet::from(lines)),
+        ));
+    }
+
+    /// Returns whether there was a diff to remove.
+    pub fn clear_diff_filter(&mut self) -> bool {
+        let len = self.filters.user_filters.len();
+        self.filters
+            .user_filters
+            .retain(|filter| !matches!(filter.mask, Mask::Diff(_)));
+        self.cursor.clamp(self.filters.len().saturating_sub(1));
+        len != self.filters.user_filters.len()
+    }
+
     pub fn edit_selected_filter(
         &mut self,
         file: &SegBuffer,
@@ -564,12 +951,17 @@ impl Compositor {
         literal: bool,
     ) -> Result<(), regex::Error> {
         let (mask, regex) = Mask::build(pattern, literal)?;
+        let max_matches = self.max_matches;
 
         if let Some(filter) = self.selected_filter_mut() {
             *filter = Filter::new(
                 mask,
                 filter.color,
-                FilterSet::Search(LineSet::search(file.segment_iter().unwrap(), regex)),
+                FilterSet::Search(LineSet::search_with_limit(
+                    file.segment_iter().unwrap(),
+                    regex,
+                    max_matches,
+                )),
             )
         }
         Ok(())
@@ -602,6 +994,34 @@ impl Compositor {
         }
     }
 
+    /// Like [Self::compute_jump], but wraps around to the first or last match
+    /// when there are no more matches in the given direction. The flag is set
+    /// if the jump wrapped around.
+    pub fn compute_wrapping_jump(
+        &self,
+        i: usize,
+        direction: Direction,
+        composite: &LineSet,
+    ) -> Option<(usize, bool)> {
+        if let Some(next) = self.compute_jump(i, direction, composite) {
+            return Some((next, false));
+        }
+        let wrap = |line: Option<usize>| {
+            let active_filters = self.filters.iter_active();
+            let iter = active_filters.filter(|filter| line.is_none_or(|ln| filter.has_line(ln)));
+            match direction {
+                Direction::Back => iter.filter_map(Filter::last).max(),
+                Direction::Next => iter.filter_map(Filter::first).min(),
+            }
+        };
+        let target = if !self.filters.all.is_enabled() {
+            composite.find(wrap(Some(composite.get(i)?))?)?
+        } else {
+            wrap(None)?
+        };
+        (target != i).then_some((target, true))
+    }
+
     pub fn cursor(&self) -> &CursorState {
         &self.cursor
     }
@@ -610,3 +1030,183 @@ impl Compositor {
         self.cursor = cursor
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Bookmarks, BookmarksExport, Compositor, Mask};
+    use bvr_core::{LineSet, SegBuffer};
+    use std::{
+        io::{Cursor, Read},
+        sync::mpsc::{self, Receiver},
+    };
+
+    /// A stream that is only read once its sender hangs up.
+    struct PendingStream(Receiver<()>);
+
+    impl Read for PendingStream {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            let _ = self.0.recv();
+            Ok(0)
+        }
+    }
+
+    fn wait_for_searches(compositor: &Compositor) {
+        while !compositor
+            .filters
+            .user_filters
+            .iter()
+            .all(|filter| filter.as_line_matches().is_complete())
+        {
+            std::thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn bookmarks_import_skips_lines_past_the_end() {
+        let export: BookmarksExport =
+            serde_json::from_str(r#"{"version":1,"lines":[1,5,12]}"#).unwrap();
+        let mut bookmarks = Bookmarks::new();
+        bookmarks.add(2);
+
+        assert_eq!(bookmarks.import(&export, 10), (2, 1));
+        assert_eq!(bookmarks.lines, [0, 2, 4]);
+        assert_eq!(
+            serde_json::to_string(&bookmarks.export()).unwrap(),
+            r#"{"version":1,"lines":[1,3,5]}"#
+        );
+    }
+
+    #[test]
+    fn line_pattern_matches_the_line_as_is() {
+        let (_, regex) = Mask::build(&Mask::line_pattern("^ab$"), false).unwrap();
+        assert!(regex.is_match(b"x\n^ab$\ny"));
+        assert!(regex.is_match(b"^ab$\r\n"));
+        assert!(!regex.is_match(b"ab\n"));
+        assert!(!regex.is_match(b"^ab$c\n"));
+        assert!(!regex.is_match(b"c^ab$\n"));
+    }
+
+    #[test]
+    fn literal_anchors_alone_are_rejected() {
+        assert!(Mask::build("^", true).is_err());
+        assert!(Mask::build("$", true).is_err());
+
+        let (_, empty) = Mask::build("^$", true).unwrap();
+        assert!(empty.is_match(b"a\n\nb"));
+        assert!(!empty.is_match(b"a\nb"));
+        let (_, caret) = Mask::build("^^", true).unwrap();
+        assert!(caret.is_match(b"a^b"));
+        assert!(!caret.is_match(b"ab"));
+    }
+
+    /// Does not depend on the colors of the terminal running the tests.
+    fn test_compositor(buf: &SegBuffer) -> Compositor {
+        Compositor::with_color_selector(buf, crate::colors::ColorSelector::Color256 { index: 0 })
+    }
+
+    #[test]
+    fn composite_is_reused() {
+        let data: &[u8] = b"error a\nwarn b\nerror c\n";
+        let buf = SegBuffer::read_stream(Box::new(Cursor::new(data)), true).unwrap();
+        let mut compositor = test_compositor(&buf);
+        compositor.add_search_filter(&buf, "error", true).unwrap();
+        compositor.add_search_filter(&buf, "warn", true).unwrap();
+        compositor.filters.all_mut().set_enabled(false);
+        wait_for_searches(&compositor);
+
+        compositor.create_composite();
+        compositor.create_composite();
+        assert_eq!(compositor.composite_cache.entries.len(), 1);
+
+        // A different set of filters is composed, and the first is reused
+        // once it is active again
+        compositor.filters.get_mut(3).unwrap().set_enabled(false);
+        compositor.create_composite();
+        assert_eq!(compositor.composite_cache.entries.len(), 2);
+        compositor.filters.get_mut(3).unwrap().set_enabled(true);
+        compositor.create_composite();
+        assert_eq!(compositor.composite_cache.entries.len(), 2);
+
+        // Searching again gives new lines, which are composed again
+        compositor.refresh_filters(&buf);
+        wait_for_searches(&compositor);
+        compositor.create_composite();
+        assert_eq!(compositor.composite_cache.entries.len(), 3);
+    }
+
+    #[test]
+    fn composite_of_incomplete_filters_is_recomputed() {
+        let (sender, receiver) = mpsc::channel();
+        let buf = SegBuffer::read_stream(Box::new(PendingStream(receiver)), false).unwrap();
+        let mut compositor = test_compositor(&buf);
+        compositor.add_search_filter(&buf, "error", true).unwrap();
+        compositor.add_search_filter(&buf, "warn", true).unwrap();
+        compositor.filters.all_mut().set_enabled(false);
+
+        compositor.create_composite();
+        assert!(compositor.composite_cache.entries.is_empty());
+
+        drop(sender);
+        wait_for_searches(&compositor);
+        compositor.create_composite();
+        assert_eq!(compositor.composite_cache.entries.len(), 1);
+    }
+
+    /// Plants a composite that composing the filters could never give, so
+    /// that getting it back means the cache was hit.
+    fn plant_composite(compositor: &mut Compositor) {
+        let key = compositor.composite_key();
+        compositor
+            .composite_cache
+            .insert(key, LineSet::from(vec![42]));
+    }
+
+    fn is_planted(composite: &LineSet) -> bool {
+        composite.len() == 1 && composite.get(0) == Some(42)
+    }
+
+    #[test]
+    fn composite_cache_hit_returns_the_cached_composite() {
+        let data: &[u8] = b"error a\nwarn b\nerror c\n";
+        let buf = SegBuffer::read_stream(Box::new(Cursor::new(data)), true).unwrap();
+        let mut compositor = test_compositor(&buf);
+        compositor.add_search_filter(&buf, "error", true).unwrap();
+        compositor.filters.all_mut().set_enabled(false);
+        wait_for_searches(&compositor);
+
+        plant_composite(&mut compositor);
+        assert!(is_planted(&compositor.create_composite()));
+    }
+
+    #[test]
+    fn composite_cache_misses_once_a_search_is_replaced() {
+        let data: &[u8] = b"error a\nwarn b\nerror c\n";
+        let buf = SegBuffer::read_stream(Box::new(Cursor::new(data)), true).unwrap();
+        let mut compositor = test_compositor(&buf);
+        compositor.add_search_filter(&buf, "error", true).unwrap();
+        compositor.filters.all_mut().set_enabled(false);
+        wait_for_searches(&compositor);
+
+        plant_composite(&mut compositor);
+        compositor.refresh_filters(&buf);
+        wait_for_searches(&compositor);
+        let composite = compositor.create_composite();
+        assert!(!is_planted(&composite));
+        assert_eq!(composite.len(), 2);
+    }
+
+    #[test]
+    fn composite_cache_misses_once_bookmarks_change() {
+        let data: &[u8] = b"error a\nwarn b\nerror c\n";
+        let buf = SegBuffer::read_stream(Box::new(Cursor::new(data)), true).unwrap();
+        let mut compositor = test_compositor(&buf);
+        compositor.filters.all_mut().set_enabled(false);
+        compositor.filters_mut().bookmarks_mut().add(1);
+
+        plant_composite(&mut compositor);
+        compositor.filters_mut().bookmarks_mut().add(2);
+        let composite = compositor.create_composite();
+        assert!(!is_planted(&composite));
+        assert_eq!(composite.len(), 2);
+    }
+}
@@ -0,0 +1,347 @@
+use super::viewport::Viewport;
+use crate::{
+    app::keybinding::{KeyBind, DEFAULT_BINDINGS},
+    direction::Direction,
+};
+
+pub struct CommandHelp {
+    pub command: &'static str,
+    pub args: &'static str,
+    pub description: &'static str,
+}
+
+impl CommandHelp {
+    const fn new(command: &'static str, args: &'static str, description: &'static str) -> Self {
+        Self {
+            command,
+            args,
+            description,
+        }
+    }
+
+    /// Every whitespace-separated term of the query must appear somewhere in
+    /// the command, its arguments, or its description.
+    fn matches(&self, query: &str) -> bool {
+        query.split_whitespace().all(|term| {
+            let term = term.to_lowercase();
+            [self.command, self.args, self.description]
+                .iter()
+                .any(|s| s.to_lowercase().contains(&term))
+        })
+    }
+}
+
+pub const COMMANDS: &[CommandHelp] = &[
+    CommandHelp::new("quit", "", "Quit."),
+    CommandHelp::new("open", "<file>", "Open a file in a new tab/view."),
+    CommandHelp::new(
+        "close",
+        "",
+        "Close the current tab/view. Opening the file again returns to where it was left, with its filters.",
+    ),
+    CommandHelp::new(
+        "close!",
+        "",
+        "Close the current tab/view, and open the file afresh next time.",
+    ),
+    CommandHelp::new(
+        "recent",
+        "[<number>|<path>]",
+        "List recently opened files, or reopen the one that best matches.",
+    ),
+    CommandHelp::new(
+        "buffer",
+        "<name>",
+        "Switch to the tab/view whose name best matches.",
+    ),
+    CommandHelp::new(
+        "branch",
+        "",
+        "Open the current file in a new tab/view with the same filters. A stream can be branched once it has loaded.",
+    ),
+    CommandHelp::new("help", "[query]", "Search the list of commands and keys."),
+    CommandHelp::new(
+        "messages",
+        "[clear]",
+        "Show the recent status messages, or forget them.",
+    ),
+    CommandHelp::new("refresh", "", "Redraw the whole screen."),
+    CommandHelp::new(
+        "mcap",
+        "",
+        "Toggle mouse capture (see --mouse-modes to capture only in some modes).",
+    ),
+    CommandHelp::new(
+        "gutter",
+        "",
+        "Toggle the line number gutter of the current view.",
+    ),
+    CommandHelp::new(
+        "gutter all",
+        "",
+        "Toggle the line number gutter of all views.",
+    ),
+    CommandHelp::new(
+        "gutter delta",
+        "",
+        "Toggle the time elapsed since the previous visible line.",
+    ),
+    CommandHelp::new(
+        "set",
+        "[option[=value]|all]",
+        "Show or change an option: `name`/`noname` turns it on/off, `name!` toggles it, `name?` shows it.",
+    ),
+    CommandHelp::new(
+        "selection",
+        "[bg|reverse]",
+        "Draw selected lines with a background or reversed.",
+    ),
+    CommandHelp::new(
+        "prompt backspace",
+        "<exit|stay>",
+        "Choose whether backspace at an empty prompt returns to normal mode.",
+    ),
+    CommandHelp::new(
+        "prompt esc",
+        "<discard|keep>",
+        "Choose whether escape from the filter prompt discards or adds the filter.",
+    ),
+    CommandHelp::new(
+        "status",
+        "[layout|reset]",
+        "Choose the status bar segments, e.g. `mode,name,info,filter|position,percent,clock`.",
+    ),
+    CommandHelp::new(
+        "linelen",
+        "",
+        "Toggle a column with the length in bytes of each line.",
+    ),
+    CommandHelp::new(
+        "raw",
+        "",
+        "Toggle drawing the lines of the view as they are, with no colors or transforms.",
+    ),
+    CommandHelp::new(
+        "ruler",
+        "[column,...]",
+        "Draw guides down the view at the given columns, or clear them.",
+    ),
+    CommandHelp::new(
+        "wrap",
+        "",
+        "Toggle wrapping long lines across rows, marking continued rows with ↪.",
+    ),
+    CommandHelp::new(
+        "dim",
+        "",
+        "Dim everything except the matches on filtered lines.",
+    ),
+    CommandHelp::new(
+        "header",
+        "[regex]",
+        "Pin the nearest line above the view matching the regex, or clear it.",
+    ),
+    CommandHelp::new(
+        "count",
+        "<regex>",
+        "Count the lines of the view matching the regex, without adding a filter.",
+    ),
+    CommandHelp::new(
+        "diff",
+        "[instance index|off]",
+        "Mark the lines removed from the active view and added to another as filters.",
+    ),
+    CommandHelp::new(
+        "hist",
+        "[regex]",
+        "Count the values of the first capture group over the visible lines, or close the chart.",
+    ),
+    CommandHelp::new(
+        "json",
+        "[fields|off]",
+        "Show the comma-separated fields of JSON lines as columns.",
+    ),
+    CommandHelp::new("mux", "[tabs|split]", "Set or toggle the multiplexer mode."),
+    CommandHelp::new(
+        "pbcopy",
+        "[--all] [--md[=<lang>]]",
+        "Copy the output of the active filters (or the whole file) to the clipboard.",
+    ),
+    CommandHelp::new(
+        "filter",
+        "<expr>",
+        "Replace the filters with the terms of an expression like 'ERROR' AND NOT /health/.",
+    ),
+    CommandHelp::new(
+        "filter refresh",
+        "",
+        "Search again with every filter to cover lines read since it was added.",
+    ),
+    CommandHelp::new(
+        "filter persist",
+        "",
+        "Persist the filters on shutdown and launch.",
+    ),
+    CommandHelp::new(
+        "filter link",
+        "",
+        "Synchronize filters across opened files.",
+    ),
+    CommandHelp::new(
+        "filter copy",
+        "<view>",
+        "Copy the filters to another opened file.",
+    ),
+    CommandHelp::new("filter save", "[name]", "Save the filters for later."),
+    CommandHelp::new("filter load", "", "Choose saved filters to load."),
+    CommandHelp::new("filter clear", "", "Clear all filters."),
+    CommandHelp::new(
+        "filters show",
+        "",
+        "Toggle a row under each view with its filters and their counts.",
+    ),
+    CommandHelp::new("filters hide", "", "Hide the row of filters under each view."),
+    CommandHelp::new(
+        "filter union",
+        "",
+        "Use union strategy for filter composites (default).",
+    ),
+    CommandHelp::new(
+        "filter intersect",
+        "",
+        "Use intersection strategy for filter composites.",
+    ),
+    CommandHelp::new("bookmarks only", "", "Show only the bookmarked lines."),
+    CommandHelp::new(
+        "bookmarks off",
+        "",
+        "Restore the filters from before `:bookmarks only`.",
+    ),
+    CommandHelp::new(
+        "marks save",
+        "<file>",
+        "Save the bookmarks of the active view to a file.",
+    ),
+    CommandHelp::new(
+        "marks load",
+        "<file>",
+        "Add the bookmarks saved in a file to the active view.",
+    ),
+    CommandHelp::new(
+        "export",
+        "[--trim] [--squeeze] [--all] [--md[=<lang>]] [--offsets] [--format <template>] <file>",
+        "Write the output of the active filters (or the whole file) to a file, optionally as a markdown code block.",
+    ),
+    CommandHelp::new(
+        "w",
+        "<file>",
+        "Write the whole file to a new file, ignoring the filters.",
+    ),
+    CommandHelp::new(
+        "byte",
+        "<offset>",
+        "Go to the line containing the byte offset, waiting for it to be indexed.",
+    ),
+    CommandHelp::new(
+        "col",
+        "<column>",
+        "Scroll the cursor line so that the display column is at the left edge.",
+    ),
+    CommandHelp::new(
+        "",
+        "<percent>%",
+        "Go to a percentage of the input, following it as the input grows.",
+    ),
+    CommandHelp::new(
+        "",
+        "<number>",
+        "Go to the specific line number, waiting for it to be indexed.",
+    ),
+];
+
+/// An entry of the help palette.
+#[derive(Clone, Copy)]
+pub enum HelpEntry {
+    Command(&'static CommandHelp),
+    Key(&'static KeyBind),
+}
+
+impl HelpEntry {
+    fn matches(self, query: &str) -> bool {
+        match self {
+            HelpEntry::Command(help) => help.matches(query),
+            HelpEntry::Key(bind) => query.split_whitespace().all(|term| {
+                let term = term.to_lowercase();
+                [bind.mode.name(), bind.label, bind.description]
+                    .iter()
+                    .any(|s| s.to_lowercase().contains(&term))
+            }),
+        }
+    }
+}
+
+pub struct HelpApp {
+    viewport: Viewport,
+    selected: usize,
+}
+
+impl HelpApp {
+    pub fn new() -> Self {
+        Self {
+            viewport: Viewport::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.viewport = Viewport::new();
+        self.selected = 0;
+    }
+
+    fn entries(query: &str) -> impl Iterator<Item = HelpEntry> + '_ {
+        let keys = DEFAULT_BINDINGS
+            .iter()
+            .filter(|bind| !bind.label.is_empty())
+            .map(HelpEntry::Key);
+        COMMANDS
+            .iter()
+            .map(HelpEntry::Command)
+            .chain(keys)
+            .filter(move |entry| entry.matches(query))
+    }
+
+    pub fn update_and_view<'a>(
+        &mut self,
+        viewport_height: usize,
+        query: &'a str,
+    ) -> impl Iterator<Item = (usize, HelpEntry)> + 'a {
+        let len = Self::entries(query).count();
+        self.selected = self.selected.min(len.saturating_sub(1));
+
+        self.viewport.fit_view(viewport_height, 0);
+        self.viewport.clamp(len);
+        self.viewport.jump_vertically_to(self.selected);
+
+        Self::entries(query)
+            .enumerate()
+            .skip(self.viewport.top())
+            .take(self.viewport.height())
+    }
+
+    pub fn move_select(&mut self, dir: Direction, query: &str) {
+        let len = Self::entries(query).count();
+        self.selected = match dir {
+            Direction::Back => self.selected.saturating_sub(1),
+            Direction::Next => self.selected.saturating_add(1),
+        }
+        .min(len.saturating_sub(1));
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected(&self, query: &str) -> Option<HelpEntry> {
+        Self::entries(query).nth(self.selected)
+    }
+}
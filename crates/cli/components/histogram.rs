@@ -0,0 +1,149 @@
+use super::instance::Instance;
+use regex::bytes::Regex;
+use std::collections::HashMap;
+
+/// Tally of the first capture group of a pattern over the visible lines of a
+/// view, scanned a batch at a time so that large inputs stay responsive.
+pub struct HistogramApp {
+    regex: Regex,
+    view_index: usize,
+    /// View index of the next line to scan.
+    next: usize,
+    counts: HashMap<Vec<u8>, usize>,
+    done: bool,
+    /// Composite of the view that the counts are of.
+    composite_id: Option<usize>,
+}
+
+impl HistogramApp {
+    const LINES_PER_STEP: usize = 200_000;
+
+    pub fn new(regex: Regex, view_index: usize) -> Self {
+        Self {
+            regex,
+            view_index,
+            next: 0,
+            counts: HashMap::new(),
+            done: false,
+            composite_id: None,
+        }
+    }
+
+    pub fn pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    /// Index of the view the histogram is computed over.
+    pub fn view_index(&self) -> usize {
+        self.view_index
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// How many of the visible lines have been scanned so far.
+    pub fn scanned(&self) -> usize {
+        self.next
+    }
+
+    /// Scans the next batch of visible lines of `instance`, starting over if
+    /// the filters changed which lines are visible.
+    pub fn step(&mut self, instance: &Instance) {
+        if self.composite_id != Some(instance.composite_id()) {
+            self.composite_id = Some(instance.composite_id());
+            self.counts.clear();
+            self.next = 0;
+            self.done = false;
+        }
+        if self.done {
+            return;
+        }
+
+        let end = instance
+            .visible_line_count()
+            .min(self.next + Self::LINES_PER_STEP);
+        for index in self.next..end {
+            let Some(line) = instance.line_at_view_index(index) else {
+                break;
+            };
+            let value = self
+                .regex
+                .captures(line.as_bytes())
+                .and_then(|captures| captures.get(1).or_else(|| captures.get(0)));
+            if let Some(value) = value {
+                *self.counts.entry(value.as_bytes().to_vec()).or_default() += 1;
+            }
+            self.next = index + 1;
+        }
+
+        // Lines may still be arriving while the view is being indexed or filtered
+        self.done = self.next >= instance.visible_line_count() && instance.is_view_complete();
+    }
+
+    /// The `n` most frequent values, most frequent first.
+    pub fn top(&self, n: usize) -> Vec<(String, usize)> {
+        let mut entries = self
+            .counts
+            .iter()
+            .map(|(value, &count)| (String::from_utf8_lossy(value).into_owned(), count))
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by(|(a, a_count), (b, b_count)| {
+            b_count.cmp(a_count).then_with(|| a.cmp(b))
+        });
+        entries.truncate(n);
+        entries
+    }
+
+    /// How many distinct values were seen.
+    pub fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::colors::ColorSelector;
+    use bvr_core::SegBuffer;
+    use std::io::Cursor;
+
+    fn run(histogram: &mut HistogramApp, instance: &mut Instance) {
+        loop {
+            instance.update_view(10, 80);
+            histogram.step(instance);
+            if histogram.is_done() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn histogram_counts_visible_lines_by_capture() {
+        let data: &[u8] = b"level=info a\nlevel=warn b\nlevel=info c\nnone\nlevel=error d\n\
+                            level=info e\nlevel=warn f\n";
+        let buf = SegBuffer::read_stream(Box::new(Cursor::new(data)), true).unwrap();
+        let colors = ColorSelector::Color256 { index: 0 };
+        let mut instance = Instance::with_color_selector(String::from("test"), buf, colors);
+        let mut histogram = HistogramApp::new(Regex::new(r"level=(\w+)").unwrap(), 0);
+
+        run(&mut histogram, &mut instance);
+        assert_eq!(
+            histogram.top(2),
+            [(String::from("info"), 3), (String::from("warn"), 2)]
+        );
+        assert_eq!(histogram.distinct(), 3);
+        // The trailing empty line is scanned too
+        assert_eq!(histogram.scanned(), 8);
+
+        // Only the lines shown by the filters are counted once they change
+        instance.toggle_filter(0);
+        instance.add_search_filter(" [a-c]\n", false).unwrap();
+        run(&mut histogram, &mut instance);
+        assert_eq!(
+            histogram.top(5),
+            [(String::from("info"), 2), (String::from("warn"), 1)]
+        );
+        assert_eq!(histogram.scanned(), 3);
+    }
+}
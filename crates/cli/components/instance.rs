@@ -1,12 +1,76 @@
 use super::{
     cursor::{Cursor, CursorState, SelectionOrigin},
-    filters::{Compositor, Filter, FilterExportSet},
+    filters::{BookmarksExport, Compositor, Filter, FilterExportSet, Mask},
+    query::Query,
     viewer::{CachedLine, ViewCache},
     viewport::Viewport,
 };
-use crate::{app::control::ViewDelta, colors::ColorSelector, direction::Direction};
-use bvr_core::SegBuffer;
-use bvr_core::{matches::CompositeStrategy, Result};
+use crate::{app::control::ViewDelta, cells, colors::ColorSelector, direction::Direction};
+use bvr_core::{
+    matches::{CompositeStrategy, MatchCount},
+    Result,
+};
+use bvr_core::{LineSet, SegBuffer, SegStr};
+use regex::bytes::Regex;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// The word around the character at byte `offset` of a line.
+fn word_at(line: &str, offset: usize) -> Option<&str> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+
+    let c = line[offset..].chars().next()?;
+    if !is_word(c) {
+        return None;
+    }
+
+    let start = line[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_word(c))
+        .last()
+        .map_or(offset, |(i, _)| i);
+    let end = line[offset..]
+        .find(|c: char| !is_word(c))
+        .map_or(line.len(), |i| offset + i);
+
+    Some(&line[start..end])
+}
+
+/// Byte offset of the bracket that pairs with the one at `offset`, skipping
+/// over nested pairs of the same kind.
+fn matching_bracket(line: &str, offset: usize) -> Option<usize> {
+    const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+    let c = line[offset..].chars().next()?;
+    let mut depth = 0usize;
+    if let Some(&(open, close)) = PAIRS.iter().find(|(open, _)| *open == c) {
+        for (i, c) in line[offset..].char_indices() {
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(offset + i);
+                }
+            }
+        }
+    } else if let Some(&(open, close)) = PAIRS.iter().find(|(_, close)| *close == c) {
+        for (i, c) in line[..=offset].char_indices().rev() {
+            if c == close {
+                depth += 1;
+            } else if c == open {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+        }
+    }
+    None
+}
 
 pub struct Instance {
     name: String,
@@ -14,21 +78,111 @@ pub struct Instance {
     cursor: CursorState,
     compositor: Compositor,
     view: ViewCache,
+    /// Line number to return to in the filtered view after viewing the original.
+    original_return: Option<usize>,
+    /// Overrides the global gutter setting for this instance.
+    gutter: Option<bool>,
+    /// Number of lines that precede the input, such as when it is a slice of
+    /// a larger file, added to the displayed line numbers.
+    line_offset: usize,
+    pending_jump: Option<PendingJump>,
+    /// Lines matching the sticky header pattern.
+    header: Option<LineSet>,
+    /// Filter enablement and strategy to restore after showing only bookmarks.
+    saved_filters: Option<(HashMap<usize, bool>, CompositeStrategy)>,
+    /// Line numbers that were just copied, highlighted until the deadline.
+    flash: Option<(Instant, Range<usize>)>,
+    /// Line number at the top of the view before following the output with
+    /// [Self::toggle_follow_output], to return to.
+    pinned: Option<usize>,
+    /// Draw lines as they are, with none of the display transforms.
+    raw: bool,
+    /// Canonical path of the file shown, if it is one.
+    path: Option<PathBuf>,
+    /// When the composite of the filters being computed was asked for.
+    composite_started: Option<Instant>,
+    /// How long the last composite took to compute, see [Self::composite_time].
+    composite_time: Option<Duration>,
+    /// Search of the lines before the selected one, see [Self::find_backward].
+    find: Option<LineSet>,
+    /// Changes whenever the filters make a new composite, see [Self::composite_id].
+    composite_id: usize,
+}
+
+/// Where the view of an instance was left, see [Instance::position].
+#[derive(Clone, Copy)]
+pub struct Position {
+    /// Line number at the top of the view.
+    line_number: usize,
+    left: usize,
+}
+
+/// A jump target that may only be resolvable once more of the input is indexed.
+#[derive(Clone, Copy)]
+pub enum PendingJump {
+    /// A one-based line number.
+    Line(usize),
+    /// A byte offset into the input.
+    Byte(u64),
+    /// A fraction of the input indexed so far, re-resolved as the input grows
+    /// until indexing completes.
+    Fraction(f64),
+    /// The first of this many lines at the end of the input, which are then
+    /// followed like `tail -f` if they fit in the view.
+    Tail(usize),
 }
 
 impl Instance {
     pub fn new(name: String, buf: SegBuffer) -> Self {
-        let mut compositor = Compositor::new(&buf);
+        Self::with_color_selector(name, buf, ColorSelector::new())
+    }
+
+    /// Like [Self::new], with the colors of filters picked by `color_selector`
+    /// rather than by one suited to the terminal.
+    pub(crate) fn with_color_selector(
+        name: String,
+        buf: SegBuffer,
+        color_selector: ColorSelector,
+    ) -> Self {
+        let mut compositor = Compositor::with_color_selector(&buf, color_selector);
         let composite = compositor.create_composite();
         Self {
             view: ViewCache::new(composite),
-            compositor: Compositor::new(&buf),
+            compositor: Compositor::with_color_selector(&buf, color_selector),
             name,
             buf,
             cursor: CursorState::new(),
+            original_return: None,
+            gutter: None,
+            line_offset: 0,
+            pending_jump: None,
+            header: None,
+            saved_filters: None,
+            flash: None,
+            pinned: None,
+            raw: false,
+            path: None,
+            composite_started: None,
+            composite_time: None,
+            find: None,
+            composite_id: 0,
         }
     }
 
+    /// Creates a new instance over the same buffer with a copy of the filters.
+    pub fn try_branch(&mut self) -> Result<Self> {
+        let mut branch = Self::new(self.name.clone(), self.buf.try_clone()?);
+        branch.compositor.set_strategy(self.compositor.strategy());
+        branch
+            .compositor
+            .set_max_matches(self.compositor.max_matches());
+        branch.import_user_filters(&self.compositor.filters().export(None));
+        branch.compositor.set_cursor(*self.compositor.cursor());
+        branch.line_offset = self.line_offset;
+        branch.path = self.path.clone();
+        Ok(branch)
+    }
+
     pub fn file(&self) -> &SegBuffer {
         &self.buf
     }
@@ -49,10 +203,82 @@ impl Instance {
         self.view.is_following_output()
     }
 
+    /// Follows the output, remembering where the view was, or stops following
+    /// it and goes back there. Returns whether the output is now followed.
+    pub fn toggle_follow_output(&mut self) -> bool {
+        if !self.is_following_output() {
+            self.pinned = self.view.line_at_view_index(self.viewport().top());
+            self.set_follow_output(true);
+            return true;
+        }
+
+        self.set_follow_output(false);
+        if let Some(index) = self
+            .pinned
+            .take()
+            .and_then(|line_number| self.nearest_index(line_number.saturating_add(1)))
+        {
+            self.view.viewport_mut().top_to(index);
+        }
+        false
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn set_path(&mut self, path: Option<PathBuf>) {
+        self.path = path;
+    }
+
+    /// Where the view is, to return to with [Self::restore_position].
+    pub fn position(&self) -> Option<Position> {
+        Some(Position {
+            line_number: self.view.line_at_view_index(self.viewport().top())?,
+            left: self.viewport().left(),
+        })
+    }
+
+    /// Brings the line that was at the top of the view back there, once it
+    /// is indexed.
+    pub fn restore_position(&mut self, position: Position) {
+        self.jump_to(PendingJump::Line(position.line_number.saturating_add(1)));
+        self.view.viewport_mut().left_to(position.left);
+    }
+
+    /// Whether lines are drawn as they are, checked before any of the
+    /// transforms of the view: JSON columns, filter colors and dimming,
+    /// search highlights, rulers, then tab expansion and control characters.
+    pub fn is_raw(&self) -> bool {
+        self.raw
+    }
+
+    pub fn toggle_raw(&mut self) -> bool {
+        self.raw = !self.raw;
+        self.raw
+    }
+
+    pub fn gutter(&self, default: bool) -> bool {
+        self.gutter.unwrap_or(default)
+    }
+
+    pub fn set_gutter(&mut self, gutter: Option<bool>) {
+        self.gutter = gutter;
+    }
+
     pub fn visible_line_count(&self) -> usize {
         self.view.composite().len()
     }
 
+    /// Whether every line that will be visible has been indexed and filtered.
+    pub fn is_view_complete(&self) -> bool {
+        self.view.composite().is_complete()
+    }
+
+    pub fn compositor(&self) -> &Compositor {
+        &self.compositor
+    }
+
     pub fn compositor_mut(&mut self) -> &mut Compositor {
         &mut self.compositor
     }
@@ -65,6 +291,12 @@ impl Instance {
         &self.cursor
     }
 
+    pub fn line_at_view_index(&self, index: usize) -> Option<SegStr> {
+        self.view
+            .line_at_view_index(index)
+            .and_then(|line_number| self.buf.get_line(line_number))
+    }
+
     pub fn nearest_index(&self, line_number: usize) -> Option<usize> {
         self.view
             .composite()
@@ -72,18 +304,202 @@ impl Instance {
             .and_then(|ln| self.view.composite().find(ln))
     }
 
-    pub fn update_and_view(
-        &mut self,
-        viewport_height: usize,
-        viewport_width: usize,
-    ) -> impl Iterator<Item = &CachedLine> {
+    /// Counts the lines shown by the current filters that match `regex`, in
+    /// the background.
+    pub fn count_matches(&self, regex: Regex) -> Result<MatchCount> {
+        let composite = self.view.composite();
+        let within = (!composite.is_all()).then(|| composite.clone());
+        Ok(MatchCount::search(self.buf.segment_iter()?, regex, within))
+    }
+
+    /// Whether the line is shown by the current filters.
+    pub fn is_line_visible(&self, line_number: usize) -> bool {
+        self.view.composite().find(line_number).is_some()
+    }
+
+    /// Line number of the selected line, scrolling it into view first.
+    pub fn selected_line_number(&mut self) -> Option<usize> {
+        self.move_selected_into_view();
+        let index = match self.cursor.state() {
+            Cursor::Singleton(i)
+            | Cursor::Selection(i, _, SelectionOrigin::Left)
+            | Cursor::Selection(_, i, SelectionOrigin::Right) => i,
+        };
+        self.view.line_at_view_index(index)
+    }
+
+    /// Moves the selection to the neighboring bookmark, even if bookmarks are
+    /// not shown in the current view. Bookmarks on lines hidden by the other
+    /// filters are skipped. Returns the position of the bookmark and the
+    /// total number of bookmarks.
+    pub fn jump_to_bookmark(&mut self, dir: Direction) -> Option<(usize, usize)> {
+        let current = self.selected_line_number()?;
+        let bookmarks = self.compositor.filters().bookmarks();
+        let mut target = current;
+        let index = loop {
+            target = match dir {
+                Direction::Back => bookmarks.nearest_backward(target).filter(|&ln| ln < target),
+                Direction::Next => bookmarks.nearest_forward(target).filter(|&ln| ln > target),
+            }?;
+            if let Some(index) = self.view.composite().find(target) {
+                break index;
+            }
+        };
+        let position = bookmarks.position(target)?;
+        let total = bookmarks.len();
+
+        self.cursor.place(index);
+        self.view.viewport_mut().jump_vertically_to(index);
+        self.set_follow_output(false);
+        Some((position, total))
+    }
+
+    /// Shows the selected line in the unfiltered view, remembering the line
+    /// so that the filtered view can be restored with [Self::return_from_original].
+    pub fn jump_to_original(&mut self) -> bool {
+        if !self.compositor.needs_composite() {
+            return false;
+        }
+        let Some(line_number) = self.selected_line_number() else {
+            return false;
+        };
+
+        self.original_return = Some(line_number);
+        self.compositor.filters_mut().all_mut().set_enabled(true);
+        self.invalidate_cache();
+
+        self.view.viewport_mut().jump_vertically_to(line_number);
+        self.cursor.place(line_number);
+        self.set_follow_output(false);
+        true
+    }
+
+    /// Restores the filtered view left by [Self::jump_to_original].
+    pub fn return_from_original(&mut self) -> bool {
+        let Some(line_number) = self.original_return.take() else {
+            return false;
+        };
+
+        self.compositor.filters_mut().all_mut().set_enabled(false);
+        self.invalidate_cache();
+
+        if let Some(index) = self.nearest_index(line_number.saturating_add(1)) {
+            self.view.viewport_mut().jump_vertically_to(index);
+            self.cursor.place(index);
+        }
+        self.set_follow_output(false);
+        true
+    }
+
+    /// Jumps to the target, chasing it while the input is still being indexed.
+    /// Returns whether the target has been reached.
+    pub fn jump_to(&mut self, jump: PendingJump) -> bool {
+        self.pending_jump = Some(jump);
+        self.resolve_pending_jump();
+        self.pending_jump.is_none()
+    }
+
+    pub fn pending_jump(&self) -> Option<PendingJump> {
+        self.pending_jump
+    }
+
+    fn resolve_pending_jump(&mut self) {
+        let Some(jump) = self.pending_jump else {
+            return;
+        };
+        let index = self.buf.index();
+        let complete = index.is_complete();
+        let last_line = index.line_count().saturating_sub(1);
+
+        let (line_number, resolved) = match jump {
+            PendingJump::Line(line_number) => match line_number.saturating_sub(1) {
+                line_number if line_number < index.line_count() => (line_number, true),
+                // Wait at the end of the indexed data until the line arrives
+                _ => (last_line, complete),
+            },
+            PendingJump::Byte(offset) => match index.line_of_data(offset) {
+                Some(line_number) => (line_number, true),
+                // Wait at the end of the indexed data until the offset arrives
+                None => (last_line, complete),
+            },
+            PendingJump::Fraction(fraction) => {
+                let data = index.data_range();
+                let offset = data.start + ((data.end - data.start) as f64 * fraction) as u64;
+                let line_number = index.line_of_data(offset).unwrap_or(last_line);
+                (line_number, complete)
+            }
+            // The view has no height until it is first drawn
+            PendingJump::Tail(count) => (
+                index.line_count().saturating_sub(count),
+                complete && self.view.viewport().height() > 0,
+            ),
+        };
+
+        if let Some(index) = self.nearest_index(line_number.saturating_add(1)) {
+            let viewport = self.view.viewport_mut();
+            match jump {
+                PendingJump::Tail(_) => viewport.top_to(index),
+                _ => viewport.jump_vertically_to(index),
+            }
+            self.cursor.place(index);
+            // Following only keeps the end in view, so the tail has to fit
+            let height = self.view.viewport().height();
+            self.view.set_follow_output(
+                matches!(jump, PendingJump::Tail(count) if resolved && count <= height),
+            );
+        }
+        if resolved {
+            self.pending_jump = None;
+        }
+    }
+
+    /// Fits the view to the area it is drawn in and reads the lines that
+    /// come into view, which [Self::cached_view] then returns.
+    pub fn update_view(&mut self, viewport_height: usize, viewport_width: usize) {
         self.view
             .viewport_mut()
             .fit_view(viewport_height, viewport_width);
         self.view.set_end_index(self.visible_line_count());
+        self.resolve_pending_jump();
+        if self.view.composite().is_complete() {
+            if let Some(started) = self.composite_started.take() {
+                self.composite_time = Some(started.elapsed());
+            }
+        }
 
-        self.view
-            .cache_view(&self.buf, |cache| cache.color_cache(&self.compositor))
+        let _ = self
+            .view
+            .cache_view(&self.buf, |cache| cache.color_cache(&self.compositor));
+    }
+
+    /// The lines in view as of the last [Self::update_view].
+    pub fn cached_view(&self) -> impl Iterator<Item = &CachedLine> {
+        self.view.cached_view()
+    }
+
+    pub fn set_header(&mut self, regex: Option<Regex>) -> Result<()> {
+        self.header = match regex {
+            Some(regex) => Some(LineSet::search(self.buf.segment_iter()?, regex)),
+            None => None,
+        };
+        Ok(())
+    }
+
+    pub fn has_header(&self) -> bool {
+        self.header.is_some()
+    }
+
+    /// The nearest header line above the top of the view, if the top line is
+    /// not a header itself.
+    pub fn sticky_header(&self) -> Option<(usize, SegStr)> {
+        let header = self.header.as_ref()?;
+        let top = self.view.line_at_view_index(self.viewport().top())?;
+        if header.has_line(top) {
+            return None;
+        }
+        // The first header is given back for lines above it too
+        let line_number = header.nearest_backward(top).filter(|&ln| ln < top)?;
+        Some((line_number, self.buf.get_line(line_number)?))
     }
 
     pub fn add_search_filter(&mut self, pattern: &str, literal: bool) -> Result<(), regex::Error> {
@@ -104,6 +520,77 @@ impl Instance {
         &self.name
     }
 
+    pub fn line_offset(&self) -> usize {
+        self.line_offset
+    }
+
+    pub fn set_line_offset(&mut self, line_offset: usize) {
+        self.line_offset = line_offset;
+    }
+
+    /// Returns a literal pattern for the word at the left edge of the view on
+    /// the cursor line, or a regex for the whole cursor line if a range of
+    /// lines is selected, along with whether the pattern is literal.
+    pub fn quick_filter_pattern(&mut self) -> Option<(String, bool)> {
+        self.move_selected_into_view();
+        let (index, whole_line) = match self.cursor.state() {
+            Cursor::Singleton(i) => (i, false),
+            Cursor::Selection(i, _, SelectionOrigin::Left)
+            | Cursor::Selection(_, i, SelectionOrigin::Right) => (i, true),
+        };
+        let line_number = self.view.line_at_view_index(index)?;
+        let data = self.buf.get_line(line_number)?;
+        let line = data.trim_end_matches(['\r', '\n']);
+
+        if whole_line {
+            (!line.is_empty()).then(|| (Mask::line_pattern(line), false))
+        } else {
+            // The left edge is a display column, which wide characters and
+            // tabs set apart from the character index
+            let offset = cells::offset_at(line, self.viewport().left())?;
+            word_at(line, offset).map(|word| (word.to_owned(), true))
+        }
+    }
+
+    /// Scrolls the bracket that pairs with the one at the left edge of the
+    /// view on the cursor line to the left edge. Returns `None` if there is no
+    /// bracket there, otherwise whether its pair was found on the line.
+    pub fn jump_to_matching_bracket(&mut self) -> Option<bool> {
+        let line_number = self.selected_line_number()?;
+        let data = self.buf.get_line(line_number)?;
+        let line = data.trim_end_matches(['\r', '\n']);
+
+        let offset = cells::offset_at(line, self.viewport().left())?;
+        if !line[offset..].starts_with(['(', ')', '[', ']', '{', '}']) {
+            return None;
+        }
+        let Some(pair) = matching_bracket(line, offset) else {
+            return Some(false);
+        };
+        self.viewport_mut().left_to(cells::column_of(line, pair));
+        Some(true)
+    }
+
+    /// Scrolls so that the zero-based display `column` of the cursor line is
+    /// at the left edge, or the last column of the line if it is shorter.
+    /// Returns the column scrolled to.
+    pub fn scroll_to_column(&mut self, column: usize) -> Option<usize> {
+        let line_number = self.selected_line_number()?;
+        let data = self.buf.get_line(line_number)?;
+        let line = data.trim_end_matches(['\r', '\n']);
+
+        // A wide character or tab is shown whole rather than cut
+        let left = match cells::offset_at(line, column) {
+            Some(offset) => cells::column_of(line, offset),
+            None => line
+                .char_indices()
+                .last()
+                .map_or(0, |(offset, _)| cells::column_of(line, offset)),
+        };
+        self.viewport_mut().left_to(left);
+        Some(left)
+    }
+
     pub fn move_selected_into_view(&mut self) {
         let current = match self.cursor.state() {
             Cursor::Singleton(i)
@@ -118,7 +605,9 @@ impl Instance {
         }
     }
 
-    pub fn move_viewport_vertical(&mut self, dir: Direction, delta: ViewDelta) {
+    /// Returns whether a match jump wrapped around the ends of the view.
+    pub fn move_viewport_vertical(&mut self, dir: Direction, delta: ViewDelta) -> bool {
+        self.pending_jump = None;
         let delta = match delta {
             ViewDelta::Number(n) => usize::from(n),
             ViewDelta::Page => self.view.viewport().height(),
@@ -126,17 +615,19 @@ impl Instance {
             ViewDelta::Boundary => usize::MAX,
             ViewDelta::Match => {
                 let current = self.view.viewport().top();
-                if let Some(next) =
+                let Some((next, wrapped)) =
                     self.compositor
-                        .compute_jump(current, dir, self.view.composite())
-                {
-                    self.view.viewport_mut().top_to(next)
-                }
-                return;
+                        .compute_wrapping_jump(current, dir, self.view.composite())
+                else {
+                    return false;
+                };
+                self.view.viewport_mut().top_to(next);
+                return wrapped;
             }
         };
         self.view.viewport_mut().pan_vertical(dir, delta);
         self.view.set_follow_output(false);
+        false
     }
 
     pub fn move_viewport_horizontal(&mut self, dir: Direction, delta: ViewDelta) {
@@ -150,7 +641,70 @@ impl Instance {
         self.set_follow_output(false);
     }
 
-    pub fn move_select(&mut self, dir: Direction, select: bool, delta: ViewDelta) {
+    /// Searches the lines before the selected line for `regex` in the
+    /// background, without adding a filter. The nearest visible match is
+    /// selected by [Self::resolve_find] once the search completes. Returns
+    /// whether there are any lines to search.
+    pub fn find_backward(&mut self, regex: Regex) -> Result<bool> {
+        let Some(current) = self.selected_line_number() else {
+            return Ok(false);
+        };
+        if current == 0 {
+            return Ok(false);
+        }
+        self.find = Some(LineSet::search(
+            self.buf.segment_iter_range(0..current)?,
+            regex,
+        ));
+        Ok(true)
+    }
+
+    pub fn is_finding(&self) -> bool {
+        self.find.is_some()
+    }
+
+    /// Selects the match of [Self::find_backward] once its search completes,
+    /// returning whether a visible line matched.
+    pub fn resolve_find(&mut self) -> Option<bool> {
+        if !self.find.as_ref()?.is_complete() {
+            return None;
+        }
+        let matches = self.find.take()?;
+        let Some(index) = (0..matches.len())
+            .rev()
+            .filter_map(|i| matches.get(i))
+            .find_map(|line_number| self.view.composite().find(line_number))
+        else {
+            return Some(false);
+        };
+
+        self.pending_jump = None;
+        self.cursor.place(index);
+        self.view.viewport_mut().jump_vertically_to(index);
+        self.set_follow_output(false);
+        Some(true)
+    }
+
+    /// Returns whether a match jump wrapped around the ends of the view.
+    pub fn move_select(&mut self, dir: Direction, select: bool, delta: ViewDelta) -> bool {
+        self.pending_jump = None;
+        if let ViewDelta::Match = delta {
+            let current = match self.cursor.state() {
+                Cursor::Singleton(i)
+                | Cursor::Selection(i, _, SelectionOrigin::Left)
+                | Cursor::Selection(_, i, SelectionOrigin::Right) => i,
+            };
+            if let Some((target, true)) =
+                self.compositor
+                    .compute_wrapping_jump(current, dir, self.view.composite())
+            {
+                // A selection cannot extend across the wrap
+                self.cursor.place(target);
+                self.view.viewport_mut().jump_vertically_to(target);
+                return true;
+            }
+        }
+
         let compute_delta = |i: usize| match delta {
             ViewDelta::Number(n) => usize::from(n),
             ViewDelta::Page => self.view.viewport().height(),
@@ -179,6 +733,28 @@ impl Instance {
             | Cursor::Selection(_, i, SelectionOrigin::Right) => i,
         };
         self.view.viewport_mut().jump_vertically_to(i);
+        false
+    }
+
+    pub fn select_range(&mut self, anchor: usize, head: usize) {
+        self.cursor.select(anchor, head);
+        self.cursor
+            .clamp(self.visible_line_count().saturating_sub(1));
+        self.set_follow_output(false);
+    }
+
+    /// Adds saved bookmarks, leaving out those past the lines indexed so far.
+    /// Returns how many were added and how many were left out.
+    pub fn import_bookmarks(&mut self, export: &BookmarksExport) -> (usize, usize) {
+        let line_count = self.buf.line_count();
+        let counts = self
+            .compositor
+            .filters_mut()
+            .bookmarks_mut()
+            .import(export, line_count);
+        self.view.set_end_index(self.visible_line_count());
+        self.invalidate_cache();
+        counts
     }
 
     pub fn toggle_bookmark_line_number(&mut self, line_number: usize) {
@@ -206,12 +782,15 @@ impl Instance {
         let mut needs_invalidation = true;
         match self.cursor.state() {
             Cursor::Singleton(i) => {
-                let line_number = self.view.line_at_view_index(i).unwrap();
+                // The cursor rests on line 0 even when there are no lines
+                let Some(line_number) = self.view.line_at_view_index(i) else {
+                    return;
+                };
                 return self.toggle_bookmark_line_number(line_number);
             }
             Cursor::Selection(start, end, _) => {
                 let line_numbers = (start..=end)
-                    .map(|i| self.view.line_at_view_index(i).unwrap())
+                    .filter_map(|i| self.view.line_at_view_index(i))
                     .collect::<Vec<_>>();
                 let present = line_numbers
                     .iter()
@@ -242,11 +821,41 @@ impl Instance {
         }
     }
 
+    /// Flips the bookmark of every visible line, so that exactly the visible
+    /// lines that were not bookmarked are.
+    pub fn invert_bookmarks(&mut self) {
+        let composite = self.view.composite().clone();
+        let line_numbers = (0..composite.len()).map_while(|i| composite.get(i));
+        self.compositor
+            .filters_mut()
+            .bookmarks_mut()
+            .toggle_all(line_numbers);
+
+        self.cursor
+            .clamp(self.visible_line_count().saturating_sub(1));
+        self.view.set_end_index(self.visible_line_count());
+        self.invalidate_cache();
+    }
+
     pub fn clear_filters(&mut self) {
         self.compositor.clear_filters();
         self.invalidate_cache();
     }
 
+    /// See [Compositor::refresh_filters].
+    pub fn refresh_filters(&mut self) -> usize {
+        let refreshed = self.compositor.refresh_filters(&self.buf);
+        self.invalidate_cache();
+        refreshed
+    }
+
+    /// See [Compositor::apply_query].
+    pub fn apply_query(&mut self, query: &Query) -> Result<(), regex::Error> {
+        self.compositor.apply_query(&self.buf, query)?;
+        self.invalidate_cache();
+        Ok(())
+    }
+
     pub fn toggle_filters(&mut self, range: std::ops::Range<usize>) {
         self.compositor.toggle_filters(range);
         self.invalidate_cache();
@@ -270,14 +879,119 @@ impl Instance {
         self.invalidate_cache();
     }
 
-    pub fn write_bytes(&mut self, mut file: &mut impl std::io::Write) -> Result<()> {
-        self.buf.write_bytes(&mut file, self.view.composite())
+    /// Enables only the bookmarks filter, remembering the previous state of
+    /// the filters for [Instance::restore_filters].
+    pub fn show_only_bookmarks(&mut self) {
+        if self.saved_filters.is_none() {
+            let enabled = self
+                .compositor
+                .filters()
+                .iter()
+                .map(|filter| (filter.id(), filter.is_enabled()));
+            self.saved_filters = Some((enabled.collect(), self.compositor.strategy()));
+        }
+
+        for filter in self.compositor.filters_mut().iter_mut() {
+            let bookmarks = matches!(filter.mask(), Mask::Bookmarks);
+            filter.set_enabled(bookmarks);
+        }
+        self.compositor.set_strategy(CompositeStrategy::Union);
+        self.invalidate_cache();
+    }
+
+    /// Restores the filters from before [Instance::show_only_bookmarks],
+    /// returning whether there was anything to restore. Filters added since
+    /// keep their state.
+    pub fn restore_filters(&mut self) -> bool {
+        let Some((enabled, strategy)) = self.saved_filters.take() else {
+            return false;
+        };
+
+        for filter in self.compositor.filters_mut().iter_mut() {
+            if let Some(&enabled) = enabled.get(&filter.id()) {
+                filter.set_enabled(enabled);
+            }
+        }
+        self.compositor.set_strategy(strategy);
+        self.invalidate_cache();
+        true
+    }
+
+    /// The lines that are exported: those currently visible, which is the
+    /// whole file unless filters are active, or the whole file regardless of
+    /// the filters if `whole_file` is set.
+    fn export_lines(&self, whole_file: bool) -> LineSet {
+        if whole_file {
+            self.buf.all_line_matches()
+        } else {
+            self.view.composite().clone()
+        }
+    }
+
+    pub fn write_bytes(
+        &mut self,
+        mut file: &mut impl std::io::Write,
+        whole_file: bool,
+    ) -> Result<()> {
+        let lines = self.export_lines(whole_file);
+        self.buf.write_bytes(&mut file, &lines)
+    }
+
+    /// Streams the exported lines through `write_line` one at a time, along
+    /// with their line numbers and the byte offsets of their starts.
+    pub fn write_lines(
+        &mut self,
+        whole_file: bool,
+        write_line: impl FnMut(usize, u64, &[u8]) -> std::io::Result<()>,
+    ) -> Result<()> {
+        let lines = self.export_lines(whole_file);
+        self.buf.write_lines(&lines, write_line)
+    }
+
+    /// Text of the lines matched by the selected filter so far, regardless of
+    /// the composite, and whether its search has finished.
+    pub fn selected_filter_string(&self) -> Option<(String, bool)> {
+        let filter = self.compositor.selected_filter()?;
+        let lines = match filter.len() {
+            // The filter that matches every line
+            None => self.buf.all_line_matches(),
+            Some(_) => filter.as_line_matches(),
+        };
+        let complete = filter.is_complete() && lines.is_complete();
+
+        let mut output = String::new();
+        for i in 0..lines.len() {
+            let Some(line) = lines.get(i).and_then(|ln| self.buf.get_line(ln)) else {
+                break;
+            };
+            output.push_str(line.as_str());
+        }
+        output.truncate(output.trim_end().len());
+        Some((output, complete))
+    }
+
+    /// Highlights the `lines` for a moment, such as to confirm a copy. Does
+    /// nothing for a zero duration.
+    pub fn flash(&mut self, lines: Range<usize>, duration: Duration) {
+        self.flash = (!duration.is_zero()).then(|| (Instant::now() + duration, lines));
+    }
+
+    /// The line numbers to highlight, until the flash runs out.
+    pub fn flashing_lines(&mut self) -> Option<Range<usize>> {
+        if self
+            .flash
+            .as_ref()
+            .is_some_and(|(deadline, _)| Instant::now() >= *deadline)
+        {
+            self.flash = None;
+        }
+        self.flash.as_ref().map(|(_, lines)| lines.clone())
     }
 
-    pub fn export_string(&mut self) -> Result<String> {
+    pub fn export_string(&mut self, whole_file: bool) -> Result<String> {
+        let lines = self.export_lines(whole_file);
         let mut output = String::new();
-        self.buf
-            .write_to_string(&mut output, self.view.composite())?;
+        self.buf.write_to_string(&mut output, &lines)?;
         output.truncate(output.trim_end_matches('\0').len());
         Ok(output)
     }
@@ -291,13 +1005,261 @@ impl Instance {
         } else {
             self.view
                 .insert_new_line_set(self.compositor.create_composite());
+            self.composite_started = Some(Instant::now());
+            self.composite_id = self.composite_id.wrapping_add(1);
         }
     }
 
+    /// Identifies the composite of the filters, so that anything computed
+    /// over the visible lines can tell when they are replaced.
+    pub fn composite_id(&self) -> usize {
+        self.composite_id
+    }
+
+    /// How long the composite of the filters has been computing for, with
+    /// `true`, or else how long the last one took. Completion is noticed when
+    /// the view is next drawn, so times are rounded up to a frame.
+    pub fn composite_time(&self) -> Option<(Duration, bool)> {
+        match self.composite_started {
+            Some(started) => Some((started.elapsed(), true)),
+            None => self.composite_time.map(|time| (time, false)),
+        }
+    }
+
+    /// Searches of the filters that have not reached the end of the input.
+    pub fn running_searches(&self) -> usize {
+        self.compositor
+            .filters()
+            .iter()
+            .filter(|filter| !filter.as_line_matches().is_complete())
+            .count()
+    }
+
     pub fn import_user_filters(&mut self, filters: &FilterExportSet) {
+        let max_matches = self.compositor.max_matches();
         self.compositor
             .filters_mut()
-            .import_user_filters(&self.buf, filters);
+            .import_user_filters(&self.buf, filters, max_matches);
         self.invalidate_cache();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    fn instance(data: &'static [u8]) -> Instance {
+        let buf = SegBuffer::read_stream(Box::new(Cursor::new(data)), true).unwrap();
+        let colors = ColorSelector::Color256 { index: 0 };
+        Instance::with_color_selector(String::from("test"), buf, colors)
+    }
+
+    #[test]
+    fn sticky_header_is_above_the_top_line() {
+        let mut instance = instance(b"a\nb\n# one\nc\nd\n# two\ne\n");
+        instance.set_header(Some(Regex::new("#").unwrap())).unwrap();
+        while !instance.header.as_ref().unwrap().is_complete() {
+            std::thread::yield_now();
+        }
+        let header = |instance: &mut Instance, top: usize| {
+            instance.viewport_mut().top_to(top);
+            instance.sticky_header().map(|(line_number, _)| line_number)
+        };
+
+        // No header is above the lines before the first one
+        assert_eq!(header(&mut instance, 0), None);
+        assert_eq!(header(&mut instance, 3), Some(2));
+        // A header at the top is shown where it is
+        assert_eq!(header(&mut instance, 5), None);
+        assert_eq!(header(&mut instance, 6), Some(5));
+    }
+
+    #[test]
+    fn jump_to_bookmark_skips_hidden_bookmarks() {
+        let mut instance = instance(b"x0\ny1\nx2\ny3\nx4\n");
+        while !instance.file().index().is_complete() {
+            std::thread::yield_now();
+        }
+        for line_number in 0..4 {
+            instance.toggle_bookmark_line_number(line_number);
+        }
+        // Only the lines matching `x` are shown, bookmarked or not
+        instance.toggle_filter(0);
+        instance.toggle_filter(1);
+        instance.add_search_filter("x", true).unwrap();
+        while !instance.view.composite().is_complete() {
+            std::thread::yield_now();
+        }
+        instance.update_view(10, 80);
+
+        assert_eq!(instance.selected_line_number(), Some(0));
+        assert_eq!(instance.jump_to_bookmark(Direction::Next), Some((2, 4)));
+        assert_eq!(instance.selected_line_number(), Some(2));
+        // The last bookmark is hidden, so there is none to move to
+        assert_eq!(instance.jump_to_bookmark(Direction::Next), None);
+        assert_eq!(instance.selected_line_number(), Some(2));
+        assert_eq!(instance.jump_to_bookmark(Direction::Back), Some((0, 4)));
+        assert_eq!(instance.selected_line_number(), Some(0));
+    }
+
+    #[test]
+    fn find_backward_selects_the_nearest_earlier_match() {
+        let mut instance = instance(b"foo\na\nfoo\nb\nfoo\nc\n");
+        while !instance.file().index().is_complete() {
+            std::thread::yield_now();
+        }
+        instance.update_view(10, 80);
+        instance.cursor.place(4);
+        let find = |instance: &mut Instance| {
+            if !instance.find_backward(Regex::new("foo").unwrap()).unwrap() {
+                return None;
+            }
+            loop {
+                match instance.resolve_find() {
+                    Some(found) => break found.then(|| instance.selected_line_number().unwrap()),
+                    None => std::thread::yield_now(),
+                }
+            }
+        };
+
+        // The selected line itself is not a match
+        assert_eq!(find(&mut instance), Some(2));
+        assert_eq!(find(&mut instance), Some(0));
+        assert_eq!(find(&mut instance), None);
+        assert_eq!(instance.selected_line_number(), Some(0));
+        assert!(!instance.is_finding());
+    }
+
+    #[test]
+    fn empty_input_can_be_navigated() {
+        let mut instance = instance(b"");
+        while !instance.file().index().is_complete() {
+            std::thread::yield_now();
+        }
+        for (height, width) in [(0, 0), (10, 80), (1, 1)] {
+            instance.update_view(height, width);
+            for dir in [Direction::Next, Direction::Back] {
+                for delta in [
+                    ViewDelta::Number(3),
+                    ViewDelta::Page,
+                    ViewDelta::HalfPage,
+                    ViewDelta::Boundary,
+                    ViewDelta::Match,
+                ] {
+                    instance.move_select(dir, false, delta);
+                    instance.move_select(dir, true, delta);
+                    instance.move_viewport_vertical(dir, delta);
+                    instance.move_viewport_horizontal(dir, delta);
+                }
+                instance.jump_to_bookmark(dir);
+            }
+            for jump in [
+                PendingJump::Line(10),
+                PendingJump::Byte(10),
+                PendingJump::Fraction(0.5),
+                PendingJump::Tail(5),
+            ] {
+                instance.jump_to(jump);
+                instance.update_view(height, width);
+            }
+        }
+
+        // The input is a single empty line, which stays selected
+        assert_eq!(instance.visible_line_count(), 1);
+        assert_eq!(instance.selected_line_number(), Some(0));
+    }
+
+    fn enabled_filters(instance: &Instance) -> Vec<bool> {
+        let filters = instance.compositor().filters();
+        filters.iter().map(Filter::is_enabled).collect()
+    }
+
+    #[test]
+    fn bookmarks_only_round_trip_restores_filters() {
+        let mut instance = instance(b"x0\ny1\nx2\n");
+        instance.add_search_filter("x", true).unwrap();
+        instance.add_search_filter("y", true).unwrap();
+        instance.toggle_filter(3);
+        instance.set_composite_strategy(CompositeStrategy::Intersection);
+        let before = enabled_filters(&instance);
+
+        instance.show_only_bookmarks();
+        assert_eq!(enabled_filters(&instance), [false, true, false, false]);
+        assert_eq!(instance.compositor().strategy(), CompositeStrategy::Union);
+        // Showing only bookmarks again keeps the state from the first time
+        instance.show_only_bookmarks();
+
+        assert!(instance.restore_filters());
+        assert_eq!(enabled_filters(&instance), before);
+        assert_eq!(
+            instance.compositor().strategy(),
+            CompositeStrategy::Intersection
+        );
+        assert!(!instance.restore_filters());
+    }
+
+    #[test]
+    fn bookmarks_only_round_trip_follows_removed_filters() {
+        let mut instance = instance(b"x0\ny1\nx2\n");
+        instance.add_search_filter("x", true).unwrap();
+        instance.add_search_filter("y", true).unwrap();
+        instance.toggle_filter(3);
+
+        instance.show_only_bookmarks();
+        // The filters after the removed one move up, and a new one is added
+        instance.remove_filters(2..3);
+        instance.add_search_filter("z", true).unwrap();
+
+        assert!(instance.restore_filters());
+        assert_eq!(enabled_filters(&instance), [true, true, false, true]);
+    }
+
+    #[test]
+    fn quick_filter_counts_display_columns() {
+        let mut instance = instance("日本 foo\tbar baz\n".as_bytes());
+        instance.update_view(10, 80);
+        let mut word_at = |left: usize| {
+            instance.viewport_mut().left_to(left);
+            instance.quick_filter_pattern().map(|(pattern, literal)| {
+                assert!(literal);
+                pattern
+            })
+        };
+
+        // Wide characters take two columns
+        assert_eq!(word_at(3).as_deref(), Some("日本"));
+        assert_eq!(word_at(4), None);
+        assert_eq!(word_at(5).as_deref(), Some("foo"));
+        // A tab reaches up to the next stop
+        assert_eq!(word_at(12), None);
+        assert_eq!(word_at(16).as_deref(), Some("bar"));
+        assert_eq!(word_at(21).as_deref(), Some("baz"));
+        assert_eq!(word_at(30), None);
+    }
+
+    #[test]
+    fn quick_filter_of_a_selection_matches_the_line_as_is() {
+        let mut instance = instance(b"^a$\na\n");
+        instance.update_view(10, 80);
+        instance.cursor.select(1, 0);
+
+        let (pattern, literal) = instance.quick_filter_pattern().unwrap();
+        assert!(!literal);
+        let (_, regex) = Mask::build(&pattern, literal).unwrap();
+        assert!(regex.is_match(b"^a$\n"));
+        assert!(!regex.is_match(b"a\n"));
+    }
+
+    #[test]
+    fn matching_bracket_skips_nested_pairs() {
+        let line = r#"{"a": [1, {"b": (2)}], "c": {}}"#;
+        assert_eq!(matching_bracket(line, 0), Some(line.len() - 1));
+        assert_eq!(matching_bracket(line, line.len() - 1), Some(0));
+        assert_eq!(matching_bracket(line, 6), Some(20));
+        assert_eq!(matching_bracket(line, 20), Some(6));
+        // Other kinds of brackets do not count toward the depth
+        assert_eq!(matching_bracket("(]", 0), None);
+        assert_eq!(matching_bracket("[(]", 0), Some(2));
+    }
+}
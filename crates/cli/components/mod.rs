@@ -2,9 +2,15 @@ pub mod cursor;
 pub mod viewport;
 
 pub mod config;
+pub mod diff;
 pub mod filters;
+pub mod help;
+pub mod histogram;
 pub mod instance;
 pub mod mux;
+pub mod peek;
+pub mod perf;
 pub mod prompt;
+pub mod query;
 pub mod status;
 pub mod viewer;
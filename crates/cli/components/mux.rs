@@ -1,7 +1,7 @@
 use super::instance::Instance;
 use crate::direction::Direction;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum MultiplexerMode {
     Panes,
     Tabs,
@@ -20,17 +20,37 @@ pub struct MultiplexerApp {
     instances: Vec<Instance>,
     mode: MultiplexerMode,
     active: usize,
+    max_instances: usize,
 }
 
 impl MultiplexerApp {
+    /// Default soft limit on the number of open instances.
+    pub const DEFAULT_MAX_INSTANCES: usize = 16;
+    /// Past this many instances, panes are too narrow to be legible.
+    const MAX_PANES: usize = 4;
+
     pub fn new() -> Self {
         Self {
             instances: Vec::new(),
             mode: MultiplexerMode::Tabs,
             active: 0,
+            max_instances: Self::DEFAULT_MAX_INSTANCES,
         }
     }
 
+    pub fn max_instances(&self) -> usize {
+        self.max_instances
+    }
+
+    pub fn set_max_instances(&mut self, max_instances: usize) {
+        self.max_instances = max_instances;
+    }
+
+    /// Whether the instances can be legibly laid out side by side.
+    pub fn can_split(&self) -> bool {
+        self.instances.len() <= Self::MAX_PANES
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.instances.len()
@@ -41,9 +61,18 @@ impl MultiplexerApp {
         self.instances.is_empty()
     }
 
-    #[inline]
-    pub fn push(&mut self, instance: Instance) {
+    /// Push a new instance, falling back to tabs if there are too many
+    /// instances to split. Returns `false` if the soft limit is exceeded.
+    pub fn push(&mut self, instance: Instance) -> bool {
         self.instances.push(instance);
+        if !self.can_split() {
+            self.mode = MultiplexerMode::Tabs;
+        }
+        self.instances.len() <= self.max_instances
+    }
+
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
     }
 
     pub fn instances_mut(&mut self) -> &mut Vec<Instance> {
@@ -65,6 +94,35 @@ impl MultiplexerApp {
         })
     }
 
+    /// Makes the next instance in `direction` whose selected filter has any
+    /// matches active, wrapping around. Returns `false` if no other instance
+    /// has matches.
+    pub fn move_active_with_matches(&mut self, direction: Direction) -> bool {
+        let len = self.instances.len();
+        let next = (1..len)
+            .map(|step| match direction {
+                Direction::Back => (self.active + len - step) % len,
+                Direction::Next => (self.active + step) % len,
+            })
+            .find(|&i| {
+                self.instances[i]
+                    .compositor()
+                    .selected_filter()
+                    .and_then(|filter| filter.len())
+                    .is_some_and(|len| len > 0)
+            });
+        if let Some(index) = next {
+            self.active = index;
+        }
+        next.is_some()
+    }
+
+    /// Returns the indices of the instances whose names best match the query,
+    /// see [find_matches].
+    pub fn find_by_name(&self, query: &str) -> Vec<usize> {
+        find_matches(query, self.instances.iter().map(Instance::name))
+    }
+
     pub fn close_active(&mut self) {
         debug_assert!(self.active < self.instances.len());
         self.instances.remove(self.active);
@@ -109,3 +167,30 @@ impl MultiplexerApp {
         self.active = 0;
     }
 }
+
+/// Finds the `names` that match `query`, ignoring case. Exact matches take
+/// precedence over substrings, which take precedence over subsequences.
+pub fn find_matches<'a>(query: &str, names: impl IntoIterator<Item = &'a str>) -> Vec<usize> {
+    let query = query.to_lowercase();
+    let is_subsequence = |name: &str| {
+        let mut chars = name.chars();
+        query.chars().all(|q| chars.any(|c| c == q))
+    };
+    let tiers: [&dyn Fn(&str) -> bool; 3] = [
+        &|name| name == query,
+        &|name| name.contains(&query),
+        &is_subsequence,
+    ];
+
+    let names = names.into_iter().map(str::to_lowercase).collect::<Vec<_>>();
+    tiers
+        .iter()
+        .map(|matches| {
+            (names.iter().enumerate())
+                .filter(|(_, name)| matches(name.as_str()))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>()
+        })
+        .find(|candidates| !candidates.is_empty())
+        .unwrap_or_default()
+}
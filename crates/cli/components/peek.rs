@@ -0,0 +1,56 @@
+use super::instance::Instance;
+
+/// A read-only snapshot of the unfiltered lines around the selected line,
+/// shown over the view without leaving it.
+pub struct PeekApp {
+    /// Line number of the line the peek was opened on.
+    line_number: usize,
+    line_offset: usize,
+    lines: Vec<PeekLine>,
+}
+
+pub struct PeekLine {
+    pub line_number: usize,
+    pub text: String,
+    /// Whether the line is also shown by the filtered view.
+    pub visible: bool,
+}
+
+impl PeekApp {
+    /// Lines shown on either side of the selected line.
+    pub const CONTEXT: usize = 5;
+
+    pub fn new(instance: &mut Instance, context: usize) -> Option<Self> {
+        let line_number = instance.selected_line_number()?;
+        let end = line_number
+            .saturating_add(context + 1)
+            .min(instance.file().line_count());
+        let lines = (line_number.saturating_sub(context)..end)
+            .filter_map(|ln| {
+                Some(PeekLine {
+                    line_number: ln,
+                    text: instance.file().get_line(ln)?.as_str().to_owned(),
+                    visible: instance.is_line_visible(ln),
+                })
+            })
+            .collect();
+        Some(Self {
+            line_number,
+            line_offset: instance.line_offset(),
+            lines,
+        })
+    }
+
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Added to line numbers before they are shown, as in the view.
+    pub fn line_offset(&self) -> usize {
+        self.line_offset
+    }
+
+    pub fn lines(&self) -> &[PeekLine] {
+        &self.lines
+    }
+}
@@ -0,0 +1,86 @@
+use super::instance::Instance;
+use bvr_core::buf::BufferStats;
+use std::time::Duration;
+
+/// Timings and counters of the active view, sampled once per frame for the
+/// `:debug perf` overlay.
+#[derive(Default)]
+pub struct PerfApp {
+    /// Time taken to lay out and draw the last frame.
+    frame: Duration,
+    /// Counters of the buffer at the last sample, to tell how many segments
+    /// were fetched since.
+    last: BufferStats,
+    rows: Vec<(&'static str, String)>,
+}
+
+impl PerfApp {
+    pub fn set_frame_time(&mut self, frame: Duration) {
+        self.frame = frame;
+    }
+
+    /// Reads the counters of the active view. The frame time is that of the
+    /// frame before, since this one is not drawn yet.
+    pub fn sample(&mut self, instance: Option<&Instance>, counting: bool) {
+        self.rows.clear();
+        self.rows
+            .push(("frame", format!("{:.1} ms", self.frame.as_secs_f64() * 1e3)));
+
+        let Some(instance) = instance else {
+            self.last = BufferStats::default();
+            return;
+        };
+        let stats = instance.file().stats();
+        // Another view was made active since the last sample
+        let last = if stats.fetches < self.last.fetches {
+            BufferStats::default()
+        } else {
+            self.last
+        };
+        self.rows.push((
+            "fetches",
+            format!(
+                "{} ({} mapped)",
+                stats.fetches - last.fetches,
+                stats.maps.saturating_sub(last.maps)
+            ),
+        ));
+        self.rows.push((
+            "segments",
+            match stats.capacity {
+                Some(capacity) => format!("{}/{capacity} cached", stats.cached),
+                None => format!("{} in memory", stats.cached),
+            },
+        ));
+        self.last = stats;
+
+        self.rows.push((
+            "index",
+            String::from(if instance.file().index().is_complete() {
+                "done"
+            } else {
+                "running"
+            }),
+        ));
+        self.rows.push((
+            "searches",
+            format!("{} running", instance.running_searches()),
+        ));
+        self.rows.push((
+            "count",
+            String::from(if counting { "running" } else { "idle" }),
+        ));
+        self.rows.push((
+            "composite",
+            match instance.composite_time() {
+                Some((time, true)) => format!("running for {} ms", time.as_millis()),
+                Some((time, false)) => format!("{:.1} ms", time.as_secs_f64() * 1e3),
+                None => String::from("none"),
+            },
+        ));
+    }
+
+    pub fn rows(&self) -> &[(&'static str, String)] {
+        &self.rows
+    }
+}
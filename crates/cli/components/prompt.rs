@@ -9,6 +9,9 @@ pub enum PromptDelta {
     Number(usize),
     Word,
     Boundary,
+    /// Like [PromptDelta::Boundary], except that going back stops at the
+    /// start of the arguments of a command before the start of the prompt.
+    SmartBoundary,
 }
 
 #[derive(Clone, Copy)]
@@ -25,12 +28,27 @@ impl PromptMovement {
     }
 }
 
+/// The candidates of a completion, which completing again cycles through
+/// until the prompt is changed in any other way.
+struct Completion {
+    /// Byte offset of the start of the completed word.
+    start: usize,
+    /// Byte offset of the end of the text in place of the word.
+    end: usize,
+    /// The word as typed, which comes back after the last candidate.
+    typed: String,
+    candidates: Vec<String>,
+    /// The candidate in place of the word, or `None` for the typed word.
+    selected: Option<usize>,
+}
+
 pub struct PromptApp {
     history: Vec<String>,
     index: usize,
     buf: String,
     cursor: CursorState,
     viewport: Viewport,
+    completion: Option<Completion>,
 }
 
 impl PromptApp {
@@ -41,6 +59,7 @@ impl PromptApp {
             buf: String::new(),
             cursor: CursorState::new(),
             viewport: Viewport::new(),
+            completion: None,
         }
     }
 
@@ -76,6 +95,7 @@ impl PromptApp {
     }
 
     pub fn move_cursor(&mut self, direction: Direction, movement: PromptMovement) {
+        self.completion = None;
         let buf = if self.index < self.history.len() {
             &self.history[self.index]
         } else {
@@ -84,25 +104,24 @@ impl PromptApp {
         match direction {
             Direction::Back => self.cursor.back(movement.select, |i| match movement.delta {
                 PromptDelta::Word => {
-                    if buf[..i]
-                        .chars()
-                        .rev()
-                        .nth(0)
-                        .map(|c| c.is_whitespace())
-                        .unwrap_or(false)
-                    {
-                        i.saturating_sub(
-                            buf[..i]
-                                .chars()
-                                .rev()
-                                .position(|c| c.is_alphanumeric())
-                                .unwrap_or(0),
-                        )
+                    let before = &buf[..i];
+                    if before.ends_with(char::is_whitespace) {
+                        before
+                            .trim_end_matches(|c: char| !c.is_alphanumeric())
+                            .len()
                     } else {
-                        buf[..i].rfind(' ').map(|p| p + 1).unwrap_or(0)
+                        before.rfind(' ').map(|p| p + 1).unwrap_or(0)
                     }
                 }
                 PromptDelta::Boundary => 0,
+                PromptDelta::SmartBoundary => {
+                    let start = arguments_start(buf);
+                    if i > start {
+                        start
+                    } else {
+                        0
+                    }
+                }
                 PromptDelta::Number(delta) => i.saturating_sub(
                     buf[..i]
                         .chars()
@@ -114,28 +133,23 @@ impl PromptApp {
             }),
             Direction::Next => self.cursor.forward(movement.select, |i| {
                 match movement.delta {
+                    // Byte offsets are found directly, rather than counting
+                    // characters and walking over them again
                     PromptDelta::Word => {
-                        if buf[i..]
-                            .chars()
-                            .nth(0)
-                            .map(|c| c.is_whitespace())
-                            .unwrap_or(false)
-                        {
-                            i.saturating_add(
-                                buf[i..]
-                                    .chars()
-                                    .position(|c| c.is_alphanumeric())
-                                    .unwrap_or(usize::MAX),
-                            )
+                        let after = &buf[i..];
+                        if after.starts_with(char::is_whitespace) {
+                            after
+                                .find(char::is_alphanumeric)
+                                .map_or(usize::MAX, |z| z + i)
                         } else {
-                            buf[(i + 1).min(buf.len())..]
-                                .chars()
-                                .position(|c| c.is_whitespace())
-                                .map(|z| z + i + 1)
-                                .unwrap_or(usize::MAX)
+                            after
+                                .char_indices()
+                                .skip(1)
+                                .find(|(_, c)| c.is_whitespace())
+                                .map_or(usize::MAX, |(z, _)| z + i)
                         }
                     }
-                    PromptDelta::Boundary => usize::MAX,
+                    PromptDelta::Boundary | PromptDelta::SmartBoundary => usize::MAX,
                     PromptDelta::Number(delta) => i.saturating_add(
                         buf[i..]
                             .chars()
@@ -154,42 +168,36 @@ impl PromptApp {
         self.enter_str(input.encode_utf8(&mut b));
     }
 
-    pub fn enter_str(&mut self, input: &str) {
+    /// Copies the entry of the history being shown into the buffer, so that
+    /// it can be edited without changing the history.
+    fn detach_history(&mut self) {
         if self.index < self.history.len() {
             self.buf = self.history[self.index].clone();
             self.index = self.history.len();
         }
+    }
+
+    pub fn enter_str(&mut self, input: &str) {
+        self.completion = None;
+        self.detach_history();
 
+        // The cursor is a byte index, so it lands right after the input
+        // without walking over it
         match self.cursor.state() {
             Cursor::Singleton(i) => {
                 self.buf.insert_str(i, input);
-                self.move_cursor(
-                    Direction::Next,
-                    PromptMovement {
-                        select: false,
-                        delta: PromptDelta::Number(input.len()),
-                    },
-                )
+                self.cursor.place(i + input.len());
             }
             Cursor::Selection(start, end, _) => {
                 self.buf.replace_range(start..end, input);
-                self.move_cursor(Direction::Back, PromptMovement::DEFAULT);
-                self.move_cursor(
-                    Direction::Next,
-                    PromptMovement {
-                        select: false,
-                        delta: PromptDelta::Number(input.len()),
-                    },
-                )
+                self.cursor.place(start + input.len());
             }
         }
     }
 
     pub fn delete(&mut self) -> bool {
-        if self.index < self.history.len() {
-            self.buf = self.history[self.index].clone();
-            self.index = self.history.len();
-        }
+        self.completion = None;
+        self.detach_history();
 
         match self.cursor.state() {
             Cursor::Singleton(curr) => {
@@ -211,11 +219,13 @@ impl PromptApp {
     }
 
     pub fn backward(&mut self) {
+        self.completion = None;
         self.index = self.index.saturating_sub(1);
         self.cursor.place(self.buf().len());
     }
 
     pub fn forward(&mut self) {
+        self.completion = None;
         self.index = self.index.saturating_add(1).min(self.history.len());
         self.cursor.place(self.buf().len());
     }
@@ -229,7 +239,73 @@ impl PromptApp {
         output
     }
 
+    /// Completes the word before the cursor, or moves on to the next or
+    /// previous candidate when completing again. `candidates` is given the
+    /// text before the cursor, and returns where the word starts in it along
+    /// with the candidates to replace it with.
+    ///
+    /// A lone candidate is taken right away, so that completing again starts
+    /// from it, such as to go into a directory.
+    pub fn complete(
+        &mut self,
+        direction: Direction,
+        candidates: impl FnOnce(&str) -> (usize, Vec<String>),
+    ) {
+        if self.completion.is_none() {
+            let Cursor::Singleton(cursor) = self.cursor.state() else {
+                return;
+            };
+            self.detach_history();
+            let (start, mut candidates) = candidates(&self.buf[..cursor]);
+            match candidates.len() {
+                0 => return,
+                1 => {
+                    let candidate = candidates.remove(0);
+                    self.buf.replace_range(start..cursor, &candidate);
+                    self.cursor.place(start + candidate.len());
+                    return;
+                }
+                _ => {
+                    self.completion = Some(Completion {
+                        start,
+                        end: cursor,
+                        typed: self.buf[start..cursor].to_owned(),
+                        candidates,
+                        selected: None,
+                    })
+                }
+            }
+        }
+
+        let Some(completion) = &mut self.completion else {
+            return;
+        };
+        let last = completion.candidates.len() - 1;
+        completion.selected = match (direction, completion.selected) {
+            (Direction::Next, None) => Some(0),
+            (Direction::Next, Some(i)) => (i < last).then_some(i + 1),
+            (Direction::Back, None) => Some(last),
+            (Direction::Back, Some(i)) => i.checked_sub(1),
+        };
+        let text = match completion.selected {
+            Some(i) => &completion.candidates[i],
+            None => &completion.typed,
+        };
+        self.buf
+            .replace_range(completion.start..completion.end, text);
+        completion.end = completion.start + text.len();
+        self.cursor.place(completion.end);
+    }
+
+    /// The candidates of the completion in progress, and which of them is in
+    /// place of the typed word.
+    pub fn completion(&self) -> Option<(&[String], Option<usize>)> {
+        let completion = self.completion.as_ref()?;
+        Some((&completion.candidates, completion.selected))
+    }
+
     pub fn take(&mut self) -> String {
+        self.completion = None;
         self.cursor.reset();
         if self.index < self.history.len() {
             let output = self.history.remove(self.index);
@@ -240,3 +316,87 @@ impl PromptApp {
         }
     }
 }
+
+/// Byte offset of the first argument of a command, past the command word and
+/// the whitespace around it.
+fn arguments_start(buf: &str) -> usize {
+    let command = buf.trim_start();
+    let arguments = command
+        .trim_start_matches(|c: char| !c.is_whitespace())
+        .trim_start();
+    buf.len() - arguments.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Cursor, Direction, PromptApp, PromptDelta, PromptMovement};
+
+    fn candidates(before: &str) -> (usize, Vec<String>) {
+        let start = before.rfind(' ').map_or(0, |i| i + 1);
+        let names = ["alpha", "beta", "bravo"]
+            .into_iter()
+            .filter(|name| name.starts_with(&before[start..]))
+            .map(String::from)
+            .collect();
+        (start, names)
+    }
+
+    #[test]
+    fn completion_cycles_through_candidates() {
+        let mut prompt = PromptApp::new();
+        prompt.enter_str("open b");
+
+        prompt.complete(Direction::Next, candidates);
+        assert_eq!(prompt.buf(), "open beta");
+        prompt.complete(Direction::Next, candidates);
+        assert_eq!(prompt.buf(), "open bravo");
+        assert_eq!(
+            prompt.completion(),
+            Some((&[String::from("beta"), String::from("bravo")][..], Some(1)))
+        );
+
+        // Past the last candidate is the word as typed, then the first again
+        prompt.complete(Direction::Next, candidates);
+        assert_eq!(prompt.buf(), "open b");
+        prompt.complete(Direction::Back, candidates);
+        assert_eq!(prompt.buf(), "open bravo");
+    }
+
+    #[test]
+    fn completion_resets_on_typing() {
+        let mut prompt = PromptApp::new();
+        prompt.enter_str("open b");
+        prompt.complete(Direction::Next, candidates);
+        prompt.enter_str(" a");
+        assert!(prompt.completion().is_none());
+
+        // A lone candidate is taken without cycling
+        prompt.complete(Direction::Next, candidates);
+        assert_eq!(prompt.buf(), "open beta alpha");
+        assert!(prompt.completion().is_none());
+    }
+
+    #[test]
+    fn smart_home_stops_at_arguments() {
+        let home = PromptMovement::new(false, PromptDelta::SmartBoundary);
+        let at = |prompt: &PromptApp| match prompt.cursor() {
+            Cursor::Singleton(i) => i,
+            Cursor::Selection(..) => unreachable!(),
+        };
+        let mut prompt = PromptApp::new();
+        prompt.enter_str("filter save  errors");
+
+        prompt.move_cursor(Direction::Back, home);
+        assert_eq!(at(&prompt), 7);
+        prompt.move_cursor(Direction::Back, home);
+        assert_eq!(at(&prompt), 0);
+        prompt.move_cursor(Direction::Next, home);
+        assert_eq!(at(&prompt), 19);
+
+        // Without arguments it goes straight to the start
+        let mut prompt = PromptApp::new();
+        prompt.enter_str("quit ");
+        prompt.move_cursor(Direction::Back, home);
+        assert_eq!(at(&prompt), 0);
+    }
+}
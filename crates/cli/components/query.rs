@@ -0,0 +1,217 @@
+use bvr_core::matches::CompositeStrategy;
+
+/// A filter expression such as `'ERROR' AND NOT /health.*check/`, which adds
+/// a filter for every term and combines them all with one strategy.
+///
+/// Terms are quoted literals (`'…'` or `"…"`), regexes between slashes or bare
+/// words taken literally, each optionally preceded by `NOT` (or `!`). Terms are
+/// joined by `AND` (`&&`) or `OR` (`||`), but not both, since filters have no
+/// grouping.
+#[derive(Debug, PartialEq)]
+pub struct Query {
+    pub terms: Vec<Term>,
+    pub strategy: CompositeStrategy,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Term {
+    pub pattern: String,
+    pub literal: bool,
+    pub negated: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Pattern { pattern: String, literal: bool },
+    And,
+    Or,
+    Not,
+}
+
+/// Whether the command reads as an expression rather than a subcommand: it
+/// has an `AND`, `OR` or `NOT` outside of its patterns, or it is a single
+/// quoted or slashed term.
+pub fn is_query(input: &str) -> bool {
+    let input = input.trim_start();
+    // Unclosed patterns are still reported as queries
+    input.starts_with(['\'', '"', '/'])
+        || tokenize(input).is_ok_and(|tokens| {
+            tokens
+                .iter()
+                .any(|token| !matches!(token, Token::Pattern { .. }))
+        })
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start();
+    while let Some(first) = rest.chars().next() {
+        let (token, after) = match first {
+            '\'' | '"' => {
+                let end = rest[1..]
+                    .find(first)
+                    .ok_or_else(|| format!("unclosed {first} in `{rest}`"))?;
+                let pattern = rest[1..1 + end].to_owned();
+                (
+                    Token::Pattern {
+                        pattern,
+                        literal: true,
+                    },
+                    &rest[end + 2..],
+                )
+            }
+            '/' => {
+                // `\/` stands for a slash inside the regex
+                let mut pattern = String::new();
+                let mut chars = rest[1..].char_indices();
+                let end = loop {
+                    match chars.next() {
+                        Some((i, '/')) => break i,
+                        Some((_, '\\')) if chars.as_str().starts_with('/') => {
+                            pattern.push('/');
+                            chars.next();
+                        }
+                        Some((_, c)) => pattern.push(c),
+                        None => return Err(format!("unclosed / in `{rest}`")),
+                    }
+                };
+                (
+                    Token::Pattern {
+                        pattern,
+                        literal: false,
+                    },
+                    &rest[end + 2..],
+                )
+            }
+            _ => {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let token = match &rest[..end] {
+                    "AND" | "&&" => Token::And,
+                    "OR" | "||" => Token::Or,
+                    "NOT" | "!" => Token::Not,
+                    word if word.starts_with('!') => {
+                        tokens.push(Token::Not);
+                        rest = &rest[1..];
+                        continue;
+                    }
+                    word => Token::Pattern {
+                        pattern: word.to_owned(),
+                        literal: true,
+                    },
+                };
+                (token, &rest[end..])
+            }
+        };
+        tokens.push(token);
+        rest = after.trim_start();
+    }
+    Ok(tokens)
+}
+
+pub fn parse(input: &str) -> Result<Query, String> {
+    let mut tokens = tokenize(input)?.into_iter().peekable();
+    let mut terms = Vec::new();
+    let mut strategy = None;
+
+    loop {
+        let mut negated = false;
+        while tokens.next_if_eq(&Token::Not).is_some() {
+            negated = !negated;
+        }
+        match tokens.next() {
+            Some(Token::Pattern { pattern, literal }) => terms.push(Term {
+                pattern,
+                literal,
+                negated,
+            }),
+            Some(_) => return Err(String::from("expected a term before AND/OR")),
+            None if terms.is_empty() => return Err(String::from("expected a term")),
+            None => return Err(String::from("expected a term after AND/OR/NOT")),
+        }
+
+        let next = match tokens.next() {
+            None => break,
+            Some(Token::And) => CompositeStrategy::Intersection,
+            Some(Token::Or) => CompositeStrategy::Union,
+            Some(Token::Pattern { pattern, .. }) => {
+                return Err(format!("expected AND or OR before `{pattern}`"))
+            }
+            Some(Token::Not) => return Err(String::from("expected AND or OR before NOT")),
+        };
+        if strategy.is_some_and(|strategy| strategy != next) {
+            return Err(String::from(
+                "cannot mix AND and OR, filters have no grouping",
+            ));
+        }
+        strategy = Some(next);
+    }
+
+    Ok(Query {
+        terms,
+        strategy: strategy.unwrap_or(CompositeStrategy::Union),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{is_query, parse, CompositeStrategy, Query, Term};
+
+    fn term(pattern: &str, literal: bool, negated: bool) -> Term {
+        Term {
+            pattern: pattern.to_owned(),
+            literal,
+            negated,
+        }
+    }
+
+    #[test]
+    fn parses_terms_and_strategy() {
+        assert_eq!(
+            parse("'ERROR' AND NOT 'healthcheck'"),
+            Ok(Query {
+                terms: vec![term("ERROR", true, false), term("healthcheck", true, true)],
+                strategy: CompositeStrategy::Intersection,
+            })
+        );
+        assert_eq!(
+            parse(r#"/time(out)?s/ || "connection reset" || !warn"#),
+            Ok(Query {
+                terms: vec![
+                    term("time(out)?s", false, false),
+                    term("connection reset", true, false),
+                    term("warn", true, true),
+                ],
+                strategy: CompositeStrategy::Union,
+            })
+        );
+        assert_eq!(
+            parse(r"/a\/b/"),
+            Ok(Query {
+                terms: vec![term("a/b", false, false)],
+                strategy: CompositeStrategy::Union,
+            })
+        );
+    }
+
+    #[test]
+    fn reports_invalid_expressions() {
+        assert!(parse("").is_err());
+        assert!(parse("'a' AND").is_err());
+        assert!(parse("'a' 'b'").is_err());
+        assert!(parse("'a' AND 'b' OR 'c'").is_err());
+        assert!(parse("'unclosed").is_err());
+        assert!(parse("/unclosed").is_err());
+    }
+
+    #[test]
+    fn tells_queries_from_subcommands() {
+        assert!(is_query("'ERROR'"));
+        assert!(is_query("NOT debug"));
+        assert!(is_query("/re/"));
+        assert!(is_query("foo AND bar"));
+        assert!(is_query("foo || !bar"));
+        assert!(!is_query("clear"));
+        assert!(!is_query("regex foo"));
+        assert!(!is_query("foo 'AND' /OR/"));
+    }
+}
@@ -1,22 +1,37 @@
 use std::{
     borrow::Cow,
+    collections::VecDeque,
     ops::Deref,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
 pub struct StatusApp {
     message: String,
     timestamp: Option<(Instant, Duration)>,
+    /// Recent messages and when they were shown, oldest first.
+    history: VecDeque<(SystemTime, String)>,
 }
 
 impl StatusApp {
+    /// Messages kept for `:messages`.
+    const HISTORY_LEN: usize = 100;
+
     pub const fn new() -> Self {
         Self {
             message: String::new(),
             timestamp: None,
+            history: VecDeque::new(),
         }
     }
 
+    pub fn history(&self) -> &VecDeque<(SystemTime, String)> {
+        &self.history
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
     pub fn msg(&mut self, message: String) {
         self.msg_with_duration(message, Some(Duration::from_secs(2)))
     }
@@ -26,12 +41,23 @@ impl StatusApp {
             self.message.clear();
             self.timestamp = None;
         } else {
+            if self.history.len() == Self::HISTORY_LEN {
+                self.history.pop_front();
+            }
+            self.history.push_back((SystemTime::now(), message.clone()));
             self.message = message;
             self.timestamp = duration.map(|dur| (Instant::now(), dur));
         }
     }
 
-    pub fn get_message_update(&mut self) -> Option<Cow<str>> {
+    /// Shows a message that is about to be replaced, such as the progress of
+    /// a task, without keeping it for `:messages`.
+    pub fn progress(&mut self, message: String) {
+        self.message = message;
+        self.timestamp = Some((Instant::now(), Duration::from_secs(2)));
+    }
+
+    pub fn get_message_update(&mut self) -> Option<Cow<'_, str>> {
         if let Some((time, dur)) = self.timestamp {
             if time.elapsed() > dur {
                 self.timestamp = None;
@@ -42,3 +68,24 @@ impl StatusApp {
         (!self.message.is_empty()).then(|| Cow::Borrowed(self.message.deref()))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::StatusApp;
+
+    #[test]
+    fn history_keeps_recent_messages() {
+        let mut status = StatusApp::new();
+        status.msg(String::new());
+        for i in 0..StatusApp::HISTORY_LEN + 5 {
+            status.msg(format!("message {i}"));
+        }
+        let history = status.history();
+        assert_eq!(history.len(), StatusApp::HISTORY_LEN);
+        assert_eq!(history.front().unwrap().1, "message 5");
+        assert_eq!(
+            history.back().unwrap().1,
+            format!("message {}", StatusApp::HISTORY_LEN + 4)
+        );
+    }
+}
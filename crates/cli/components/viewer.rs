@@ -1,6 +1,7 @@
 use super::{filters::Compositor, viewport::Viewport};
 use bvr_core::{LineSet, SegBuffer, SegStr};
 use ratatui::style::Color;
+use regex::bytes::Regex;
 use std::collections::VecDeque;
 
 #[derive(Clone)]
@@ -9,6 +10,8 @@ pub struct CachedLine {
     pub line_number: usize,
     pub data: SegStr,
     pub color: Color,
+    /// Pattern of the filter that colored this line, if it has one.
+    pub matcher: Option<Regex>,
     pub bookmarked: bool,
 }
 
@@ -21,6 +24,9 @@ pub struct ViewCache {
 
     follow_output: bool,
     end_index: usize,
+    /// Line number kept at the top of the view while the composite fills in,
+    /// along with the index it was last placed at.
+    anchor: Option<(usize, usize)>,
 
     need_recoloring: bool,
 }
@@ -35,6 +41,7 @@ impl ViewCache {
             follow_output: false,
             need_recoloring: false,
             end_index: 0,
+            anchor: None,
         }
     }
 
@@ -76,6 +83,7 @@ impl ViewCache {
             line_number,
             data,
             color: Color::Reset,
+            matcher: None,
             bookmarked: false,
         });
     }
@@ -94,6 +102,7 @@ impl ViewCache {
             line_number,
             data,
             color: Color::Reset,
+            matcher: None,
             bookmarked: false,
         });
         true
@@ -109,7 +118,11 @@ impl ViewCache {
                 .jump_vertically_to(self.end_index.saturating_sub(1));
         }
 
+        self.follow_anchor();
         self.curr_viewport.clamp(self.end_index);
+        if let Some((_, top)) = &mut self.anchor {
+            *top = self.curr_viewport.top();
+        }
 
         let (old_top, new_top) = (self.prev_viewport.top(), self.curr_viewport.top());
         let (old_bot, new_bot) = (self.prev_viewport.bottom(), self.curr_viewport.bottom());
@@ -147,6 +160,11 @@ impl ViewCache {
         self.cache.iter()
     }
 
+    /// The lines in view as of the last [Self::cache_view].
+    pub fn cached_view(&self) -> impl Iterator<Item = &CachedLine> {
+        self.cache.iter()
+    }
+
     pub fn color_cache(&mut self, compositor: &Compositor) {
         if self.need_recoloring {
             self.reset_color_cache();
@@ -162,12 +180,12 @@ impl ViewCache {
             .iter_mut()
             .filter(|line| line.color == Color::Reset)
             .for_each(|line| {
-                line.color = filters
+                let filter = filters
                     .iter()
                     .rev()
-                    .find(|filter| filter.has_line(line.line_number))
-                    .map(|filter| filter.color())
-                    .unwrap_or(Color::White);
+                    .find(|filter| filter.has_line(line.line_number));
+                line.color = filter.map(|filter| filter.color()).unwrap_or(Color::White);
+                line.matcher = filter.and_then(|filter| filter.mask().regex());
 
                 line.bookmarked = compositor.filters().bookmarks().has_line(line.line_number);
             });
@@ -184,11 +202,31 @@ impl ViewCache {
         self.cache.clear();
         let old_line_number = self.line_at_view_index(self.curr_viewport.top());
         self.composite = line_set;
-        if let Some(old_line_number) = old_line_number {
-            if let Some(index) = self.composite.find(old_line_number) {
-                self.curr_viewport.top_to(index);
-            }
+        self.anchor = old_line_number.map(|line_number| (line_number, self.curr_viewport.top()));
+        self.follow_anchor();
+    }
+
+    /// Keeps the anchored line at the top of the view as the lines before it
+    /// arrive, instead of letting the view drift as the composite grows.
+    /// Scrolling or the composite completing releases the anchor.
+    fn follow_anchor(&mut self) {
+        let Some((line_number, top)) = self.anchor else {
+            return;
+        };
+        if self.follow_output || self.curr_viewport.top() != top {
+            self.anchor = None;
+            return;
+        }
+
+        let index = self
+            .composite
+            .nearest_backward(line_number.saturating_add(1))
+            .and_then(|line_number| self.composite.find(line_number));
+        if let Some(index) = index {
+            self.curr_viewport.top_to(index);
         }
+        self.anchor =
+            (!self.composite.is_complete()).then_some((line_number, self.curr_viewport.top()));
     }
 
     pub fn is_following_output(&self) -> bool {
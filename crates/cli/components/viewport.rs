@@ -50,12 +50,12 @@ impl Viewport {
 
     #[inline(always)]
     pub fn right(&self) -> usize {
-        self.left + self.width
+        self.left.saturating_add(self.width)
     }
 
     #[inline(always)]
     pub fn bottom(&self) -> usize {
-        self.top + self.height
+        self.top.saturating_add(self.height)
     }
 
     pub fn clamp(&mut self, end_index: usize) {
@@ -71,6 +71,10 @@ impl Viewport {
         self.top = index;
     }
 
+    pub fn left_to(&mut self, column: usize) {
+        self.left = column;
+    }
+
     pub fn jump_vertically_to(&mut self, index: usize) {
         if !(self.top..self.bottom()).contains(&index) {
             // height remains unchanged
@@ -78,8 +82,8 @@ impl Viewport {
                 // bring the top to current
                 self.top = index;
             } else {
-                // bring the bottom to current
-                self.top = index.saturating_sub(self.height).saturating_add(1);
+                // bring the bottom to current, an empty view is both at once
+                self.top = index.saturating_sub(self.height.max(1)).saturating_add(1);
             }
         }
     }
@@ -92,7 +96,7 @@ impl Viewport {
                 self.left = index;
             } else {
                 // bring the right to current
-                self.left = index.saturating_sub(self.width).saturating_add(1);
+                self.left = index.saturating_sub(self.width.max(1)).saturating_add(1);
             }
         }
     }
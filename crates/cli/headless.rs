@@ -0,0 +1,250 @@
+use anyhow::{bail, Result};
+use bvr_core::{index::Delimiter, matches::CompositeStrategy, LineSet, SegBuffer};
+use regex::bytes::Regex;
+use serde::Serialize;
+use std::{
+    borrow::Cow,
+    io::{ErrorKind, Write},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// One matching line, as printed by `--json-matches`.
+#[derive(Serialize)]
+struct MatchRecord<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<Cow<'a, str>>,
+    /// One-based line number.
+    line: usize,
+    /// Byte offset of the start of the line.
+    offset: u64,
+    /// The line without its terminator, base64 encoded if it is not UTF-8.
+    text: Cow<'a, str>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    base64: bool,
+}
+
+/// Prints every line of `files` that matches `regex` as a JSON object per
+/// line, without starting the pager. Standard input is read if there are no
+/// files.
+pub fn print_json_matches(
+    files: &[PathBuf],
+    regex: Regex,
+    max_matches: Option<usize>,
+    lock: bool,
+    delimiter: Delimiter,
+) -> Result<()> {
+    let stdout = std::io::stdout().lock();
+    let mut output = std::io::BufWriter::new(stdout);
+
+    let stdin = [PathBuf::from("-")];
+    let files = if files.is_empty() { &stdin[..] } else { files };
+    let result = files.iter().try_for_each(|path| {
+        let buf = if path == Path::new("-") {
+            SegBuffer::read_stream_delimited(Box::new(std::io::stdin()), delimiter, false)?
+        } else {
+            let file = std::fs::File::open(path)?;
+            let seg_count = NonZeroUsize::new(25).unwrap();
+            SegBuffer::from_file_delimited(file, seg_count, delimiter, false, lock)?
+        };
+        let name = (files.len() > 1).then(|| path.to_string_lossy());
+        write_matches(&buf, name, regex.clone(), max_matches, &mut output)
+    });
+
+    let kind = |err: &anyhow::Error| {
+        err.downcast_ref::<std::io::Error>()
+            .map(std::io::Error::kind)
+            .or_else(|| err.downcast_ref::<serde_json::Error>()?.io_error_kind())
+    };
+    match result.and_then(|_| Ok(output.flush()?)) {
+        // The reader went away, such as `head`, which is not an error
+        Err(err) if kind(&err) == Some(ErrorKind::BrokenPipe) => Ok(()),
+        result => result,
+    }
+}
+
+/// Writes the lines of `files` that match `regex`, or all of them without a
+/// pattern, to `output` (`-` for standard output) once each file is searched
+/// to the end, and reports how many were written on standard error. Standard
+/// input is read if there are no files.
+pub fn write_output(
+    files: &[PathBuf],
+    output: &Path,
+    regex: Option<Regex>,
+    max_matches: Option<usize>,
+    lock: bool,
+    delimiter: Delimiter,
+) -> Result<()> {
+    let stdin = [PathBuf::from("-")];
+    let files = if files.is_empty() { &stdin[..] } else { files };
+
+    // Creating the output truncates it, so it must not be one of the inputs.
+    // An output that does not exist yet cannot be one.
+    if let Ok(output) = output.canonicalize() {
+        if let Some(path) = files
+            .iter()
+            .find(|path| path.canonicalize().is_ok_and(|path| path == output))
+        {
+            bail!("{}: the output is also an input", path.display());
+        }
+    }
+
+    let mut writer: Box<dyn Write> = if output == Path::new("-") {
+        Box::new(std::io::stdout().lock())
+    } else {
+        Box::new(std::fs::File::create(output)?)
+    };
+
+    let mut count = 0;
+    for path in files {
+        let mut buf = if path == Path::new("-") {
+            SegBuffer::read_stream_delimited(Box::new(std::io::stdin()), delimiter, true)?
+        } else {
+            let file = std::fs::File::open(path)?;
+            let seg_count = NonZeroUsize::new(25).unwrap();
+            SegBuffer::from_file_delimited(file, seg_count, delimiter, true, lock)?
+        };
+
+        let filters = regex
+            .iter()
+            .map(|regex| {
+                Ok(LineSet::search_with_limit(
+                    buf.segment_iter()?,
+                    regex.clone(),
+                    max_matches,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for filter in &filters {
+            while !filter.is_complete() {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+        let lines = if filters.is_empty() {
+            buf.all_line_matches()
+        } else {
+            LineSet::compose(filters, true, CompositeStrategy::Union)?
+        };
+
+        buf.write_bytes(&mut writer, &lines)?;
+        count += lines.len();
+    }
+    writer.flush()?;
+
+    let name = if output == Path::new("-") {
+        Cow::Borrowed("standard output")
+    } else {
+        output.to_string_lossy()
+    };
+    eprintln!("{count} lines written to {name}");
+    Ok(())
+}
+
+/// Streams the matches out as the search finds them.
+fn write_matches(
+    buf: &SegBuffer,
+    file: Option<Cow<str>>,
+    regex: Regex,
+    max_matches: Option<usize>,
+    mut output: impl Write,
+) -> Result<()> {
+    let lines = LineSet::search_with_limit(buf.segment_iter()?, regex, max_matches);
+
+    let mut i = 0;
+    loop {
+        // Checked first, so that no match is missed between draining and stopping
+        let complete = lines.is_complete();
+        while let Some(line_number) = lines.get(i) {
+            let (Some(offset), Some(data)) = (
+                buf.index().data_of_line(line_number),
+                buf.get_bytes(line_number),
+            ) else {
+                break;
+            };
+            let data = data.as_bytes();
+            let data = data.strip_suffix(b"\n").unwrap_or(data);
+            let data = data.strip_suffix(b"\r").unwrap_or(data);
+            let (text, base64) = match std::str::from_utf8(data) {
+                Ok(text) => (Cow::Borrowed(text), false),
+                Err(_) => (Cow::Owned(encode_base64(data)), true),
+            };
+
+            serde_json::to_writer(
+                &mut output,
+                &MatchRecord {
+                    file: file.clone(),
+                    line: line_number + 1,
+                    offset,
+                    text,
+                    base64,
+                },
+            )?;
+            output.write_all(b"\n")?;
+            i += 1;
+        }
+        if complete {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn encode_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n =
+            (chunk.iter().enumerate()).fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::{encode_base64, write_output};
+    use bvr_core::index::Delimiter;
+    use std::path::PathBuf;
+
+    #[test]
+    fn write_output_rejects_an_input_as_output() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("bvr_output_{}.log", std::process::id()));
+        std::fs::write(&path, "a\nb\n").unwrap();
+        // The same file, named differently
+        let alias = dir.join(".").join(path.file_name().unwrap());
+
+        let files = [PathBuf::from("-"), path.clone()];
+        let result = write_output(&files, &alias, None, None, false, Delimiter::Newline);
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a\nb\n");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn encode_base64_test_vectors() {
+        // From RFC 4648, section 10, covering no padding, `==` and `=`
+        for (data, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(encode_base64(data.as_bytes()), encoded);
+        }
+        // The last two characters of the alphabet
+        assert_eq!(encode_base64(&[0xfb, 0xff, 0xbf]), "+/+/");
+    }
+}
@@ -0,0 +1,143 @@
+use serde_json::Value;
+use unicode_width::UnicodeWidthStr;
+
+/// Extracts the values at the dotted `paths` (such as `request.id`) from a
+/// line holding a JSON object, or `None` if the line is not one. Missing
+/// fields are rendered as `-`.
+pub fn extract_fields(line: &str, paths: &[String]) -> Option<Vec<String>> {
+    let value = serde_json::from_str::<Value>(line.trim_end()).ok()?;
+    if !value.is_object() {
+        return None;
+    }
+
+    Some(
+        paths
+            .iter()
+            .map(|path| {
+                path.split('.')
+                    .try_fold(&value, |value, key| value.get(key))
+                    .map_or_else(
+                        || String::from("-"),
+                        |value| match value {
+                            Value::String(s) => s.replace(['\n', '\r', '\t'], " "),
+                            value => value.to_string(),
+                        },
+                    )
+            })
+            .collect(),
+    )
+}
+
+/// Pads the fields of every row into columns as wide as the widest value of
+/// that field, counting wide characters as two columns. Rows without fields
+/// are left as `None`.
+pub fn align_columns(rows: Vec<Option<Vec<String>>>) -> Vec<Option<String>> {
+    let mut widths = Vec::new();
+    for fields in rows.iter().flatten() {
+        widths.resize(widths.len().max(fields.len()), 0);
+        for (width, field) in widths.iter_mut().zip(fields) {
+            *width = (*width).max(field.width());
+        }
+    }
+
+    rows.into_iter()
+        .map(|row| {
+            row.map(|fields| {
+                let mut line = String::new();
+                for (i, (field, &width)) in fields.iter().zip(&widths).enumerate() {
+                    line.push_str(field);
+                    if i + 1 < fields.len() {
+                        let padding = width - field.width() + 2;
+                        line.extend(std::iter::repeat_n(' ', padding));
+                    }
+                }
+                line
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|&path| path.to_owned()).collect()
+    }
+
+    #[test]
+    fn extract_fields_of_json_objects() {
+        let line = r#"{"level":"info","msg":"a\tb","req":{"id":7,"tags":["x"]},"ok":true}"#;
+        assert_eq!(
+            extract_fields(
+                &format!("{line}\n"),
+                &paths(&["level", "req.id", "msg", "ok"])
+            ),
+            Some(paths(&["info", "7", "a b", "true"]))
+        );
+        // Values that are not strings are shown as JSON
+        assert_eq!(
+            extract_fields(line, &paths(&["req.tags", "req"])),
+            Some(paths(&[r#"["x"]"#, r#"{"id":7,"tags":["x"]}"#]))
+        );
+    }
+
+    #[test]
+    fn extract_fields_marks_missing_fields() {
+        let line = r#"{"level":"warn","req":{"id":7}}"#;
+        assert_eq!(
+            extract_fields(line, &paths(&["msg", "req.user", "level.name", "level"])),
+            Some(paths(&["-", "-", "-", "warn"]))
+        );
+    }
+
+    #[test]
+    fn extract_fields_skips_other_lines() {
+        for line in [
+            "plain text",
+            "",
+            "[1, 2]",
+            "\"string\"",
+            "42",
+            r#"{"open":"#,
+        ] {
+            assert_eq!(extract_fields(line, &paths(&["level"])), None, "{line}");
+        }
+    }
+
+    #[test]
+    fn align_columns_pads_all_but_the_last_field() {
+        let rows = vec![
+            Some(paths(&["info", "ready", "x"])),
+            None,
+            Some(paths(&["warning", "-", "yz"])),
+            Some(paths(&["é", "done"])),
+        ];
+        assert_eq!(
+            align_columns(rows),
+            [
+                Some(String::from("info     ready  x")),
+                None,
+                Some(String::from("warning  -      yz")),
+                Some(String::from("é        done")),
+            ]
+        );
+    }
+
+    #[test]
+    fn align_columns_counts_wide_characters_as_two_columns() {
+        let rows = vec![
+            Some(paths(&["错误", "a"])),
+            Some(paths(&["info", "b"])),
+            Some(paths(&["ok", "c"])),
+        ];
+        assert_eq!(
+            align_columns(rows),
+            [
+                Some(String::from("错误  a")),
+                Some(String::from("info  b")),
+                Some(String::from("ok    c")),
+            ]
+        );
+    }
+}
@@ -1,43 +1,283 @@
 mod app;
+mod cells;
 mod colors;
 mod components;
 mod direction;
+mod headless;
+mod json;
+mod numbers;
+mod timestamp;
 
-use anyhow::Result;
-use app::App;
+use anyhow::{anyhow, Result};
+use app::{
+    control::{LineRange, MouseModes, StatusLayout},
+    keybinding::KeyAccelerator,
+    App,
+};
+use bvr_core::index::Delimiter;
 use clap::Parser;
-use ratatui::{prelude::CrosstermBackend, Terminal};
-use std::{io::IsTerminal, path::PathBuf};
+use components::mux::MultiplexerApp;
+use numbers::NumberFormat;
+use ratatui::{prelude::CrosstermBackend, style::Color, Terminal};
+use std::{io::IsTerminal, num::NonZeroU64, path::PathBuf, time::Duration};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 struct Args {
-    /// Files to open in the pager
+    /// Files to open in the pager (`-` reads standard input); put them after
+    /// `--` when their names start with `-`
     files: Vec<PathBuf>,
+
+    /// Soft limit on the number of open files before warning
+    #[arg(long, default_value_t = MultiplexerApp::DEFAULT_MAX_INSTANCES)]
+    max_instances: usize,
+
+    /// Take a shared advisory lock on opened files, so that writers that
+    /// honor it cannot change them while they are read. Files locked
+    /// exclusively by another process then fail to open, and on Windows the
+    /// lock keeps growing logs from being written to.
+    #[arg(long)]
+    lock: bool,
+
+    /// Draw in the main screen instead of the alternate screen, leaving the
+    /// last frame in the terminal scrollback on exit
+    #[arg(long)]
+    no_alt_screen: bool,
+
+    /// Start with mouse capture off (`:mcap` turns it on), leaving text
+    /// selection and copying to the terminal
+    #[arg(long)]
+    no_mouse: bool,
+
+    /// Input modes in which the mouse is captured, as `all`, `none` or
+    /// comma-separated modes out of `normal`, `visual`, `filter`, `config`
+    /// and `prompt`; in the others the terminal selects text natively
+    #[arg(long, value_name = "MODES", default_value = "all")]
+    mouse_modes: MouseModes,
+
+    /// Keep at most this many MiB of piped input in memory, moving the rest
+    /// to a temporary file
+    #[arg(long, value_name = "MIB")]
+    stream_mem_limit: Option<u64>,
+
+    /// Segments of 1 MiB to read in ahead of those being shown, so that
+    /// scrolling through large files does not pause at each new segment
+    /// (0 turns it off)
+    #[arg(long, value_name = "SEGMENTS", default_value_t = App::DEFAULT_READAHEAD)]
+    readahead: usize,
+
+    /// Pipe each file through this command, e.g. `jq .`, and show what it
+    /// prints instead of the file
+    #[arg(long, value_name = "COMMAND")]
+    through: Option<String>,
+
+    /// Split the input into records of this many bytes instead of at
+    /// newlines, for fixed-width binary or padded data
+    #[arg(long, value_name = "BYTES")]
+    record_size: Option<NonZeroU64>,
+
+    /// Only show lines X to Y of each file, e.g. `1000:2000`, `1000:` or
+    /// `:2000`, which reads the file only as far as line Y
+    #[arg(long, value_name = "X:Y")]
+    range: Option<LineRange>,
+
+    /// Open files at the first of their last N lines, following new output
+    /// like `tail -f` when they fit on screen
+    #[arg(long, value_name = "N")]
+    tail: Option<usize>,
+
+    /// Stop searching after this many matching lines per filter
+    #[arg(long)]
+    max_matches: Option<usize>,
+
+    /// Most lines moved per repeat while a movement key is held, growing the
+    /// longer it is held (1 disables acceleration)
+    #[arg(long, default_value_t = KeyAccelerator::DEFAULT_MAX_DELTA)]
+    max_scroll_speed: u16,
+
+    /// Pattern to search for when printing matches with `--json-matches` or
+    /// writing them with `--output`
+    #[arg(long, value_name = "PATTERN")]
+    filter: Option<String>,
+
+    /// Write the lines matching `--filter`, or every line without it, to this
+    /// file (`-` for standard output) instead of starting the pager; it cannot
+    /// be one of the files read
+    #[arg(long, value_name = "PATH", conflicts_with = "json_matches")]
+    output: Option<PathBuf>,
+
+    /// Print the lines matching `--filter` as JSON objects, one per line,
+    /// instead of starting the pager
+    #[arg(long, requires = "filter")]
+    json_matches: bool,
+
+    /// Add this many lines to the line numbers of piped input, for when it is
+    /// a slice of a larger file (e.g. `tail -n +1001 file | bvr --line-offset 1000`)
+    #[arg(long, default_value_t = 0)]
+    line_offset: usize,
+
+    /// Status bar segments, left and right of a `|`, out of `mode`, `name`,
+    /// `info`, `filter`, `position`, `percent` and `clock`
+    #[arg(
+        long,
+        value_name = "LAYOUT",
+        default_value = "mode,name,info,filter|position,percent"
+    )]
+    status_bar: StatusLayout,
+
+    /// Separator between groups of thousands in counts and time deltas, out
+    /// of `off`, `comma`, `dot`, `space`, `apostrophe` and `underscore`
+    #[arg(long, value_name = "SEP", default_value = "comma")]
+    thousands: NumberFormat,
+
+    /// Highlight copied lines for this many milliseconds, 0 to not highlight
+    /// them (off when `NO_COLOR` is set)
+    #[arg(long, value_name = "MS", default_value_t = App::DEFAULT_FLASH_MS)]
+    flash: u64,
+
+    /// Background of the highlight on copied lines, as a color name, index
+    /// or `#rrggbb`
+    #[arg(long, value_name = "COLOR")]
+    flash_color: Option<Color>,
+
+    /// For screen readers and other assistive setups: no bracketed paste or
+    /// mouse capture, no colors (bookmarked lines are marked `*` and matched
+    /// ones `+`), no highlight on copied lines and fewer redraws
+    #[arg(long)]
+    accessible: bool,
+
+    /// Allow `:debug` commands, which show internals of the pager for tuning
+    /// its performance
+    #[arg(long, hide = true)]
+    debug: bool,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
+    let delimiter = args
+        .record_size
+        .map_or(Delimiter::Newline, Delimiter::Fixed);
+
+    if args.json_matches {
+        let pattern = args.filter.as_deref().unwrap_or_default();
+        return headless::print_json_matches(
+            &args.files,
+            regex_compile(pattern)?,
+            args.max_matches,
+            args.lock,
+            delimiter,
+        );
+    }
+
+    if let Some(output) = &args.output {
+        return headless::write_output(
+            &args.files,
+            output,
+            args.filter.as_deref().map(regex_compile).transpose()?,
+            args.max_matches,
+            args.lock,
+            delimiter,
+        );
+    }
 
     let stdout = std::io::stdout().lock();
     let backend = CrosstermBackend::new(stdout);
     let terminal = Terminal::new(backend)?;
 
     let mut app = App::new(terminal);
+    app.set_max_instances(args.max_instances);
+    app.set_lock_files(args.lock);
+    app.set_alt_screen(!args.no_alt_screen);
+    app.set_mouse_capture(!args.no_mouse);
+    app.set_mouse_modes(args.mouse_modes);
+    app.set_max_matches(args.max_matches);
+    app.set_delimiter(delimiter);
+    app.set_line_range(args.range.map(|LineRange(lines)| lines));
+    app.set_tail(args.tail);
+    app.set_stream_memory_limit(args.stream_mem_limit.map(|mib| mib.saturating_mul(1 << 20)));
+    app.set_readahead(args.readahead);
+    app.set_through(args.through);
+    app.set_line_offset(args.line_offset);
+    app.set_max_scroll_delta(args.max_scroll_speed);
+    app.set_status_layout(args.status_bar);
+    app.set_number_format(args.thousands);
+    // The flash is nothing but color, so NO_COLOR turns it off
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let flash = if no_color { 0 } else { args.flash };
+    app.set_flash(Duration::from_millis(flash), args.flash_color);
+    app.set_accessible(args.accessible);
+    app.set_debug(args.debug);
 
+    let explicit_stdin = args.files.iter().any(|path| path.as_os_str() == "-");
     for path in args.files {
-        app.open_file(&path)?;
+        app.open_file(&path)
+            .map_err(|err| anyhow!("{}: {err}", path.display()))?;
     }
 
-    if !std::io::stdin().is_terminal() {
+    if !explicit_stdin && !std::io::stdin().is_terminal() {
         app.open_stream(String::from("Pipe Stream"), Box::new(std::io::stdin()))?;
     }
 
     app.run_app()
 }
 
+/// Patterns ignore case unless they contain an uppercase letter (smart
+/// case). Inline flags such as `(?i)` or `(?-i)` take precedence either way.
 fn regex_compile(pattern: &str) -> std::result::Result<regex::bytes::Regex, regex::Error> {
     regex::bytes::RegexBuilder::new(pattern)
-        .case_insensitive(true)
+        .case_insensitive(!has_uppercase(pattern))
         .build()
 }
+
+/// Whether the pattern matches an uppercase letter, leaving out escapes such
+/// as `\W` and `\p{Lu}`, flags such as `(?U)` and group names.
+fn has_uppercase(pattern: &str) -> bool {
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('p' | 'P') if chars.peek() == Some(&'{') => {
+                    chars.find(|&c| c == '}');
+                }
+                Some('p' | 'P') => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '(' if chars.peek() == Some(&'?') => {
+                let end = if chars.clone().nth(1).is_some_and(|c| c == 'P' || c == '<') {
+                    '>'
+                } else {
+                    ')'
+                };
+                while chars.next_if(|&c| c != end && c != ':').is_some() {}
+            }
+            c if c.is_uppercase() => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::{has_uppercase, regex_compile};
+
+    #[test]
+    fn smart_case_leaves_out_escapes_and_flags() {
+        assert!(!has_uppercase(r"error\W+\p{Lu}\pL"));
+        assert!(!has_uppercase(r"(?U)a+(?P<Name>b)(?<Id>c)(?sU:d)"));
+        assert!(has_uppercase(r"(?U)Error"));
+        assert!(has_uppercase(r"\dERROR"));
+    }
+
+    #[test]
+    fn inline_flags_override_smart_case() {
+        let matches =
+            |pattern: &str, text: &str| regex_compile(pattern).unwrap().is_match(text.as_bytes());
+        assert!(matches("error", "ERROR"));
+        assert!(!matches("Error", "ERROR"));
+        assert!(matches("(?i)Error", "ERROR"));
+        assert!(!matches("(?-i)error", "ERROR"));
+    }
+}
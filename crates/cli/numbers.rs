@@ -0,0 +1,135 @@
+use std::str::FromStr;
+
+/// How numbers are laid out for display, such as `5,000,000 lines`.
+///
+/// The decimal mark is the one that does not clash with the separator, so
+/// grouping with `.` writes fractions as `1.234,56`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    separator: Option<char>,
+}
+
+impl NumberFormat {
+    /// Names of the separators, as accepted by `--thousands` and `:set`.
+    pub const NAMES: &'static [&'static str] =
+        &["off", "comma", "dot", "space", "apostrophe", "underscore"];
+
+    pub fn name(self) -> &'static str {
+        match self.separator {
+            None => "off",
+            Some(',') => "comma",
+            Some('.') => "dot",
+            Some(' ') => "space",
+            Some('\'') => "apostrophe",
+            Some(_) => "underscore",
+        }
+    }
+
+    fn decimal_mark(self) -> char {
+        if self.separator == Some('.') {
+            ','
+        } else {
+            '.'
+        }
+    }
+
+    /// Formats an integer with its digits grouped in threes.
+    pub fn int(self, n: usize) -> String {
+        self.localize(&n.to_string())
+    }
+
+    /// Groups the integer digits and swaps the decimal mark of a number that
+    /// was formatted by Rust, keeping any sign and suffix as they are.
+    pub fn localize(self, number: &str) -> String {
+        let digits_start = number
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(number.len());
+        let digits_end = number[digits_start..]
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or(number.len(), |end| digits_start + end);
+        let (sign, digits, rest) = (
+            &number[..digits_start],
+            &number[digits_start..digits_end],
+            &number[digits_end..],
+        );
+
+        let mut output = String::with_capacity(number.len() + digits.len() / 3);
+        output.push_str(sign);
+        for (i, digit) in digits.chars().enumerate() {
+            if let Some(separator) = self.separator {
+                if i > 0 && (digits.len() - i) % 3 == 0 {
+                    output.push(separator);
+                }
+            }
+            output.push(digit);
+        }
+        match rest.strip_prefix('.') {
+            Some(fraction) => {
+                output.push(self.decimal_mark());
+                output.push_str(fraction);
+            }
+            None => output.push_str(rest),
+        }
+        output
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            separator: Some(','),
+        }
+    }
+}
+
+impl FromStr for NumberFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let separator = match s {
+            "off" | "none" => None,
+            "comma" => Some(','),
+            "dot" => Some('.'),
+            "space" => Some(' '),
+            "apostrophe" => Some('\''),
+            "underscore" => Some('_'),
+            _ => return Err(format!("expected one of `{}`", Self::NAMES.join("`, `"))),
+        };
+        Ok(Self { separator })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NumberFormat;
+
+    #[test]
+    fn groups_thousands() {
+        let comma = NumberFormat::default();
+        assert_eq!(comma.int(0), "0");
+        assert_eq!(comma.int(999), "999");
+        assert_eq!(comma.int(1000), "1,000");
+        assert_eq!(comma.int(5_000_000), "5,000,000");
+
+        let off: NumberFormat = "off".parse().unwrap();
+        assert_eq!(off.int(5_000_000), "5000000");
+    }
+
+    #[test]
+    fn keeps_sign_and_suffix() {
+        let comma = NumberFormat::default();
+        assert_eq!(comma.localize("+12345s"), "+12,345s");
+        assert_eq!(comma.localize("-0.42s"), "-0.42s");
+
+        let dot: NumberFormat = "dot".parse().unwrap();
+        assert_eq!(dot.localize("+1234.50s"), "+1.234,50s");
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for name in NumberFormat::NAMES {
+            assert_eq!(name.parse::<NumberFormat>().unwrap().name(), *name);
+        }
+        assert!("tab".parse::<NumberFormat>().is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::numbers::NumberFormat;
+
+/// Parses the timestamp at the start of a line into seconds.
+///
+/// Recognizes `YYYY-MM-DD HH:MM:SS[.fff]` (separated by a space or `T`) and a
+/// bare `HH:MM:SS[.fff]`, optionally wrapped in `[`. Dates are converted to days
+/// since the Unix epoch so that differences across midnight remain correct.
+pub fn parse_leading(line: &str) -> Option<f64> {
+    let mut scanner = Scanner {
+        data: line.trim_start().as_bytes(),
+        pos: 0,
+    };
+    scanner.eat(b'[');
+
+    let start = scanner.pos;
+    let days = match scanner.date() {
+        Some(days) if scanner.eat(b' ') || scanner.eat(b'T') => days,
+        Some(_) => return None,
+        None => {
+            scanner.pos = start;
+            0
+        }
+    };
+
+    let hours = scanner.digits(2)?;
+    scanner.eat(b':').then_some(())?;
+    let minutes = scanner.digits(2)?;
+    scanner.eat(b':').then_some(())?;
+    let seconds = scanner.digits(2)?;
+    if hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+
+    let fraction = if scanner.eat(b'.') || scanner.eat(b',') {
+        scanner.fraction()
+    } else {
+        0.0
+    };
+
+    Some(days as f64 * 86400.0 + f64::from(hours * 3600 + minutes * 60 + seconds) + fraction)
+}
+
+/// Formats a difference between timestamps for display, such as `+0.42s`.
+pub fn format_delta(delta: f64, numbers: NumberFormat) -> String {
+    if delta.abs() < 100.0 {
+        numbers.localize(&format!("{delta:+.2}s"))
+    } else {
+        numbers.localize(&format!("{delta:+.0}s"))
+    }
+}
+
+struct Scanner<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.data.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn digits(&mut self, count: usize) -> Option<u32> {
+        let digits = self.data.get(self.pos..self.pos + count)?;
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        self.pos += count;
+        Some(
+            digits
+                .iter()
+                .fold(0, |acc, &d| acc * 10 + u32::from(d - b'0')),
+        )
+    }
+
+    fn fraction(&mut self) -> f64 {
+        let mut scale = 0.1;
+        let mut value = 0.0;
+        while let Some(d) = self.data.get(self.pos).filter(|d| d.is_ascii_digit()) {
+            value += f64::from(d - b'0') * scale;
+            scale /= 10.0;
+            self.pos += 1;
+        }
+        value
+    }
+
+    /// Parses `YYYY-MM-DD` into days since the Unix epoch.
+    fn date(&mut self) -> Option<i64> {
+        let year = self.digits(4)?;
+        self.eat(b'-').then_some(())?;
+        let month = self.digits(2)?;
+        self.eat(b'-').then_some(())?;
+        let day = self.digits(2)?;
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        Some(days_from_civil(i64::from(year), month, day))
+    }
+}
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
@@ -1,10 +1,10 @@
-use bvr_core::LineIndex;
+use bvr_core::{index::Delimiter, LineIndex};
 
 fn main() {
     let file = std::fs::File::open("./tests/test_5000000.log").unwrap();
 
     let start = std::time::Instant::now();
-    let index = LineIndex::read_file(file, true).unwrap();
+    let index = LineIndex::read_file(file, Delimiter::Newline, true).unwrap();
     dbg!(index.line_count());
 
     let elapsed = start.elapsed();
@@ -4,35 +4,263 @@
 pub mod segment;
 
 use self::segment::{SegBytes, SegStr, Segment};
-use crate::{index::BoxedStream, LineIndex, LineSet, Result};
+use crate::{
+    err::Error,
+    index::{BoxedStream, Delimiter},
+    LineIndex, LineSet, Result,
+};
 use lru::LruCache;
-use std::cell::RefCell;
-use std::fs::File;
-use std::io::{BufWriter, Cursor, Read, Seek, Write};
+use std::cell::{Cell, RefCell};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, Write};
 use std::num::NonZeroUsize;
 use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{Receiver, TryRecvError};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 /// A segmented buffer that holds data in multiple segments.
 ///
 /// The `Buffer` struct represents a buffer that is divided into multiple segments.
 /// It contains the [LineIndex] and the internal representation of the segments.
+///
+/// # Threads
+///
+/// Unless `complete` is set, the constructors return right away and index in
+/// a background thread, which stops once it reaches the end of the data or
+/// once every handle to the [LineIndex] has been dropped. With `complete` set,
+/// indexing runs to the end before the constructor returns, and no thread
+/// that it started is still running by then. [SegBuffer::read_file_complete]
+/// does this for a path.
+///
+/// Reading lines never starts a thread, but searches such as
+/// [LineSet::search] run in their own thread until they finish or the
+/// [LineSet] is dropped.
 pub struct SegBuffer {
     /// The [LineIndex] of this buffer.
     index: LineIndex,
     /// The internal representation of this buffer.
     map: BufferMap,
+    /// Segment cache shared by the iterators of every search over this buffer.
+    search_segments: SharedSegments,
 }
 
 struct BufferMap {
     repr: BufferRepr,
     segment_size: u64,
+    /// Segments asked for, see [BufferStats::fetches].
+    fetches: Cell<usize>,
+    /// Segments mapped from the file, see [BufferStats::maps].
+    maps: Cell<usize>,
+}
+
+/// How a [SegBuffer] has been read so far, for diagnosing slow reads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BufferStats {
+    /// Segments asked for by reads of lines, cached or not.
+    pub fetches: usize,
+    /// Segments that had to be mapped from the file because they were not
+    /// in the cache, counting those mapped ahead. Always zero for streams.
+    pub maps: usize,
+    /// Segments held by the cache.
+    pub cached: usize,
+    /// Segments that the cache can hold, or `None` for streams, which hold
+    /// every segment that is not spilled to disk.
+    pub capacity: Option<usize>,
 }
 
 struct StreamInner {
     pending_segs: Option<Receiver<Segment>>,
+    /// Segments held in memory, following the ones moved to the spill file.
     segments: Vec<Arc<Segment>>,
+    spill: Option<Spill>,
+}
+
+impl StreamInner {
+    /// Move the segments that the indexer has sent so far into `segments`.
+    fn receive_pending(&mut self) {
+        let Some(rx) = &self.pending_segs else {
+            return;
+        };
+        loop {
+            match rx.try_recv() {
+                Ok(segment) => {
+                    #[cfg(debug_assertions)]
+                    if let Some(first_segment) = self.segments.first() {
+                        debug_assert_eq!(first_segment.len(), segment.len())
+                    }
+                    self.segments.push(Arc::new(segment))
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.pending_segs = None;
+                    break;
+                }
+            }
+        }
+        self.spill_excess();
+    }
+
+    /// Move the oldest segments to the spill file until no more than its
+    /// budget is held in memory. The newest segment always stays, since it
+    /// may not be full yet.
+    fn spill_excess(&mut self) {
+        let Some(spill) = &mut self.spill else {
+            return;
+        };
+        let excess = self.segments.len().saturating_sub(spill.max_resident);
+        let mut spilled = 0;
+        for segment in &self.segments[..excess] {
+            if spill.push(segment).is_err() {
+                // Keep the data in memory rather than losing it
+                spill.max_resident = usize::MAX;
+                break;
+            }
+            spilled += 1;
+        }
+        self.segments.drain(..spilled);
+    }
+
+    fn spilled_count(&self) -> usize {
+        self.spill.as_ref().map_or(0, |spill| spill.len)
+    }
+
+    fn get(&mut self, seg_id: usize) -> Option<Arc<Segment>> {
+        let spilled = self.spilled_count();
+        if seg_id < spilled {
+            self.spill.as_mut()?.get(seg_id)
+        } else {
+            self.segments.get(seg_id - spilled).cloned()
+        }
+    }
+
+    /// Write the first `len` bytes of the received data, leaving out the
+    /// unused space at the end of the last segment.
+    fn write_data(&mut self, len: u64, output: &mut impl Write) -> std::io::Result<()> {
+        let mut remaining = len;
+        if let Some(spill) = &mut self.spill {
+            let spilled_len = (spill.len as u64 * spill.segment_size).min(remaining);
+            spill.file.seek(std::io::SeekFrom::Start(0))?;
+            std::io::copy(&mut (&spill.file).take(spilled_len), output)?;
+            remaining -= spilled_len;
+        }
+        for seg in &self.segments {
+            if remaining == 0 {
+                break;
+            }
+            let take = remaining.min(seg.len() as u64);
+            remaining -= take;
+            output.write_all(&seg[..take as usize])?;
+        }
+        Ok(())
+    }
+}
+
+/// Temporary file that the oldest segments of a stream are moved to once
+/// more than `max_resident` of them are held in memory, so that they are
+/// paged in from the file on demand like a file-backed buffer.
+struct Spill {
+    file: File,
+    /// Removed on drop by the buffer that created the file.
+    path: Option<PathBuf>,
+    segment_size: u64,
+    max_resident: usize,
+    /// How many of the leading segments have been written to the file.
+    len: usize,
+    segments: LruCache<usize, Arc<Segment>>,
+}
+
+impl Spill {
+    const SEG_COUNT: NonZeroUsize = NonZeroUsize::new(25).unwrap();
+
+    fn create(segment_size: u64, max_resident: usize) -> Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "bvr_spill_{}_{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        // On Unix the file lives on without a name until it is closed
+        let path = std::fs::remove_file(&path).is_err().then_some(path);
+
+        Ok(Self {
+            file,
+            path,
+            segment_size,
+            max_resident: max_resident.max(1),
+            len: 0,
+            segments: LruCache::new(Self::SEG_COUNT),
+        })
+    }
+
+    fn try_clone_detached(&self) -> Result<Self> {
+        Ok(Self {
+            file: self.file.try_clone()?,
+            path: None,
+            segment_size: self.segment_size,
+            max_resident: usize::MAX,
+            len: self.len,
+            segments: LruCache::new(Self::SEG_COUNT),
+        })
+    }
+
+    fn push(&mut self, segment: &Segment) -> std::io::Result<()> {
+        // A failed write may leave a partial segment behind, so each segment
+        // is written at its own offset
+        let start = self.len as u64 * self.segment_size;
+        self.file.seek(std::io::SeekFrom::Start(start))?;
+        self.file.write_all(segment)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn get(&mut self, seg_id: usize) -> Option<Arc<Segment>> {
+        let start = seg_id as u64 * self.segment_size;
+        let range = start..start + self.segment_size;
+        self.segments
+            .try_get_or_insert(seg_id, || {
+                Segment::map_file(range, &self.file).map(Arc::new)
+            })
+            .ok()
+            .cloned()
+    }
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Segment cache that can be shared between threads, so that concurrent
+/// searches over the same buffer map and fetch each segment once, rather than
+/// once per search through a cache of their own.
+#[derive(Clone)]
+struct SharedSegments(Arc<Mutex<LruCache<usize, Arc<Segment>>>>);
+
+impl SharedSegments {
+    const SEG_COUNT: NonZeroUsize = NonZeroUsize::new(25).unwrap();
+
+    fn new() -> Self {
+        Self(Arc::new(Mutex::new(LruCache::new(Self::SEG_COUNT))))
+    }
+
+    fn fetch(&self, map: &BufferMap, seg_id: usize) -> Option<Arc<Segment>> {
+        // A search that panicked while holding the lock left the cache intact
+        let mut segments = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        segments
+            .try_get_or_insert(seg_id, || map.fetch(seg_id).ok_or(()))
+            .ok()
+            .cloned()
+    }
 }
 
 /// Internal representation of the segmented buffer, which allows for working
@@ -44,6 +272,11 @@ enum BufferRepr {
         file: File,
         len: u64,
         segments: RefCell<LruCache<usize, Arc<Segment>>>,
+        /// Segments mapped ahead of a newly mapped one, see
+        /// [SegBuffer::set_readahead].
+        readahead: usize,
+        /// Last segment fetched, which tells the direction of reading.
+        last_fetch: Cell<usize>,
     },
     /// Data is all present in memory in multiple anonymous mmaps.
     Stream(RefCell<StreamInner>),
@@ -52,67 +285,231 @@ enum BufferRepr {
 impl BufferMap {
     #[inline]
     pub fn id_of_data(&self, start: u64) -> usize {
-        (start / self.segment_size) as usize
+        // On 32-bit targets the segment id may not fit, saturate instead of
+        // truncating so that the fetch fails rather than aliasing a segment.
+        usize::try_from(start / self.segment_size).unwrap_or(usize::MAX)
     }
 
     #[inline]
     pub fn data_range_of_id(&self, id: usize) -> Range<u64> {
-        let start = id as u64 * self.segment_size;
-        start..start + self.segment_size
+        let start = (id as u64).saturating_mul(self.segment_size);
+        start..start.saturating_add(self.segment_size)
     }
 
     fn fetch(&self, seg_id: usize) -> Option<Arc<Segment>> {
+        self.fetches.set(self.fetches.get() + 1);
         match &self.repr {
             BufferRepr::File {
                 file,
                 len,
                 segments,
+                readahead,
+                last_fetch,
             } => {
+                let mut segments = segments.borrow_mut();
+                let mapped = segments.contains(&seg_id);
                 let range = self.data_range_of_id(seg_id);
                 let range = range.start..range.end.min(*len);
-                Some(
-                    segments
-                        .borrow_mut()
-                        .get_or_insert(seg_id, || {
-                            Arc::new(Segment::map_file(range, file).expect("mmap was successful"))
-                        })
-                        .clone(),
-                )
-            }
-            BufferRepr::Stream(inner) => {
-                let StreamInner {
-                    pending_segs,
-                    segments,
-                } = &mut *inner.borrow_mut();
-                if let Some(rx) = pending_segs {
-                    loop {
-                        match rx.try_recv() {
-                            Ok(segment) => {
-                                #[cfg(debug_assertions)]
-                                if let Some(first_segment) = segments.first() {
-                                    debug_assert_eq!(first_segment.len(), segment.len())
-                                }
-                                segments.push(Arc::new(segment))
-                            }
-                            Err(TryRecvError::Empty) => break,
-                            Err(TryRecvError::Disconnected) => {
-                                *pending_segs = None;
-                                break;
+                let segment = segments
+                    .try_get_or_insert(seg_id, || Segment::map_file(range, file).map(Arc::new))
+                    .ok()
+                    .cloned()?;
+                if !mapped {
+                    self.maps.set(self.maps.get() + 1);
+                }
+
+                let backwards = seg_id < last_fetch.replace(seg_id);
+                // Prefetching more than half the cache would evict the
+                // segments being read to make room for the next ones
+                let readahead = (*readahead).min((segments.cap().get() - 1) / 2);
+                if !mapped && readahead > 0 {
+                    for distance in 1..=readahead {
+                        let neighbor = if backwards {
+                            seg_id.checked_sub(distance)
+                        } else {
+                            seg_id.checked_add(distance)
+                        };
+                        let Some(neighbor) = neighbor else { break };
+                        let range = self.data_range_of_id(neighbor);
+                        if range.start >= *len {
+                            break;
+                        }
+                        if segments.contains(&neighbor) {
+                            continue;
+                        }
+                        // Mapped with `WillNeed`, so the kernel reads it in
+                        // the background while this segment is being read
+                        let range = range.start..range.end.min(*len);
+                        match Segment::map_file(range, file) {
+                            Ok(ahead) => {
+                                segments.put(neighbor, Arc::new(ahead));
+                                self.maps.set(self.maps.get() + 1);
                             }
+                            Err(_) => break,
                         }
                     }
+                    segments.promote(&seg_id);
                 }
-                segments.get(seg_id).cloned()
+                Some(segment)
+            }
+            BufferRepr::Stream(inner) => {
+                let mut inner = inner.borrow_mut();
+                inner.receive_pending();
+                inner.get(seg_id)
             }
         }
     }
+
+    /// Create an independent map over the same data with its own segment
+    /// cache, so that it can be read without touching the original's cache.
+    fn try_clone_detached(&self, seg_count: NonZeroUsize) -> Result<Self> {
+        let repr = match &self.repr {
+            BufferRepr::File {
+                file,
+                len,
+                readahead,
+                ..
+            } => BufferRepr::File {
+                file: file.try_clone()?,
+                len: *len,
+                segments: RefCell::new(LruCache::new(seg_count)),
+                readahead: *readahead,
+                last_fetch: Cell::new(0),
+            },
+            BufferRepr::Stream(inner) => {
+                let mut inner = inner.borrow_mut();
+                inner.receive_pending();
+                BufferRepr::Stream(RefCell::new(StreamInner {
+                    pending_segs: None,
+                    segments: inner.segments.clone(),
+                    spill: inner
+                        .spill
+                        .as_ref()
+                        .map(Spill::try_clone_detached)
+                        .transpose()?,
+                }))
+            }
+        };
+        Ok(Self {
+            repr,
+            segment_size: self.segment_size,
+            fetches: Cell::new(0),
+            maps: Cell::new(0),
+        })
+    }
+
+    fn get_bytes(&self, index: &LineIndex, line_number: usize) -> Option<SegBytes> {
+        assert!(line_number <= index.line_count());
+
+        let data_start = index.data_of_line(line_number)?;
+        let data_end = index.data_of_line(line_number.checked_add(1)?)?;
+        let seg_start = self.id_of_data(data_start);
+        let seg_end = self.id_of_data(data_end);
+
+        if data_start == data_end {
+            // Empty files cannot be mapped, and an empty line needs no data
+            Some(SegBytes::new_owned(Vec::new()))
+        } else if seg_start == seg_end {
+            // The data is in a single segment
+            let seg = self.fetch(seg_start)?;
+            let range = seg.translate_inner_data_range(data_start, data_end);
+            Some(seg.get_bytes(range))
+        } else {
+            debug_assert!(seg_start < seg_end);
+            // The data may cross several segments, so we must piece together
+            // the data from across the segments.
+            let mut buf = Vec::with_capacity((data_end - data_start) as usize);
+
+            let seg_first = self.fetch(seg_start)?;
+            let seg_last = self.fetch(seg_end)?;
+            let (start, end) = (
+                seg_first.translate_inner_data_index(data_start),
+                seg_last.translate_inner_data_index(data_end),
+            );
+            buf.extend_from_slice(&seg_first[start as usize..]);
+            for seg_id in seg_start + 1..seg_end {
+                buf.extend_from_slice(&self.fetch(seg_id)?);
+            }
+            buf.extend_from_slice(&seg_last[..end as usize]);
+
+            Some(SegBytes::new_owned(buf))
+        }
+    }
 }
 
 impl SegBuffer {
-    const SEGMENT_SIZE: u64 = 1 << 20;
+    pub(crate) const SEGMENT_SIZE: u64 = 1 << 20;
+    /// Segments kept mapped by [SegBuffer::read_file_complete].
+    const COMPLETE_SEG_COUNT: usize = 25;
 
-    pub fn read_file(file: File, seg_count: NonZeroUsize, complete: bool) -> Result<Self> {
-        let index = LineIndex::read_file(file.try_clone()?, complete)?;
+    /// Create a [SegBuffer] backed by a file.
+    ///
+    /// If `complete` is set, the whole file is indexed before this returns,
+    /// see the [threads](SegBuffer#threads) of a buffer.
+    ///
+    /// If `lock` is set, a shared advisory lock is held on the file for the
+    /// lifetime of the buffer, and this fails if another process holds an
+    /// exclusive lock. Without the lock, a concurrent writer may cause the
+    /// memory maps to observe torn or truncated data.
+    pub fn read_file(
+        file: File,
+        seg_count: NonZeroUsize,
+        complete: bool,
+        lock: bool,
+    ) -> Result<Self> {
+        Self::read_file_delimited(file, seg_count, Delimiter::Newline, complete, lock)
+    }
+
+    /// Open the file at `path` and index it in full before returning, without
+    /// locking it. Every line can be read right away, and no background thread
+    /// is left running, so this suits embedding in synchronous code.
+    ///
+    /// ```no_run
+    /// # fn main() -> bvr_core::Result<()> {
+    /// let buffer = bvr_core::SegBuffer::read_file_complete("server.log")?;
+    /// for line in buffer.lines() {
+    ///     println!("{}", line.as_str());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_file_complete(path: impl AsRef<Path>) -> Result<Self> {
+        Self::read_file(
+            File::open(path)?,
+            NonZeroUsize::new(Self::COMPLETE_SEG_COUNT).unwrap(),
+            true,
+            false,
+        )
+    }
+
+    /// Create a [SegBuffer] backed by a file, split into lines by `delimiter`.
+    /// See [SegBuffer::read_file].
+    pub fn read_file_delimited(
+        file: File,
+        seg_count: NonZeroUsize,
+        delimiter: Delimiter,
+        complete: bool,
+        lock: bool,
+    ) -> Result<Self> {
+        Self::read_file_lines(file, seg_count, delimiter, 0..usize::MAX, complete, lock)
+    }
+
+    /// Create a [SegBuffer] of only the zero-based `lines` of a file, which is
+    /// indexed only as far as the end of the range. Line numbers of the buffer
+    /// start from zero at the start of the range. See [SegBuffer::read_file].
+    pub fn read_file_lines(
+        file: File,
+        seg_count: NonZeroUsize,
+        delimiter: Delimiter,
+        lines: Range<usize>,
+        complete: bool,
+        lock: bool,
+    ) -> Result<Self> {
+        if lock {
+            file.try_lock_shared()
+                .map_err(|err| Error::Lock(err.into()))?;
+        }
+        let index = LineIndex::read_file_lines(file.try_clone()?, delimiter, lines, complete)?;
 
         Ok(Self {
             index,
@@ -121,15 +518,56 @@ impl SegBuffer {
                     len: file.metadata()?.len(),
                     file,
                     segments: RefCell::new(LruCache::new(seg_count)),
+                    readahead: 0,
+                    last_fetch: Cell::new(0),
                 },
                 segment_size: Self::SEGMENT_SIZE,
+                fetches: Cell::new(0),
+                maps: Cell::new(0),
             },
+            search_segments: SharedSegments::new(),
         })
     }
 
+    /// Create a [SegBuffer] from an already opened file, such as an inherited
+    /// descriptor. Anything other than a regular file (pipes, terminals,
+    /// sockets) cannot be memory mapped, and is read as a stream instead.
+    pub fn from_file(
+        file: File,
+        seg_count: NonZeroUsize,
+        complete: bool,
+        lock: bool,
+    ) -> Result<Self> {
+        Self::from_file_delimited(file, seg_count, Delimiter::Newline, complete, lock)
+    }
+
+    /// Like [SegBuffer::from_file], split into lines by `delimiter`.
+    pub fn from_file_delimited(
+        file: File,
+        seg_count: NonZeroUsize,
+        delimiter: Delimiter,
+        complete: bool,
+        lock: bool,
+    ) -> Result<Self> {
+        if file.metadata()?.is_file() {
+            Self::read_file_delimited(file, seg_count, delimiter, complete, lock)
+        } else {
+            Self::read_stream_delimited(Box::new(file), delimiter, complete)
+        }
+    }
+
     pub fn read_stream(stream: BoxedStream, complete: bool) -> Result<Self> {
+        Self::read_stream_delimited(stream, Delimiter::Newline, complete)
+    }
+
+    /// Create a [SegBuffer] from a stream, split into lines by `delimiter`.
+    pub fn read_stream_delimited(
+        stream: BoxedStream,
+        delimiter: Delimiter,
+        complete: bool,
+    ) -> Result<Self> {
         let (sx, rx) = std::sync::mpsc::channel();
-        let index = LineIndex::read_stream(stream, sx, complete, Self::SEGMENT_SIZE)?;
+        let index = LineIndex::read_stream(stream, sx, complete, Self::SEGMENT_SIZE, delimiter)?;
 
         Ok(Self {
             index,
@@ -137,9 +575,88 @@ impl SegBuffer {
                 repr: BufferRepr::Stream(RefCell::new(StreamInner {
                     pending_segs: Some(rx),
                     segments: Vec::new(),
+                    spill: None,
                 })),
                 segment_size: Self::SEGMENT_SIZE,
+                fetches: Cell::new(0),
+                maps: Cell::new(0),
             },
+            search_segments: SharedSegments::new(),
+        })
+    }
+
+    /// Hold at most about `limit` bytes of a stream in memory, moving the
+    /// oldest data to a temporary file that is read back on demand once the
+    /// limit is exceeded. Data is moved as it is received from the indexer,
+    /// which happens whenever lines are read from this buffer.
+    ///
+    /// Does nothing for file-backed buffers.
+    pub fn limit_stream_memory(&mut self, limit: u64) -> Result<()> {
+        if let BufferRepr::Stream(inner) = &self.map.repr {
+            let max_resident = usize::try_from(limit / self.map.segment_size).unwrap_or(usize::MAX);
+            let mut inner = inner.borrow_mut();
+            if inner.spill.is_none() {
+                inner.spill = Some(Spill::create(self.map.segment_size, max_resident)?);
+            }
+            inner.spill_excess();
+        }
+        Ok(())
+    }
+
+    /// Map up to `segments` segments past one that is read for the first
+    /// time, or before it when reading backwards, so that the kernel reads
+    /// them in while it is being read and scrolling does not stall on each
+    /// new segment. At most half of the segment cache is used for this,
+    /// so there is no readahead with a cache of less than three segments.
+    ///
+    /// Does nothing for streams, which are held in memory.
+    pub fn set_readahead(&mut self, segments: usize) {
+        if let BufferRepr::File { readahead, .. } = &mut self.map.repr {
+            *readahead = segments;
+        }
+    }
+
+    /// Counters of how this buffer has been read, and how full its segment
+    /// cache is. Segments read by searches, which have a cache of their own,
+    /// are not counted.
+    pub fn stats(&self) -> BufferStats {
+        let (cached, capacity) = match &self.map.repr {
+            BufferRepr::File { segments, .. } => {
+                let segments = segments.borrow();
+                (segments.len(), Some(segments.cap().get()))
+            }
+            BufferRepr::Stream(inner) => (inner.borrow().segments.len(), None),
+        };
+        BufferStats {
+            fetches: self.map.fetches.get(),
+            maps: self.map.maps.get(),
+            cached,
+            capacity,
+        }
+    }
+
+    /// Create another [SegBuffer] over the same data, sharing the [LineIndex]
+    /// but with its own segment cache.
+    ///
+    /// Streams can only be cloned once they have been read in full, since
+    /// the remaining data is only delivered to one of the buffers.
+    pub fn try_clone(&self) -> Result<Self> {
+        let seg_count = match &self.map.repr {
+            BufferRepr::File { segments, .. } => segments.borrow().cap(),
+            BufferRepr::Stream(inner) => {
+                let mut inner = inner.borrow_mut();
+                inner.receive_pending();
+                if inner.pending_segs.is_some() {
+                    return Err(Error::IndexIncomplete);
+                }
+                NonZeroUsize::MIN
+            }
+        };
+
+        Ok(Self {
+            index: self.index.clone(),
+            map: self.map.try_clone_detached(seg_count)?,
+            search_segments: SharedSegments::new(),
         })
     }
 
@@ -155,41 +672,25 @@ impl SegBuffer {
         &self.index
     }
 
-    pub fn get_bytes(&self, line_number: usize) -> Option<SegBytes> {
-        assert!(line_number <= self.line_count());
-
-        let data_start = self.index.data_of_line(line_number)?;
-        let data_end = self.index.data_of_line(line_number + 1)?;
-        let seg_start = self.map.id_of_data(data_start);
-        let seg_end = self.map.id_of_data(data_end);
-
-        if seg_start == seg_end {
-            // The data is in a single segment
-            let seg = self.map.fetch(seg_start)?;
-            let range = seg.translate_inner_data_range(data_start, data_end);
-            Some(seg.get_bytes(range))
-        } else {
-            debug_assert!(seg_start < seg_end);
-            // The data may cross several segments, so we must piece together
-            // the data from across the segments.
-            let mut buf = Vec::with_capacity((data_end - data_start) as usize);
-
-            let seg_first = self.map.fetch(seg_start)?;
-            let seg_last = self.map.fetch(seg_end)?;
-            let (start, end) = (
-                seg_first.translate_inner_data_index(data_start),
-                seg_last.translate_inner_data_index(data_end),
-            );
-            buf.extend_from_slice(&seg_first[start as usize..]);
-            for seg_id in seg_start + 1..seg_end {
-                buf.extend_from_slice(&self.map.fetch(seg_id)?);
+    /// Whether the file backing this buffer has been deleted since it was
+    /// opened. The open file keeps its data alive, so lines can still be read.
+    ///
+    /// Always `false` for streams, and on platforms other than Unix.
+    pub fn is_deleted(&self) -> bool {
+        match &self.map.repr {
+            #[cfg(unix)]
+            BufferRepr::File { file, .. } => {
+                use std::os::unix::fs::MetadataExt;
+                file.metadata().is_ok_and(|meta| meta.nlink() == 0)
             }
-            buf.extend_from_slice(&seg_last[..end as usize]);
-
-            Some(SegBytes::new_owned(buf))
+            _ => false,
         }
     }
 
+    pub fn get_bytes(&self, line_number: usize) -> Option<SegBytes> {
+        self.map.get_bytes(&self.index, line_number)
+    }
+
     /// Retrieves a line of text from the buffer based on the given line number.
     ///
     /// # Panics
@@ -204,44 +705,95 @@ impl SegBuffer {
         Some(SegStr::from_bytes(self.get_bytes(line_number)?))
     }
 
+    /// Iterate over the lines of this buffer in order, up to the lines that
+    /// have been indexed so far.
+    pub fn lines(&self) -> impl Iterator<Item = SegStr> + '_ {
+        (0..self.line_count()).map_while(|line_number| self.get_line(line_number))
+    }
+
+    /// Create an iterator over the lines of this buffer that can be sent to
+    /// another thread, such as to search it.
+    ///
+    /// Every iterator of this buffer shares one segment cache, so that
+    /// searches running at the same time, such as when several filters are
+    /// added at once, map each segment once between them.
     pub fn segment_iter(&self) -> Result<ContiguousSegmentIterator> {
-        match &self.map.repr {
-            BufferRepr::File { file, len, .. } => Ok(ContiguousSegmentIterator::new(
-                self.index.clone(),
-                0..usize::MAX, // ..self.index.line_count() if nondynamic
-                BufferMap {
-                    repr: BufferRepr::File {
-                        file: file.try_clone()?,
-                        len: *len,
-                        segments: RefCell::new(LruCache::new(NonZeroUsize::new(2).unwrap())),
-                    },
-                    segment_size: self.map.segment_size,
-                },
-            )),
-            BufferRepr::Stream(inner) => Ok(ContiguousSegmentIterator::new(
-                self.index.clone(),
-                0..usize::MAX,
-                BufferMap {
-                    repr: BufferRepr::Stream(RefCell::new(StreamInner {
-                        pending_segs: None,
-                        segments: inner.borrow().segments.clone(),
-                    })),
-                    segment_size: self.map.segment_size,
-                },
-            )),
-        }
+        let mut iter = ContiguousSegmentIterator::new(
+            self.index.clone(),
+            ContiguousSegmentIterator::UNBOUNDED, // ..self.index.line_count() if nondynamic
+            self.map.try_clone_detached(NonZeroUsize::MIN)?,
+        );
+        iter.shared = Some(self.search_segments.clone());
+        Ok(iter)
+    }
+
+    /// Like [SegBuffer::segment_iter], over only the lines in `line_range`,
+    /// so that a search of them reads no further.
+    pub fn segment_iter_range(
+        &self,
+        line_range: Range<usize>,
+    ) -> Result<ContiguousSegmentIterator> {
+        let mut iter = self.segment_iter()?;
+        iter.line_range = line_range;
+        Ok(iter)
+    }
+
+    /// Create a [BufferSnapshot] that can be sent to another thread to read
+    /// lines concurrently with this buffer.
+    ///
+    /// The snapshot shares the [LineIndex] of this buffer, but has its own
+    /// segment cache of `seg_count` segments. For streams, only the data that
+    /// has been received at the time of the snapshot can be read from it.
+    pub fn snapshot(&self, seg_count: NonZeroUsize) -> Result<BufferSnapshot> {
+        Ok(BufferSnapshot {
+            index: self.index.clone(),
+            map: self.map.try_clone_detached(seg_count)?,
+        })
     }
 
     pub fn all_line_matches(&self) -> LineSet {
         LineSet::all(self.index.clone())
     }
 
+    fn incomplete_error(lines: &LineSet) -> Error {
+        if lines.is_all() {
+            Error::IndexIncomplete
+        } else {
+            Error::InProgress
+        }
+    }
+
+    /// Streams `lines` through `write_line` one at a time, along with their
+    /// line numbers and the offsets of their starts in the data.
+    pub fn write_lines<F>(&self, lines: &LineSet, mut write_line: F) -> Result<()>
+    where
+        F: FnMut(usize, u64, &[u8]) -> std::io::Result<()>,
+    {
+        if !lines.is_complete() {
+            return Err(Self::incomplete_error(lines));
+        }
+
+        for i in 0..lines.len() {
+            let Some(line_number) = lines.get(i) else {
+                break;
+            };
+            let (Some(offset), Some(data)) = (
+                self.index.data_of_line(line_number),
+                self.get_bytes(line_number),
+            ) else {
+                break;
+            };
+            write_line(line_number, offset, data.as_bytes())?;
+        }
+        Ok(())
+    }
+
     pub fn write_bytes<W>(&mut self, output: &mut W, lines: &LineSet) -> Result<()>
     where
         W: Write,
     {
         if !lines.is_complete() {
-            return Err(crate::err::Error::InProgress);
+            return Err(Self::incomplete_error(lines));
         }
 
         match lines.snapshot() {
@@ -254,17 +806,16 @@ impl SegBuffer {
             }
             None => match &mut self.map.repr {
                 BufferRepr::File { file, .. } => {
-                    file.seek(std::io::SeekFrom::Start(0))?;
+                    let range = self.index.data_range();
+                    file.seek(std::io::SeekFrom::Start(range.start))?;
                     let mut output = output;
-                    std::io::copy(file, &mut output)?;
+                    std::io::copy(&mut file.take(range.end - range.start), &mut output)?;
                 }
                 BufferRepr::Stream(inner) => {
                     let mut writer = BufWriter::new(output);
-                    let inner = inner.borrow();
-
-                    for seg in inner.segments.iter() {
-                        writer.write_all(seg)?;
-                    }
+                    let mut inner = inner.borrow_mut();
+                    inner.receive_pending();
+                    inner.write_data(self.index.indexed_len(), &mut writer)?;
                 }
             },
         }
@@ -274,7 +825,7 @@ impl SegBuffer {
 
     pub fn write_to_string(&mut self, output: &mut String, lines: &LineSet) -> Result<()> {
         if !lines.is_complete() {
-            return Err(crate::err::Error::InProgress);
+            return Err(Self::incomplete_error(lines));
         }
 
         match lines.snapshot() {
@@ -286,16 +837,18 @@ impl SegBuffer {
             }
             None => match &mut self.map.repr {
                 BufferRepr::File { file, .. } => {
-                    file.seek(std::io::SeekFrom::Start(0))?;
-                    file.read_to_string(output)?;
+                    let range = self.index.data_range();
+                    file.seek(std::io::SeekFrom::Start(range.start))?;
+                    file.take(range.end - range.start).read_to_string(output)?;
                 }
                 BufferRepr::Stream(inner) => {
-                    let inner = inner.borrow();
+                    let mut inner = inner.borrow_mut();
+                    inner.receive_pending();
 
-                    for seg in inner.segments.iter() {
-                        let mut reader = Cursor::new(&seg[..]);
-                        reader.read_to_string(output)?;
-                    }
+                    // Characters may be split across segments
+                    let mut data = Vec::new();
+                    inner.write_data(self.index.indexed_len(), &mut data)?;
+                    output.push_str(std::str::from_utf8(&data)?);
                 }
             },
         }
@@ -305,6 +858,37 @@ impl SegBuffer {
     }
 }
 
+/// A read handle over the data of a [SegBuffer] that can be moved to another
+/// thread. See [SegBuffer::snapshot].
+pub struct BufferSnapshot {
+    index: LineIndex,
+    map: BufferMap,
+}
+
+impl BufferSnapshot {
+    /// Return the line count of the underlying [SegBuffer].
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.index.line_count()
+    }
+
+    /// Return the [LineIndex] of the underlying [SegBuffer].
+    #[inline]
+    pub fn index(&self) -> &LineIndex {
+        &self.index
+    }
+
+    /// See [SegBuffer::get_bytes].
+    pub fn get_bytes(&self, line_number: usize) -> Option<SegBytes> {
+        self.map.get_bytes(&self.index, line_number)
+    }
+
+    /// See [SegBuffer::get_line].
+    pub fn get_line(&self, line_number: usize) -> Option<SegStr> {
+        Some(SegStr::from_bytes(self.get_bytes(line_number)?))
+    }
+}
+
 pub struct ContiguousSegmentIterator {
     index: LineIndex,
     map: BufferMap,
@@ -315,6 +899,8 @@ pub struct ContiguousSegmentIterator {
     // Intermediate segment storage for the buffer to borrow from
     // for when the buffer lies within a single segment
     imm_seg: Option<Arc<Segment>>,
+    // Cache shared with the other iterators over the same buffer, if any
+    shared: Option<SharedSegments>,
 }
 
 pub struct ContiguousSegment<'a> {
@@ -324,6 +910,10 @@ pub struct ContiguousSegment<'a> {
 }
 
 impl ContiguousSegmentIterator {
+    /// Line range that follows the index as it grows. The end is a sentinel and
+    /// is never used to compute a line number, only to bound one.
+    const UNBOUNDED: Range<usize> = 0..usize::MAX;
+
     fn new(index: LineIndex, line_range: Range<usize>, map: BufferMap) -> Self {
         Self {
             line_range,
@@ -331,6 +921,7 @@ impl ContiguousSegmentIterator {
             map,
             imm_buf: Vec::new(),
             imm_seg: None,
+            shared: None,
         }
     }
 
@@ -351,27 +942,34 @@ impl ContiguousSegmentIterator {
     /// # Returns
     ///
     /// - `Some((&Idx, u64, &[u8]))`: A tuple containing the index, starting data
-    ///                               position, and a slice of the buffer data.
+    ///   position, and a slice of the buffer data.
     /// - `None`: If there are no more buffers available.
-    pub fn next(&mut self) -> Option<ContiguousSegment> {
+    // The segment borrows the iterator, which `Iterator` cannot express
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<ContiguousSegment<'_>> {
         if self.line_range.is_empty() {
             return None;
         }
 
         let curr_line = self.line_range.start;
         let curr_line_data_start = self.index.data_of_line(curr_line)?;
-        let curr_line_data_end = self.index.data_of_line(curr_line + 1)?;
+        let curr_line_data_end = self.index.data_of_line(curr_line.checked_add(1)?)?;
 
         let curr_line_seg_start = self.map.id_of_data(curr_line_data_start);
         let curr_line_seg_end = self.map.id_of_data(curr_line_data_end);
 
+        let fetch = |seg_id| match &self.shared {
+            Some(shared) => shared.fetch(&self.map, seg_id),
+            None => self.map.fetch(seg_id),
+        };
+
         if curr_line_seg_end != curr_line_seg_start {
             self.imm_buf.clear();
             self.imm_buf
                 .reserve((curr_line_data_end - curr_line_data_start) as usize);
 
-            let seg_first = self.map.fetch(curr_line_seg_start)?;
-            let seg_last = self.map.fetch(curr_line_seg_end)?;
+            let seg_first = fetch(curr_line_seg_start)?;
+            let seg_last = fetch(curr_line_seg_end)?;
             let (start, end) = (
                 seg_first.translate_inner_data_index(curr_line_data_start),
                 seg_last.translate_inner_data_index(curr_line_data_end),
@@ -379,7 +977,7 @@ impl ContiguousSegmentIterator {
 
             self.imm_buf.extend_from_slice(&seg_first[start as usize..]);
             for seg_id in curr_line_seg_start + 1..curr_line_seg_end {
-                self.imm_buf.extend_from_slice(&self.map.fetch(seg_id)?);
+                self.imm_buf.extend_from_slice(&fetch(seg_id)?);
             }
             self.imm_buf.extend_from_slice(&seg_last[..end as usize]);
 
@@ -390,8 +988,10 @@ impl ContiguousSegmentIterator {
                 data: &self.imm_buf,
             })
         } else {
-            let curr_seg_data_start = curr_line_seg_start as u64 * self.map.segment_size;
-            let curr_seg_data_end = curr_seg_data_start + self.map.segment_size;
+            let Range {
+                start: curr_seg_data_start,
+                end: curr_seg_data_end,
+            } = self.map.data_range_of_id(curr_line_seg_start);
 
             let line_end = self
                 .index
@@ -401,7 +1001,7 @@ impl ContiguousSegmentIterator {
             let line_end_data_start = self.index.data_of_line(line_end)?;
 
             // this line should not cross multiple segments, else we would have caught in the first case
-            let segment = self.map.fetch(curr_line_seg_start)?;
+            let segment = fetch(curr_line_seg_start)?;
             let range =
                 segment.translate_inner_data_range(curr_line_data_start, line_end_data_start);
             assert!(line_end_data_start - curr_seg_data_start <= self.map.segment_size);
@@ -424,42 +1024,83 @@ impl ContiguousSegmentIterator {
 mod test {
     use anyhow::Result;
     use std::{
+        cell::Cell,
         fs::File,
         io::{BufReader, Read},
-        num::NonZeroUsize,
+        num::{NonZeroU64, NonZeroUsize},
     };
 
+    use super::{BufferMap, BufferRepr, BufferStats, ContiguousSegmentIterator, StreamInner};
+    use crate::buf::segment::SegmentMut;
     use crate::buf::SegBuffer;
+    use crate::fixture::{Fixture, FixtureBuilder};
+    use crate::index::Delimiter;
+    use crate::{LineIndex, LineSet};
+    use std::{cell::RefCell, io::Cursor, sync::Arc};
 
     #[test]
+    #[cfg_attr(miri, ignore)]
     fn file_stream_consistency_1() -> Result<()> {
-        file_stream_consistency_base(File::open("../../tests/test_10.log")?, 10)
+        let fixture = FixtureBuilder::new(1)
+            .lines(9, 50..150)
+            .unterminated_line(80)
+            .build();
+        file_stream_consistency_base(&fixture, "short", Some(10))
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)]
     fn file_stream_consistency_2() -> Result<()> {
-        file_stream_consistency_base(File::open("../../tests/test_50_long.log")?, 50)
+        let fixture = FixtureBuilder::new(2)
+            .lines(20, 9000..15000)
+            .long_line()
+            .lines(20, 9000..15000)
+            .build();
+        file_stream_consistency_base(&fixture, "long", None)
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)]
     fn file_stream_consistency_3() -> Result<()> {
-        file_stream_consistency_base(File::open("../../tests/test_5000000.log")?, 5_000_000)
+        let fixture = FixtureBuilder::new(3)
+            .crlf_lines(20)
+            .multibyte_lines(20)
+            .lines(1, 0..1)
+            .crlf_lines(5)
+            .unterminated_line(3)
+            .build();
+        file_stream_consistency_base(&fixture, "mixed", Some(47))
     }
 
-    fn file_stream_consistency_base(file: File, line_count: usize) -> Result<()> {
-        let stream = BufReader::new(file.try_clone()?);
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn file_stream_consistency_empty() -> Result<()> {
+        file_stream_consistency_base(&FixtureBuilder::new(4).build(), "empty", None)
+    }
 
-        let file_index = SegBuffer::read_file(file, NonZeroUsize::new(25).unwrap(), true)?;
-        let stream_index = SegBuffer::read_stream(Box::new(stream), true)?;
+    fn file_stream_consistency_base(
+        fixture: &Fixture,
+        name: &str,
+        line_count: Option<usize>,
+    ) -> Result<()> {
+        let file = fixture.file(name)?;
+        let file_index = SegBuffer::read_file(file, NonZeroUsize::new(25).unwrap(), true, true)?;
+        let stream_index = SegBuffer::read_stream(fixture.stream(), true)?;
 
         assert_eq!(file_index.line_count(), stream_index.line_count());
-        assert_eq!(file_index.line_count(), line_count);
+        if let Some(line_count) = line_count {
+            assert_eq!(file_index.line_count(), line_count);
+        }
+        let mut data = Vec::new();
         for i in 0..file_index.line_count() {
+            let line = file_index.get_bytes(i).unwrap();
             assert_eq!(
-                file_index.get_line(i).unwrap().as_str(),
-                stream_index.get_line(i).unwrap().as_str()
+                line.as_bytes(),
+                stream_index.get_bytes(i).unwrap().as_bytes()
             );
+            data.extend_from_slice(line.as_bytes());
         }
+        assert_eq!(data, fixture.data());
 
         Ok(())
     }
@@ -486,7 +1127,7 @@ mod test {
         let file_len = file.metadata()?.len();
         let mut reader = BufReader::new(file.try_clone()?);
 
-        let file_buffer = SegBuffer::read_file(file, NonZeroUsize::new(25).unwrap(), true)?;
+        let file_buffer = SegBuffer::read_file(file, NonZeroUsize::new(25).unwrap(), true, true)?;
         let mut buffers = file_buffer.segment_iter()?;
 
         let mut total_bytes = 0;
@@ -503,4 +1144,408 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn write_filtered_lines() -> Result<()> {
+        let data: &[u8] = b"alpha\nbeta\ngamma\n";
+        let mut buf = SegBuffer::read_stream(Box::new(Cursor::new(data)), true)?;
+        let filtered = LineSet::from(vec![0, 2]);
+
+        let mut bytes = Vec::new();
+        buf.write_bytes(&mut bytes, &filtered)?;
+        assert_eq!(bytes, b"alpha\ngamma\n");
+
+        let mut string = String::new();
+        buf.write_to_string(&mut string, &filtered)?;
+        assert_eq!(string, "alpha\ngamma");
+
+        let mut bytes = Vec::new();
+        let all = buf.all_line_matches();
+        buf.write_bytes(&mut bytes, &all)?;
+        assert_eq!(bytes, data);
+
+        let mut lines = Vec::new();
+        buf.write_lines(&filtered, |line_number, offset, data| {
+            lines.push((line_number, offset, data.to_vec()));
+            Ok(())
+        })?;
+        assert_eq!(
+            lines,
+            [(0, 0, b"alpha\n".to_vec()), (2, 11, b"gamma\n".to_vec())]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn read_file_lines() -> Result<()> {
+        let fixture = FixtureBuilder::new(6)
+            .lines(10, 0..40)
+            .long_line()
+            .lines(10, 0..40)
+            .build();
+        let seg_count = NonZeroUsize::new(25).unwrap();
+        let file = fixture.file("range")?;
+        let whole = SegBuffer::read_file(file.try_clone()?, seg_count, true, false)?;
+        let mut buf =
+            SegBuffer::read_file_lines(file, seg_count, Delimiter::Newline, 8..14, true, false)?;
+
+        assert_eq!(buf.line_count(), 6);
+        for line_number in 0..6 {
+            assert_eq!(
+                buf.get_bytes(line_number).unwrap().as_bytes(),
+                whole.get_bytes(line_number + 8).unwrap().as_bytes()
+            );
+        }
+
+        // Searches and exports go no further than the lines of the range
+        let mut segments = buf.segment_iter()?;
+        let mut data = Vec::new();
+        while let Some(segment) = segments.next() {
+            data.extend_from_slice(segment.data);
+        }
+        let mut bytes = Vec::new();
+        let all = buf.all_line_matches();
+        buf.write_bytes(&mut bytes, &all)?;
+        assert_eq!(bytes, data);
+        let expected = (8..14)
+            .flat_map(|line_number| whole.get_bytes(line_number).unwrap().as_bytes().to_vec())
+            .collect::<Vec<_>>();
+        assert_eq!(bytes, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_file_complete() -> Result<()> {
+        let buffer = SegBuffer::read_file_complete("../../tests/test_10.log")?;
+        assert!(buffer.index().is_complete());
+        assert_eq!(buffer.lines().count(), 10);
+
+        // Lines keep their terminator
+        let data = std::fs::read_to_string("../../tests/test_10.log")?;
+        for (line, expected) in buffer.lines().zip(data.split_inclusive('\n')) {
+            assert_eq!(line.as_str(), expected);
+        }
+
+        assert!(SegBuffer::read_file_complete("../../tests/missing.log").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn read_file_locked() -> Result<()> {
+        // A copy, since the other tests read the fixture with a shared lock
+        let path = std::env::temp_dir().join(format!("bvr_locked_{}.log", std::process::id()));
+        std::fs::copy("../../tests/test_10.log", &path)?;
+        let exclusive = File::open(&path)?;
+        exclusive.lock()?;
+
+        let result = SegBuffer::read_file(File::open(&path)?, NonZeroUsize::MIN, true, true);
+        assert!(matches!(result, Err(crate::err::Error::Lock(_))));
+
+        let result = SegBuffer::read_file(File::open(&path)?, NonZeroUsize::MIN, true, false);
+        assert!(result.is_ok());
+
+        drop((result, exclusive));
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    #[cfg_attr(miri, ignore)]
+    fn read_deleted_file() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("bvr_deleted_{}.log", std::process::id()));
+        std::fs::copy("../../tests/test_10.log", &path)?;
+
+        let buffer = SegBuffer::read_file(File::open(&path)?, NonZeroUsize::MIN, true, true)?;
+        let expected = std::fs::read_to_string(&path)?;
+        assert!(!buffer.is_deleted());
+
+        std::fs::remove_file(&path)?;
+        assert!(buffer.is_deleted());
+        for (i, line) in expected.split_inclusive('\n').enumerate() {
+            assert_eq!(buffer.get_line(i).unwrap().as_str(), line);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn read_empty_file() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("bvr_empty_{}.log", std::process::id()));
+        File::create(&path)?;
+        let file = File::open(&path)?;
+        std::fs::remove_file(&path)?;
+        let stream = BufReader::new(file.try_clone()?);
+
+        for mut buffer in [
+            SegBuffer::read_file(file, NonZeroUsize::MIN, true, true)?,
+            SegBuffer::read_stream(Box::new(stream), true)?,
+        ] {
+            assert_eq!(buffer.line_count(), 1);
+            for i in 0..buffer.line_count() {
+                assert_eq!(buffer.get_line(i).unwrap().as_str(), "");
+            }
+            assert!(buffer.get_line(buffer.line_count()).is_none());
+
+            let mut iter = buffer.segment_iter()?;
+            while let Some(segment) = iter.next() {
+                assert!(segment.data.is_empty());
+            }
+
+            let all = buffer.all_line_matches();
+            assert_eq!(all.len(), buffer.line_count());
+            assert_eq!(all.nearest_forward(0), all.get(0));
+
+            let mut bytes = Vec::new();
+            buffer.write_bytes(&mut bytes, &all)?;
+            assert!(bytes.is_empty());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn stream_spills_to_file() -> Result<()> {
+        let file = File::open("../../tests/test_5000000.log")?;
+        let stream = BufReader::new(file.try_clone()?);
+
+        let file_buffer = SegBuffer::read_file(file, NonZeroUsize::new(25).unwrap(), true, true)?;
+        let mut stream_buffer = SegBuffer::read_stream(Box::new(stream), true)?;
+        stream_buffer.limit_stream_memory(0)?;
+
+        let step = file_buffer.line_count() / 1000;
+        for i in (0..file_buffer.line_count()).step_by(step.max(1)) {
+            assert_eq!(
+                file_buffer.get_line(i).unwrap().as_str(),
+                stream_buffer.get_line(i).unwrap().as_str()
+            );
+        }
+
+        let snapshot = stream_buffer.snapshot(NonZeroUsize::MIN)?;
+        let last = snapshot.line_count() - 1;
+        assert_eq!(
+            snapshot.get_line(last).unwrap().as_str(),
+            file_buffer.get_line(last).unwrap().as_str()
+        );
+
+        let all = stream_buffer.all_line_matches();
+        let mut bytes = Vec::new();
+        stream_buffer.write_bytes(&mut bytes, &all)?;
+        assert_eq!(bytes, std::fs::read("../../tests/test_5000000.log")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fixed_size_records() -> Result<()> {
+        let size = Delimiter::Fixed(NonZeroU64::new(4).unwrap());
+        let buf =
+            SegBuffer::read_stream_delimited(Box::new(Cursor::new(b"aaaabbbbcc")), size, true)?;
+        assert_eq!(buf.line_count(), 3);
+        assert_eq!(buf.get_line(0).unwrap().as_str(), "aaaa");
+        assert_eq!(buf.get_line(1).unwrap().as_str(), "bbbb");
+        assert_eq!(buf.get_line(2).unwrap().as_str(), "cc");
+
+        // Records are cut regardless of newlines, and across segment boundaries
+        let size = Delimiter::Fixed(NonZeroU64::new(7).unwrap());
+        let file = File::open("../../tests/test_5000000.log")?;
+        let len = file.metadata()?.len();
+        let stream = BufReader::new(file.try_clone()?);
+        let file_buffer =
+            SegBuffer::read_file_delimited(file, NonZeroUsize::new(25).unwrap(), size, true, true)?;
+        let stream_buffer = SegBuffer::read_stream_delimited(Box::new(stream), size, true)?;
+        assert_eq!(
+            file_buffer.line_count(),
+            len.div_ceil(7) as usize + usize::from(len % 7 == 0)
+        );
+        assert_eq!(file_buffer.line_count(), stream_buffer.line_count());
+
+        let step = file_buffer.line_count() / 1000;
+        for i in (0..file_buffer.line_count()).step_by(step.max(1)) {
+            assert_eq!(
+                file_buffer.get_bytes(i).unwrap().as_bytes(),
+                stream_buffer.get_bytes(i).unwrap().as_bytes()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn clone_shares_data() -> Result<()> {
+        let file = File::open("../../tests/test_10.log")?;
+        let stream = BufReader::new(file.try_clone()?);
+
+        for buffer in [
+            SegBuffer::read_file(file, NonZeroUsize::new(25).unwrap(), true, true)?,
+            SegBuffer::read_stream(Box::new(stream), true)?,
+        ] {
+            let clone = buffer.try_clone()?;
+            assert_eq!(clone.line_count(), buffer.line_count());
+            for i in 0..buffer.line_count() {
+                assert_eq!(
+                    clone.get_line(i).unwrap().as_str(),
+                    buffer.get_line(i).unwrap().as_str()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn snapshot_reads_from_another_thread() -> Result<()> {
+        let file = File::open("../../tests/test_50_long.log")?;
+        let stream = BufReader::new(file.try_clone()?);
+
+        for buffer in [
+            SegBuffer::read_file(file, NonZeroUsize::new(25).unwrap(), true, true)?,
+            SegBuffer::read_stream(Box::new(stream), true)?,
+        ] {
+            let snapshot = buffer.snapshot(NonZeroUsize::new(2).unwrap())?;
+            let lines = std::thread::spawn(move || {
+                (0..snapshot.line_count())
+                    .map(|i| snapshot.get_line(i).unwrap().as_str().to_owned())
+                    .collect::<Vec<_>>()
+            })
+            .join()
+            .unwrap();
+
+            assert_eq!(lines.len(), buffer.line_count());
+            for (i, line) in lines.iter().enumerate() {
+                assert_eq!(buffer.get_line(i).unwrap().as_str(), line);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn readahead_maps_next_segments() -> Result<()> {
+        let file = File::open("../../tests/test_5000000.log")?;
+        let mut buffer = SegBuffer::read_file(file, NonZeroUsize::new(5).unwrap(), true, true)?;
+        buffer.set_readahead(3);
+        let mapped = |buffer: &SegBuffer, seg_id| match &buffer.map.repr {
+            BufferRepr::File { segments, .. } => segments.borrow().contains(&seg_id),
+            BufferRepr::Stream(_) => unreachable!(),
+        };
+
+        // Limited to half of the cache
+        buffer.get_line(0).unwrap();
+        assert!(mapped(&buffer, 1) && mapped(&buffer, 2));
+        assert!(!mapped(&buffer, 3));
+
+        // Reading backwards maps the segments before
+        let line = buffer.line_count() - 1;
+        buffer.get_line(line).unwrap();
+        let last = buffer
+            .map
+            .id_of_data(buffer.index().data_of_line(line).unwrap());
+        buffer.get_line(line / 2).unwrap();
+        let middle = buffer
+            .map
+            .id_of_data(buffer.index().data_of_line(line / 2).unwrap());
+        assert!(last > middle + 2);
+        assert!(mapped(&buffer, middle - 1) && mapped(&buffer, middle - 2));
+        Ok(())
+    }
+
+    #[test]
+    fn stats_count_fetches() -> Result<()> {
+        let fixture = FixtureBuilder::new(9).long_line().lines(10, 0..40).build();
+        let file = fixture.file("stats")?;
+        let buffer = SegBuffer::read_file(file, NonZeroUsize::new(2).unwrap(), true, true)?;
+        assert_eq!(
+            buffer.stats(),
+            BufferStats {
+                fetches: 0,
+                maps: 0,
+                cached: 0,
+                capacity: Some(2),
+            }
+        );
+
+        // A line across three segments is pieced together from all of them
+        buffer.get_line(0).unwrap();
+        let stats = buffer.stats();
+        assert_eq!((stats.fetches, stats.maps, stats.cached), (3, 3, 2));
+
+        // The next line starts in the last of them, which is still cached
+        buffer.get_line(1).unwrap();
+        let stats = buffer.stats();
+        assert_eq!((stats.fetches, stats.maps), (4, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn segment_iters_share_segments() -> Result<()> {
+        let file = File::open("../../tests/test_10.log")?;
+        let buffer = SegBuffer::read_file(file, NonZeroUsize::MIN, true, true)?;
+
+        let mut first = buffer.segment_iter()?;
+        let mut second = buffer.segment_iter()?;
+        assert_eq!(first.next().unwrap().data, second.next().unwrap().data);
+        assert!(Arc::ptr_eq(
+            first.imm_seg.as_ref().unwrap(),
+            second.imm_seg.as_ref().unwrap()
+        ));
+
+        Ok(())
+    }
+
+    fn synthetic_iter(data: &[u8], offsets: Vec<u64>) -> Result<ContiguousSegmentIterator> {
+        let mut segment = SegmentMut::new(0, data.len() as u64)?;
+        segment.copy_from_slice(data);
+
+        Ok(ContiguousSegmentIterator::new(
+            LineIndex::from_offsets(offsets),
+            ContiguousSegmentIterator::UNBOUNDED,
+            BufferMap {
+                repr: BufferRepr::Stream(RefCell::new(StreamInner {
+                    pending_segs: None,
+                    segments: vec![Arc::new(segment.into_read_only()?)],
+                    spill: None,
+                })),
+                segment_size: SegBuffer::SEGMENT_SIZE,
+                fetches: Cell::new(0),
+                maps: Cell::new(0),
+            },
+        ))
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn segment_iter_unbounded_sentinel() -> Result<()> {
+        let mut iter = synthetic_iter(b"a\nbb\nccc\n", vec![0, 2, 5, 9])?;
+
+        let segment = iter.next().unwrap();
+        assert_eq!(segment.range, 0..9);
+        assert_eq!(segment.data, b"a\nbb\nccc\n");
+
+        // The sentinel end must not be used to compute any line numbers
+        assert_eq!(iter.remaining_range(), 3..usize::MAX);
+        assert!(iter.next().is_none());
+        assert!(iter.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn segment_iter_near_max_line() -> Result<()> {
+        let mut iter = synthetic_iter(b"a\n", vec![0, 2])?;
+        iter.line_range = usize::MAX - 1..usize::MAX;
+        assert!(iter.next().is_none());
+
+        iter.line_range = usize::MAX..usize::MAX;
+        assert!(iter.next().is_none());
+
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
-use crate::Result;
+use crate::{err::Error, Result};
 use memmap2::{Mmap, MmapMut};
-use std::{borrow::Cow, ops::Range, ptr::NonNull, sync::Arc};
+use std::{ops::Range, ptr::NonNull, str::Utf8Error, sync::Arc};
 
 #[cfg(unix)]
 pub(crate) use std::os::fd::AsRawFd as Mmappable;
@@ -64,7 +64,10 @@ where
 
 impl SegmentMut {
     pub(crate) fn new(start: u64, len: u64) -> Result<Self> {
-        let data = memmap2::MmapOptions::new().len(len as usize).map_anon()?;
+        let data = memmap2::MmapOptions::new()
+            .len(len as usize)
+            .map_anon()
+            .map_err(Error::Mmap)?;
         #[cfg(unix)]
         data.advise(memmap2::Advice::Sequential)?;
         Ok(Self {
@@ -75,7 +78,7 @@ impl SegmentMut {
 
     pub fn into_read_only(self) -> Result<Segment> {
         Ok(Segment {
-            data: self.data.make_read_only()?,
+            data: self.data.make_read_only().map_err(Error::Mmap)?,
             range: self.range,
         })
     }
@@ -89,7 +92,8 @@ impl Segment {
             memmap2::MmapOptions::new()
                 .offset(range.start)
                 .len(size as usize)
-                .map(file)?
+                .map(file)
+                .map_err(Error::Mmap)?
         };
         #[cfg(unix)]
         data.advise(memmap2::Advice::WillNeed)?;
@@ -198,9 +202,15 @@ impl std::convert::AsRef<[u8]> for SegBytes {
 /// If the [SegStr] borrows from the segment, the segment will not be dropped until
 /// all of its referents is dropped.
 ///
-/// This structure avoids cloning unnecessarily.
+/// This structure avoids cloning unnecessarily. Data that is not valid utf-8
+/// is replaced with `U+FFFD`, which can be detected with [SegStr::is_lossy]
+/// and refused with [SegStr::try_as_str].
 #[derive(Clone)]
-pub struct SegStr(SegStrRepr);
+pub struct SegStr {
+    repr: SegStrRepr,
+    // The error from the original data, if it had to be converted lossily.
+    error: Option<Utf8Error>,
+}
 
 /// Internal representation of [SegStr].
 #[derive(Clone)]
@@ -225,27 +235,39 @@ impl SegStr {
             SegBytesRepr::Borrowed { _ref, ptr, len } => {
                 // Safety: by construction of SegBytes
                 let data = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), len) };
-                match String::from_utf8_lossy(data) {
-                    Cow::Owned(s) => Self(SegStrRepr::Owned(s)),
-                    Cow::Borrowed(_) => Self(SegStrRepr::Borrowed { ptr, len, _ref }),
+                match std::str::from_utf8(data) {
+                    Ok(_) => Self {
+                        repr: SegStrRepr::Borrowed { ptr, len, _ref },
+                        error: None,
+                    },
+                    Err(err) => Self {
+                        repr: SegStrRepr::Owned(String::from_utf8_lossy(data).into_owned()),
+                        error: Some(err),
+                    },
                 }
             }
-            SegBytesRepr::Owned(b) => match String::from_utf8_lossy(&b) {
-                Cow::Owned(s) => Self(SegStrRepr::Owned(s)),
-                Cow::Borrowed(_) => {
-                    // Safety: We already checked that the data is valid utf-8
-                    //         in the `String::from_utf8_lossy` call.
-                    Self(SegStrRepr::Owned(unsafe { String::from_utf8_unchecked(b) }))
-                }
+            SegBytesRepr::Owned(b) => match String::from_utf8(b) {
+                Ok(s) => Self {
+                    repr: SegStrRepr::Owned(s),
+                    error: None,
+                },
+                Err(err) => Self {
+                    repr: SegStrRepr::Owned(String::from_utf8_lossy(err.as_bytes()).into_owned()),
+                    error: Some(err.utf8_error()),
+                },
             },
         }
     }
 
     /// Returns a byte slice of this [SegStr]'s components.
+    ///
+    /// These are the bytes of [SegStr::as_str], so offsets into one are valid
+    /// in the other. They borrow the segment without copying unless the
+    /// original data was converted lossily.
     #[inline]
     pub fn as_bytes(&self) -> &[u8] {
         // Safety: We have already checked in the constructor.
-        match &self.0 {
+        match &self.repr {
             SegStrRepr::Borrowed { ptr, len, .. } => unsafe {
                 std::slice::from_raw_parts(ptr.as_ptr(), *len)
             },
@@ -259,6 +281,22 @@ impl SegStr {
         // Safety: we already did utf-8 checking
         unsafe { std::str::from_utf8_unchecked(self.as_bytes()) }
     }
+
+    /// Extract a [str] slice only if the original data was valid utf-8,
+    /// otherwise the error describing the first invalid sequence.
+    #[inline]
+    pub fn try_as_str(&self) -> std::result::Result<&str, Utf8Error> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.as_str()),
+        }
+    }
+
+    /// Whether invalid utf-8 in the original data was replaced with `U+FFFD`.
+    #[inline]
+    pub fn is_lossy(&self) -> bool {
+        self.error.is_some()
+    }
 }
 
 impl std::borrow::Borrow<str> for SegStr {
@@ -289,3 +327,51 @@ impl std::fmt::Debug for SegStr {
         std::fmt::Debug::fmt(self.as_str(), f)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{SegBytes, SegStr, SegmentMut};
+    use std::sync::Arc;
+
+    fn borrowed(data: &[u8]) -> SegStr {
+        let mut segment = SegmentMut::new(0, data.len() as u64).unwrap();
+        segment.copy_from_slice(data);
+        let segment = Arc::new(segment.into_read_only().unwrap());
+        segment.get_line(0..data.len() as u64)
+    }
+
+    #[test]
+    fn valid_utf8_is_strict() {
+        for line in [
+            borrowed("héllo".as_bytes()),
+            SegStr::from_bytes(SegBytes::new_owned("héllo".into())),
+        ] {
+            assert!(!line.is_lossy());
+            assert_eq!(line.try_as_str(), Ok("héllo"));
+            assert_eq!(line.as_str(), "héllo");
+            assert_eq!(line.as_bytes(), "héllo".as_bytes());
+        }
+    }
+
+    #[test]
+    fn invalid_utf8_is_lossy() {
+        let data = b"ab\xffcd";
+        for line in [
+            borrowed(data),
+            SegStr::from_bytes(SegBytes::new_owned(data.to_vec())),
+        ] {
+            assert!(line.is_lossy());
+            assert_eq!(line.try_as_str().unwrap_err().valid_up_to(), 2);
+            assert_eq!(line.as_str(), "ab\u{FFFD}cd");
+            assert_eq!(line.as_bytes(), "ab\u{FFFD}cd".as_bytes());
+        }
+    }
+
+    #[test]
+    fn truncated_sequence_is_lossy() {
+        let line = borrowed(&"é".as_bytes()[..1]);
+        assert!(line.is_lossy());
+        assert_eq!(line.try_as_str().unwrap_err().error_len(), None);
+        assert_eq!(line.as_str(), "\u{FFFD}");
+    }
+}
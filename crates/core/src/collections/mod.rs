@@ -1,5 +0,0 @@
-pub mod cowset;
-pub mod ftree;
-pub mod indexset;
-
-pub use indexset::BTreeSet;
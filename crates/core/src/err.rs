@@ -1,19 +1,39 @@
 //! Error types and utilities.
 
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 /// Represents an error that can occur in the application.
 pub enum Error {
-    /// An I/O error occurred.
-    #[error("i/o error {0}")]
+    /// An I/O error occurred while opening or reading the input.
+    #[error("i/o error: {0}")]
     Io(#[from] std::io::Error),
 
-    /// An internal error occurred.
+    /// The file could not be locked, usually because another process holds
+    /// an exclusive lock on it.
+    #[error("file is locked: {0}")]
+    Lock(#[source] std::io::Error),
+
+    /// A segment of the input could not be memory mapped.
+    #[error("failed to map segment: {0}")]
+    Mmap(#[source] std::io::Error),
+
+    /// The data is not valid UTF-8.
+    #[error("invalid utf-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+
+    /// A background worker stopped unexpectedly.
     #[error("internal error")]
     Internal,
 
+    /// The operation needs a search or composite that is still running.
     #[error("operation not supported when input is in-progress")]
     InProgress,
 
+    /// The operation needs the whole input, but it has not been fully indexed.
+    #[error("operation not supported until the input is fully indexed")]
+    IndexIncomplete,
+
+    /// The operation is not supported for this input.
     #[error("operation not implemented")]
     Unimplemented,
 }
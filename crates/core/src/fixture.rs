@@ -0,0 +1,125 @@
+//! Small, deterministic data for tests, built in memory so that correctness
+//! tests do not depend on the large files written by `test_gen`.
+
+use crate::{buf::SegBuffer, index::BoxedStream};
+use std::{fs::File, io::Cursor, io::Write, ops::Range};
+
+/// Generator for reproducible data, the same for a seed on every platform.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn range(&mut self, range: Range<usize>) -> usize {
+        range.start + (self.next() % (range.end - range.start).max(1) as u64) as usize
+    }
+}
+
+/// Builds the data of a [Fixture] out of lines, in order.
+pub struct FixtureBuilder {
+    rng: SplitMix64,
+    data: Vec<u8>,
+}
+
+impl FixtureBuilder {
+    const ALPHANUMERIC: &'static [u8] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SplitMix64(seed),
+            data: Vec::new(),
+        }
+    }
+
+    /// Adds `count` lines of random alphanumeric text with a length in `len`,
+    /// each ending in `\n`.
+    pub fn lines(mut self, count: usize, len: Range<usize>) -> Self {
+        for _ in 0..count {
+            let len = self.rng.range(len.clone());
+            self.text(len);
+            self.data.push(b'\n');
+        }
+        self
+    }
+
+    /// Adds a line that is split across several segments of a [SegBuffer].
+    pub fn long_line(mut self) -> Self {
+        self.text(SegBuffer::SEGMENT_SIZE as usize * 2 + 17);
+        self.data.push(b'\n');
+        self
+    }
+
+    /// Adds lines ending in `\r\n`.
+    pub fn crlf_lines(mut self, count: usize) -> Self {
+        for _ in 0..count {
+            let len = self.rng.range(0..40);
+            self.text(len);
+            self.data.extend_from_slice(b"\r\n");
+        }
+        self
+    }
+
+    /// Adds lines of multibyte characters, some with combining marks.
+    pub fn multibyte_lines(mut self, count: usize) -> Self {
+        const WORDS: [&str; 6] = ["日本語", "café", "e\u{301}", "🦀", "Ελληνικά", "a\u{20dd}"];
+        for _ in 0..count {
+            for _ in 0..self.rng.range(1..8) {
+                let word = WORDS[self.rng.range(0..WORDS.len())];
+                self.data.extend_from_slice(word.as_bytes());
+                self.data.push(b' ');
+            }
+            self.data.push(b'\n');
+        }
+        self
+    }
+
+    /// Adds a line that is not ended by a newline, which should be last.
+    pub fn unterminated_line(mut self, len: usize) -> Self {
+        self.text(len);
+        self
+    }
+
+    pub fn build(self) -> Fixture {
+        Fixture { data: self.data }
+    }
+
+    fn text(&mut self, len: usize) {
+        let rng = &mut self.rng;
+        self.data
+            .extend((0..len).map(|_| Self::ALPHANUMERIC[rng.range(0..Self::ALPHANUMERIC.len())]));
+    }
+}
+
+/// Data to open as a file or to read as a stream.
+pub struct Fixture {
+    data: Vec<u8>,
+}
+
+impl Fixture {
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn stream(&self) -> BoxedStream {
+        Box::new(Cursor::new(self.data.clone()))
+    }
+
+    /// Writes the data to a temporary file, opened for reading. On Unix the
+    /// file is removed right away and lives on until it is closed.
+    pub fn file(&self, name: &str) -> std::io::Result<File> {
+        let path =
+            std::env::temp_dir().join(format!("bvr_fixture_{name}_{}.log", std::process::id()));
+        File::create(&path)?.write_all(&self.data)?;
+        let file = File::open(&path)?;
+        #[cfg(unix)]
+        std::fs::remove_file(&path)?;
+        Ok(file)
+    }
+}
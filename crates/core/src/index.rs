@@ -2,29 +2,75 @@ use crate::buf::segment::{Segment, SegmentMut};
 use crate::cowvec::{CowVec, CowVecWriter};
 use crate::err::{Error, Result};
 use std::fs::File;
+use std::num::NonZeroU64;
+use std::ops::Range;
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::{atomic::AtomicBool, Arc};
 use std::thread::JoinHandle;
 
+/// How the input is split into lines.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Delimiter {
+    /// Every line ends after a `\n`.
+    #[default]
+    Newline,
+    /// Every line is a record of exactly this many bytes, except possibly
+    /// the last one, for inputs of fixed-width records without separators.
+    Fixed(NonZeroU64),
+}
+
+impl Delimiter {
+    /// Offsets just past the end of every line ending within `data`, which
+    /// starts at offset `start` of the input.
+    fn line_ends(self, data: &[u8], start: u64) -> Box<dyn Iterator<Item = u64> + '_> {
+        match self {
+            Delimiter::Newline => {
+                Box::new(memchr::memchr_iter(b'\n', data).map(move |i| start + i as u64 + 1))
+            }
+            Delimiter::Fixed(size) => {
+                let size = size.get();
+                let first = (start / size + 1) * size;
+                let end = start + data.len() as u64;
+                Box::new((first..=end).step_by(size as usize))
+            }
+        }
+    }
+}
+
 struct IndexingTask {
-    /// This is the sender side of the channel that receives byte indexes of `\n`.
+    /// This is the sender side of the channel that receives the byte indexes
+    /// just past the end of every line.
     sx: Sender<u64>,
     segment: Segment,
+    delimiter: Delimiter,
 }
 
 impl IndexingTask {
     #[inline]
-    fn new(file: &File, start: u64, end: u64) -> Result<(Self, Receiver<u64>)> {
+    fn new(
+        file: &File,
+        start: u64,
+        end: u64,
+        delimiter: Delimiter,
+    ) -> Result<(Self, Receiver<u64>)> {
         let segment = Segment::map_file(start..end, file)?;
         let (sx, rx) = std::sync::mpsc::channel();
-        Ok((Self { sx, segment }, rx))
+        Ok((
+            Self {
+                sx,
+                segment,
+                delimiter,
+            },
+            rx,
+        ))
     }
 
     fn compute(self) -> Result<()> {
-        for i in memchr::memchr_iter(b'\n', &self.segment) {
-            self.sx
-                .send(self.segment.start() + i as u64 + 1)
-                .map_err(|_| Error::Internal)?;
+        for line_end in self
+            .delimiter
+            .line_ends(&self.segment, self.segment.start())
+        {
+            self.sx.send(line_end).map_err(|_| Error::Internal)?;
         }
 
         Ok(())
@@ -44,7 +90,14 @@ struct LineIndexRemote {
 impl LineIndexRemote {
     const BYTES_PER_LINE_HEURISTIC: u64 = 128;
 
-    pub fn index_file(mut self, file: File) -> Result<()> {
+    /// Indexes the zero-based `lines` of the file, with offsets from the start
+    /// of the file. The file is read no further than the end of the range.
+    pub fn index_file(
+        mut self,
+        file: File,
+        delimiter: Delimiter,
+        lines: Range<usize>,
+    ) -> Result<()> {
         // Build index
         let (sx, rx) = std::sync::mpsc::sync_channel(4);
 
@@ -52,38 +105,56 @@ impl LineIndexRemote {
         let file = file.try_clone()?;
 
         self.buf
-            .reserve((len / Self::BYTES_PER_LINE_HEURISTIC) as usize);
-        self.buf.push(0);
+            .reserve(((len / Self::BYTES_PER_LINE_HEURISTIC) as usize).min(lines.len()));
+        if lines.start == 0 {
+            self.buf.push(0);
+        }
 
-        // Indexing worker
+        // Indexing worker, the tasks are scoped so that none of them outlives
+        // the indexing, even if it is cut short
         let spawner: JoinHandle<Result<()>> = std::thread::spawn(move || {
-            let mut curr = 0;
+            std::thread::scope(|scope| {
+                let mut curr = 0;
 
-            while curr < len {
-                let end = (curr + SegmentMut::TODO_REMOVE_SIZE).min(len);
-                let (task, task_rx) = IndexingTask::new(&file, curr, end)?;
-                sx.send(task_rx).map_err(|_| Error::Internal)?;
+                while curr < len {
+                    let end = (curr + SegmentMut::TODO_REMOVE_SIZE).min(len);
+                    let (task, task_rx) = IndexingTask::new(&file, curr, end, delimiter)?;
+                    if sx.send(task_rx).is_err() {
+                        // The indexing was cut short
+                        break;
+                    }
 
-                std::thread::spawn(|| task.compute());
+                    scope.spawn(|| task.compute());
 
-                curr = end;
-            }
+                    curr = end;
+                }
 
-            Ok(())
+                Ok(())
+            })
         });
 
-        while let Ok(task_rx) = rx.recv() {
+        let mut line_count = 0;
+        'tasks: while let Ok(task_rx) = rx.recv() {
             if !self.has_readers() {
                 break;
             }
 
             while let Ok(line_data) = task_rx.recv() {
-                self.buf.push(line_data);
+                line_count += 1;
+                if line_count >= lines.start {
+                    self.buf.push(line_data);
+                }
+                if line_count >= lines.end {
+                    break 'tasks;
+                }
             }
         }
 
+        drop(rx);
         spawner.join().map_err(|_| Error::Internal)??;
-        self.buf.push(len);
+        if line_count < lines.end {
+            self.buf.push(len);
+        }
 
         Ok(())
     }
@@ -93,6 +164,7 @@ impl LineIndexRemote {
         mut stream: BoxedStream,
         outgoing: Sender<Segment>,
         segment_size: u64,
+        delimiter: Delimiter,
     ) -> Result<()> {
         let mut len = 0;
 
@@ -109,9 +181,9 @@ impl LineIndexRemote {
                 }
             }
 
-            for i in memchr::memchr_iter(b'\n', &segment) {
-                let line_data = len + i as u64;
-                self.buf.push(line_data + 1);
+            // Only the filled part, as fixed records would also end in the rest
+            for line_end in delimiter.line_ends(&segment[..buf_len], len) {
+                self.buf.push(line_end);
             }
 
             outgoing
@@ -149,7 +221,18 @@ pub struct LineIndex {
 
 impl LineIndex {
     #[inline]
-    pub fn read_file(file: File, complete: bool) -> Result<Self> {
+    pub fn read_file(file: File, delimiter: Delimiter, complete: bool) -> Result<Self> {
+        Self::read_file_lines(file, delimiter, 0..usize::MAX, complete)
+    }
+
+    /// Like [LineIndex::read_file], but indexes only the zero-based `lines`,
+    /// which are numbered from zero at the start of the range.
+    pub fn read_file_lines(
+        file: File,
+        delimiter: Delimiter,
+        lines: Range<usize>,
+        complete: bool,
+    ) -> Result<Self> {
         let (buf, writer) = CowVec::new();
         let completed = Arc::new(AtomicBool::new(false));
         let task = {
@@ -159,7 +242,7 @@ impl LineIndex {
                     buf: writer,
                     completed,
                 }
-                .index_file(file)
+                .index_file(file, delimiter, lines)
             }
         };
         if complete {
@@ -176,6 +259,7 @@ impl LineIndex {
         outgoing: Sender<Segment>,
         block_until_complete: bool,
         segment_size: u64,
+        delimiter: Delimiter,
     ) -> Result<Self> {
         let (buf, writer) = CowVec::new();
         let completed = Arc::new(AtomicBool::new(false));
@@ -186,7 +270,7 @@ impl LineIndex {
                     buf: writer,
                     completed,
                 }
-                .index_stream(stream, outgoing, segment_size)
+                .index_stream(stream, outgoing, segment_size, delimiter)
             }
         };
         if block_until_complete {
@@ -201,6 +285,18 @@ impl LineIndex {
         self.buf.len().saturating_sub(1)
     }
 
+    /// Return the number of bytes indexed so far, which keeps growing until
+    /// the index is complete.
+    pub fn indexed_len(&self) -> u64 {
+        self.data_of_line(self.line_count()).unwrap_or(0)
+    }
+
+    /// Return the offsets of the data indexed so far, which starts past zero
+    /// if only some lines of a file are indexed.
+    pub fn data_range(&self) -> Range<u64> {
+        self.data_of_line(0).unwrap_or(0)..self.indexed_len()
+    }
+
     pub fn data_of_line(&self, line_number: usize) -> Option<u64> {
         self.buf.get(line_number)
     }
@@ -236,4 +332,87 @@ impl LineIndex {
     pub fn is_complete(&self) -> bool {
         self.completed.load(std::sync::atomic::Ordering::Relaxed)
     }
+
+    /// Construct a completed index directly from line start offsets.
+    #[cfg(test)]
+    pub(crate) fn from_offsets(offsets: Vec<u64>) -> Self {
+        Self {
+            buf: Arc::new(CowVec::from(offsets)),
+            completed: Arc::new(AtomicBool::new(true)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Delimiter, LineIndex};
+    use crate::fixture::FixtureBuilder;
+    use crate::LineSet;
+    use std::num::NonZeroU64;
+
+    #[test]
+    fn test_miri_index_boundaries() {
+        let index = LineIndex::from_offsets(vec![0, 2, 5, 9]);
+
+        assert_eq!(index.line_count(), 3);
+        assert_eq!(index.indexed_len(), 9);
+        assert_eq!(index.data_of_line(3), Some(9));
+        assert_eq!(index.data_of_line(4), None);
+        assert_eq!(index.data_of_line(usize::MAX), None);
+
+        assert_eq!(index.line_of_data(0), Some(0));
+        assert_eq!(index.line_of_data(8), Some(2));
+        assert_eq!(index.line_of_data(9), None);
+        assert_eq!(index.line_of_data(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_miri_index_empty() {
+        let index = LineIndex::from_offsets(vec![]);
+
+        assert_eq!(index.line_count(), 0);
+        assert_eq!(index.indexed_len(), 0);
+        assert_eq!(index.data_of_line(0), None);
+        assert_eq!(index.line_of_data(0), None);
+        assert_eq!(index.line_of_data(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_miri_all_lines_sentinel() {
+        let lines = LineSet::all(LineIndex::from_offsets(vec![0, 2, 5, 9]));
+
+        assert_eq!(lines.get(usize::MAX), None);
+        assert_eq!(lines.find(usize::MAX), None);
+        assert_eq!(lines.nearest_forward(1), Some(2));
+        assert_eq!(lines.nearest_forward(2), Some(2));
+        assert_eq!(lines.nearest_forward(usize::MAX), None);
+        assert_eq!(lines.nearest_backward(0), None);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn index_file_lines() {
+        let fixture = FixtureBuilder::new(5).lines(10, 0..20).build();
+        let file = fixture.file("lines").unwrap();
+        let read = |lines, delimiter| {
+            let file = file.try_clone().unwrap();
+            let index = LineIndex::read_file_lines(file, delimiter, lines, true).unwrap();
+            (0..=index.line_count())
+                .map(|line_number| index.data_of_line(line_number).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let full = read(0..usize::MAX, Delimiter::Newline);
+        let len = fixture.data().len() as u64;
+        assert_eq!(full.len(), 12);
+
+        // Offsets are of the file, as the lines are not copied out of it
+        assert_eq!(read(0..1, Delimiter::Newline), full[0..2]);
+        assert_eq!(read(3..6, Delimiter::Newline), full[3..7]);
+        assert_eq!(read(8..usize::MAX, Delimiter::Newline), full[8..]);
+        assert_eq!(read(20..30, Delimiter::Newline), [len]);
+
+        let fixed = Delimiter::Fixed(NonZeroU64::new(4).unwrap());
+        assert_eq!(read(1..2, fixed), [4, 8]);
+        assert_eq!(read(2..usize::MAX, fixed).last(), Some(&len));
+    }
 }
@@ -1,10 +1,10 @@
 pub mod buf;
 
-// #[doc(hidden)]
-// pub mod collections;
 mod cowvec;
 
 pub mod err;
+#[cfg(test)]
+mod fixture;
 pub mod index;
 pub mod matches;
 
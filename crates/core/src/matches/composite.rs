@@ -60,15 +60,12 @@ impl Queues {
             CompositeStrategy::Intersection => {
                 loop {
                     // Take the highest line number from all the queues
-                    let Some((queue_index, line_number)) = self
+                    let (queue_index, line_number) = self
                         .queues
                         .iter_mut()
                         .enumerate()
                         .filter_map(|(i, queue)| queue.peek().map(|ln| (i, ln)))
-                        .max_by_key(|&(_, ln)| ln)
-                    else {
-                        return None;
-                    };
+                        .max_by_key(|&(_, ln)| ln)?;
 
                     // Progress all queues that have a line number less than the max
                     for queue in self.queues.iter_mut() {
@@ -160,7 +157,7 @@ mod tests {
         let composite =
             LineSet::compose(vec![matches1, matches2], true, CompositeStrategy::Union).unwrap();
 
-        let result = vec![1, 2, 3, 4, 5, 7, 9];
+        let result = [1, 2, 3, 4, 5, 7, 9];
 
         let inner = composite.into_inner();
         for i in 0..result.len() {
@@ -192,7 +189,7 @@ mod tests {
         let composite =
             LineSet::compose(vec![matches1, matches2], true, CompositeStrategy::Union).unwrap();
 
-        let result = vec![1, 2, 3, 4, 5];
+        let result = [1, 2, 3, 4, 5];
 
         let inner = composite.into_inner();
         for i in 0..result.len() {
@@ -208,7 +205,7 @@ mod tests {
         let composite =
             LineSet::compose(vec![matches1, matches2], true, CompositeStrategy::Union).unwrap();
 
-        let result = vec![1, 2, 3, 4, 5];
+        let result = [1, 2, 3, 4, 5];
 
         let inner = composite.into_inner();
         for i in 0..result.len() {
@@ -241,7 +238,7 @@ mod tests {
         )
         .unwrap();
 
-        let result = vec![1, 2, 3, 4, 5];
+        let result = [1, 2, 3, 4, 5];
 
         let inner = composite.into_inner();
         for i in 0..result.len() {
@@ -329,7 +326,7 @@ mod tests {
         )
         .unwrap();
 
-        let result = vec![1, 3, 5];
+        let result = [1, 3, 5];
 
         let inner = composite.into_inner();
         for i in 0..result.len() {
@@ -365,7 +362,7 @@ mod tests {
         )
         .unwrap();
 
-        let result = vec![1, 2, 3, 4, 5];
+        let result = [1, 2, 3, 4, 5];
 
         let inner = composite.into_inner();
         for i in 0..result.len() {
@@ -499,7 +496,7 @@ mod tests {
         )
         .unwrap();
 
-        let result = vec![10, 20, 30, 40, 50];
+        let result = [10, 20, 30, 40, 50];
         let inner = composite.into_inner();
         for i in 0..result.len() {
             assert_eq!(result.get(i).copied(), inner.get(i));
@@ -0,0 +1,107 @@
+use crate::buf::ContiguousSegmentIterator;
+use crate::LineSet;
+use regex::bytes::Regex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Number of lines that a regex matches, counted in a background thread
+/// without keeping the line numbers, so that counting over a huge input
+/// takes no memory.
+///
+/// The count can be read while the search is running. The search stops once
+/// it reaches the end of the input, or once it is cancelled or dropped.
+pub struct MatchCount {
+    count: Arc<AtomicUsize>,
+    completed: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl MatchCount {
+    /// Count the lines that the regex matches. With `within`, only the
+    /// matching lines in that set are counted, once it is complete.
+    pub fn search(
+        mut iter: ContiguousSegmentIterator,
+        regex: Regex,
+        within: Option<LineSet>,
+    ) -> Self {
+        let count = Arc::new(AtomicUsize::new(0));
+        let completed = Arc::new(AtomicBool::new(false));
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        std::thread::spawn({
+            let count = count.clone();
+            let completed = completed.clone();
+            let cancelled = cancelled.clone();
+            move || {
+                // Whether a line is in the set is only known once it is complete
+                while within.as_ref().is_some_and(|lines| !lines.is_complete()) {
+                    if cancelled.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+
+                while !cancelled.load(Ordering::Relaxed) {
+                    let Some(segment) = iter.next() else {
+                        if iter.index().is_complete() {
+                            completed.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                        std::hint::spin_loop();
+                        continue;
+                    };
+
+                    let mut start = 0;
+                    while let Some(m) = regex.find_at(segment.data, start) {
+                        let match_start = m.start() as u64 + segment.range.start;
+                        let line_number = segment.index.line_of_data(match_start).unwrap();
+                        if within
+                            .as_ref()
+                            .is_none_or(|lines| lines.has_line(line_number))
+                        {
+                            count.fetch_add(1, Ordering::Relaxed);
+                        }
+
+                        // Each line is counted once, however often it matches
+                        let Some(next_line_start) = line_number
+                            .checked_add(1)
+                            .and_then(|next| segment.index.data_of_line(next))
+                            .filter(|&next| next < segment.range.end)
+                        else {
+                            break;
+                        };
+                        start = (next_line_start - segment.range.start) as usize;
+                    }
+                }
+            }
+        });
+
+        Self {
+            count,
+            completed,
+            cancelled,
+        }
+    }
+
+    /// Lines counted so far.
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Whether the whole input has been searched.
+    pub fn is_complete(&self) -> bool {
+        self.completed.load(Ordering::Relaxed)
+    }
+
+    /// Stop the search, leaving the count where it was.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for MatchCount {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
@@ -1,16 +1,82 @@
 pub mod composite;
+pub mod count;
 
-use crate::buf::ContiguousSegmentIterator;
+use crate::buf::{ContiguousSegment, ContiguousSegmentIterator};
 use crate::cowvec::{CowVec, CowVecSnapshot, CowVecWriter};
 use crate::{LineIndex, Result};
 use regex::bytes::Regex;
-use std::sync::{atomic::AtomicBool, Arc};
+use std::ops::Range;
+use std::sync::{atomic::AtomicBool, mpsc::Sender, Arc};
 
 pub use composite::CompositeStrategy;
+pub use count::MatchCount;
+
+/// Byte ranges of the matches on each matched line, relative to the start of
+/// the line.
+///
+/// The spans of all lines are stored flattened, with `ends[i]` marking the end
+/// of the spans of the `i`th matched line.
+#[derive(Clone)]
+pub struct MatchSpans {
+    spans: Arc<CowVec<(u32, u32)>>,
+    ends: Arc<CowVec<usize>>,
+}
+
+impl MatchSpans {
+    fn new() -> (Self, MatchSpansWriter) {
+        let (spans, spans_writer) = CowVec::new();
+        let (ends, ends_writer) = CowVec::new();
+        (
+            Self { spans, ends },
+            MatchSpansWriter {
+                spans: spans_writer,
+                ends: ends_writer,
+            },
+        )
+    }
+
+    fn get(&self, idx: usize) -> Option<Vec<Range<u32>>> {
+        let end = self.ends.get(idx)?;
+        let start = match idx.checked_sub(1) {
+            Some(prev) => self.ends.get(prev)?,
+            None => 0,
+        };
+        let spans = self.spans.snapshot();
+        Some(
+            spans
+                .as_slice()
+                .get(start..end)?
+                .iter()
+                .map(|&(start, end)| start..end)
+                .collect(),
+        )
+    }
+}
+
+struct MatchSpansWriter {
+    spans: CowVecWriter<(u32, u32)>,
+    ends: CowVecWriter<usize>,
+}
+
+impl MatchSpansWriter {
+    fn record(&mut self, regex: &Regex, line: &[u8]) {
+        let clamp = |i: usize| u32::try_from(i).unwrap_or(u32::MAX);
+        for m in regex.find_iter(line) {
+            self.spans.push((clamp(m.start()), clamp(m.end())));
+        }
+        self.ends.push(self.spans.len());
+    }
+}
 
 struct LineMatchRemote {
     buf: CowVecWriter<usize>,
+    spans: Option<MatchSpansWriter>,
     completed: Arc<AtomicBool>,
+    limit: Option<usize>,
+    capped: Arc<AtomicBool>,
+    notify: Option<Sender<usize>>,
+    // Collect the lines that do not match instead
+    invert: bool,
 }
 
 impl LineMatchRemote {
@@ -19,6 +85,13 @@ impl LineMatchRemote {
             if !self.has_readers() {
                 break;
             } else if let Some(segment) = iter.next() {
+                if self.invert {
+                    if !self.reject(&segment, &regex) {
+                        return Ok(());
+                    }
+                    continue;
+                }
+
                 let mut buf_start = 0;
                 while let Some(res) = regex.find_at(segment.data, buf_start as usize) {
                     let match_start = res.start() as u64 + segment.range.start;
@@ -31,10 +104,45 @@ impl LineMatchRemote {
                         debug_assert!(line_number > last);
                     }
 
+                    if self.limit.is_some_and(|limit| self.buf.len() >= limit) {
+                        self.capped
+                            .store(true, std::sync::atomic::Ordering::Release);
+                        return Ok(());
+                    }
+
+                    let next_line_start = line_number
+                        .checked_add(1)
+                        .and_then(|next| segment.index.data_of_line(next));
+
+                    // Spans must be recorded before the line number is visible
+                    if let Some(spans) = self.spans.as_mut() {
+                        let line_start = segment
+                            .index
+                            .data_of_line(line_number)
+                            .unwrap_or(match_start)
+                            .max(segment.range.start);
+                        let line_end = next_line_start
+                            .unwrap_or(segment.range.end)
+                            .min(segment.range.end);
+                        spans.record(
+                            &regex,
+                            &segment.data[(line_start - segment.range.start) as usize
+                                ..(line_end - segment.range.start) as usize],
+                        );
+                    }
+
                     self.buf.push(line_number);
+                    if let Some(notify) = &self.notify {
+                        // The matches are still collected for the other readers
+                        if notify.send(line_number).is_err() {
+                            self.notify = None;
+                        }
+                    }
 
-                    buf_start =
-                        segment.index.data_of_line(line_number + 1).unwrap() - segment.range.start;
+                    let Some(next_line_start) = next_line_start else {
+                        break;
+                    };
+                    buf_start = next_line_start - segment.range.start;
                 }
             } else if iter.index().is_complete() {
                 break;
@@ -45,6 +153,46 @@ impl LineMatchRemote {
         Ok(())
     }
 
+    /// Collects the lines of the segment that the regex does not match, line
+    /// by line since a segment always ends at a line boundary. Returns false
+    /// once the limit is reached.
+    fn reject(&mut self, segment: &ContiguousSegment, regex: &Regex) -> bool {
+        let Some(mut line_number) = segment.index.line_of_data(segment.range.start) else {
+            return true;
+        };
+        let mut line_start = segment.range.start;
+        while line_start < segment.range.end {
+            let line_end = line_number
+                .checked_add(1)
+                .and_then(|next| segment.index.data_of_line(next))
+                .unwrap_or(segment.range.end)
+                .min(segment.range.end);
+            if line_end <= line_start {
+                break;
+            }
+
+            let line = &segment.data[(line_start - segment.range.start) as usize
+                ..(line_end - segment.range.start) as usize];
+            if !regex.is_match(line) {
+                if self.limit.is_some_and(|limit| self.buf.len() >= limit) {
+                    self.capped
+                        .store(true, std::sync::atomic::Ordering::Release);
+                    return false;
+                }
+                self.buf.push(line_number);
+                if let Some(notify) = &self.notify {
+                    if notify.send(line_number).is_err() {
+                        self.notify = None;
+                    }
+                }
+            }
+
+            line_number += 1;
+            line_start = line_end;
+        }
+        true
+    }
+
     pub fn has_readers(&self) -> bool {
         Arc::strong_count(&self.completed) > 1
     }
@@ -52,8 +200,10 @@ impl LineMatchRemote {
 
 impl Drop for LineMatchRemote {
     fn drop(&mut self) {
+        // Publishes everything written before, such as the cap, to the
+        // readers that see the set complete
         self.completed
-            .store(true, std::sync::atomic::Ordering::Relaxed);
+            .store(true, std::sync::atomic::Ordering::Release);
     }
 }
 
@@ -68,6 +218,10 @@ pub enum LineSet {
         // Optimization field for composite filters
         // Minimum length of all filters combined
         min_len: usize,
+        // Match positions, only present if requested at search time
+        spans: Option<MatchSpans>,
+        // Set if the search stopped early at its match limit
+        capped: Option<Arc<AtomicBool>>,
     },
 }
 
@@ -78,6 +232,8 @@ impl LineSet {
             buf: Arc::new(CowVec::empty()),
             completed: Arc::new(AtomicBool::new(true)),
             min_len: 0,
+            spans: None,
+            capped: None,
         }
     }
 
@@ -91,14 +247,88 @@ impl LineSet {
 
     #[inline]
     pub fn search(iter: ContiguousSegmentIterator, regex: Regex) -> Self {
+        Self::search_inner(iter, regex, false, None, None, false)
+    }
+
+    /// Search for the lines that the regex does not match, otherwise like
+    /// [LineSet::search_with_limit].
+    #[inline]
+    pub fn search_inverted(
+        iter: ContiguousSegmentIterator,
+        regex: Regex,
+        limit: Option<usize>,
+    ) -> Self {
+        Self::search_inner(iter, regex, false, limit, None, true)
+    }
+
+    /// Search for the regex like [LineSet::search], but also send the line
+    /// number of every match to `notify` as soon as it is found, so that
+    /// consumers can react to new matches without polling [LineSet::len].
+    ///
+    /// The sender is dropped once the search is over, which disconnects the
+    /// channel. If the receiver is dropped, the search carries on.
+    #[inline]
+    pub fn search_with_sender(
+        iter: ContiguousSegmentIterator,
+        regex: Regex,
+        notify: Sender<usize>,
+    ) -> Self {
+        Self::search_inner(iter, regex, false, None, Some(notify), false)
+    }
+
+    /// Search for the regex like [LineSet::search], but stop once `limit`
+    /// lines have matched. A search that stopped early is complete, and is
+    /// marked with [LineSet::is_capped].
+    #[inline]
+    pub fn search_with_limit(
+        iter: ContiguousSegmentIterator,
+        regex: Regex,
+        limit: Option<usize>,
+    ) -> Self {
+        Self::search_inner(iter, regex, false, limit, None, false)
+    }
+
+    /// Search for the regex like [LineSet::search], but also record the byte
+    /// ranges of every match on each matched line, retrievable with
+    /// [LineSet::spans].
+    ///
+    /// This costs an additional 8 bytes per match and 8 bytes per matched line
+    /// on top of the line numbers, and the matched lines are scanned a second
+    /// time to collect all of their matches.
+    #[inline]
+    pub fn search_with_spans(iter: ContiguousSegmentIterator, regex: Regex) -> Self {
+        Self::search_inner(iter, regex, true, None, None, false)
+    }
+
+    fn search_inner(
+        iter: ContiguousSegmentIterator,
+        regex: Regex,
+        with_spans: bool,
+        limit: Option<usize>,
+        notify: Option<Sender<usize>>,
+        invert: bool,
+    ) -> Self {
         let (buf, writer) = CowVec::new();
+        let (spans, spans_writer) = if with_spans {
+            let (spans, writer) = MatchSpans::new();
+            (Some(spans), Some(writer))
+        } else {
+            (None, None)
+        };
         let complete = Arc::new(AtomicBool::new(false));
+        let capped = Arc::new(AtomicBool::new(false));
         std::thread::spawn({
             let complete = complete.clone();
+            let capped = capped.clone();
             move || {
                 LineMatchRemote {
                     buf: writer,
+                    spans: spans_writer,
                     completed: complete,
+                    limit,
+                    capped,
+                    notify,
+                    invert,
                 }
                 .search(iter, regex)
             }
@@ -107,6 +337,8 @@ impl LineSet {
             buf,
             completed: complete,
             min_len: 0,
+            spans,
+            capped: limit.map(|_| capped),
         }
     }
 
@@ -146,6 +378,8 @@ impl LineSet {
                     buf,
                     completed,
                     min_len,
+                    spans: None,
+                    capped: None,
                 })
             }
         }
@@ -164,11 +398,23 @@ impl LineSet {
         match self {
             LineSet::All { buf } => buf.is_complete(),
             LineSet::Dynamic { completed, .. } => {
-                completed.load(std::sync::atomic::Ordering::Relaxed)
+                completed.load(std::sync::atomic::Ordering::Acquire)
             }
         }
     }
 
+    /// Return whether a search stopped early at its match limit, in which
+    /// case there are more matching lines than are present in this set.
+    pub fn is_capped(&self) -> bool {
+        match self {
+            LineSet::Dynamic {
+                capped: Some(capped),
+                ..
+            } => capped.load(std::sync::atomic::Ordering::Acquire),
+            _ => false,
+        }
+    }
+
     pub fn get(&self, idx: usize) -> Option<usize> {
         match self {
             LineSet::All { buf } => {
@@ -208,11 +454,22 @@ impl LineSet {
         self.find(line_number).is_some()
     }
 
+    /// Returns the byte ranges of the matches on the `idx`th line of this set,
+    /// relative to the start of the line.
+    ///
+    /// This is only available if the set was created by [LineSet::search_with_spans].
+    pub fn spans(&self, idx: usize) -> Option<Vec<Range<u32>>> {
+        match self {
+            LineSet::All { .. } => None,
+            LineSet::Dynamic { spans, .. } => spans.as_ref()?.get(idx),
+        }
+    }
+
     pub fn nearest_forward(&self, line_number: usize) -> Option<usize> {
         match self {
             LineSet::All { buf } => {
                 if line_number < buf.line_count() {
-                    Some((line_number + 1).min(buf.line_count()))
+                    Some(line_number.saturating_add(1).min(buf.line_count() - 1))
                 } else {
                     None
                 }
@@ -279,6 +536,150 @@ impl From<Vec<usize>> for LineSet {
             min_len: vec.len(),
             buf: Arc::new(CowVec::from(vec)),
             completed: Arc::new(AtomicBool::new(true)),
+            spans: None,
+            capped: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LineSet, MatchCount};
+    use crate::SegBuffer;
+    use anyhow::Result;
+    use regex::bytes::Regex;
+    use std::io::Cursor;
+
+    fn search_limited(data: &'static [u8], pattern: &str, limit: usize) -> Result<LineSet> {
+        let buf = SegBuffer::read_stream(Box::new(Cursor::new(data)), true)?;
+        let lines =
+            LineSet::search_with_limit(buf.segment_iter()?, Regex::new(pattern)?, Some(limit));
+        while !lines.is_complete() {
+            std::hint::spin_loop();
+        }
+        Ok(lines)
+    }
+
+    fn search_complete(data: &'static [u8], pattern: &str, with_spans: bool) -> Result<LineSet> {
+        let buf = SegBuffer::read_stream(Box::new(Cursor::new(data)), true)?;
+        // Pull the indexed segments into the buffer
+        buf.get_line(0);
+
+        let iter = buf.segment_iter()?;
+        let regex = Regex::new(pattern)?;
+        let lines = if with_spans {
+            LineSet::search_with_spans(iter, regex)
+        } else {
+            LineSet::search(iter, regex)
+        };
+        while !lines.is_complete() {
+            std::hint::spin_loop();
+        }
+        Ok(lines)
+    }
+
+    #[test]
+    fn search_with_spans() -> Result<()> {
+        let lines = search_complete(b"foo bar foo\nbar\nbarfoo\n", "foo", true)?;
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines.get(0), Some(0));
+        assert_eq!(lines.spans(0), Some(vec![0..3, 8..11]));
+        assert_eq!(lines.get(1), Some(2));
+        assert_eq!(lines.spans(1), Some(std::iter::once(3..6).collect()));
+        assert_eq!(lines.spans(2), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn search_sends_matches() -> Result<()> {
+        let buf = SegBuffer::read_stream(Box::new(Cursor::new(b"foo\nbar\nfoo foo\n")), true)?;
+        buf.get_line(0);
+
+        let (sx, rx) = std::sync::mpsc::channel();
+        let lines = LineSet::search_with_sender(buf.segment_iter()?, Regex::new("foo")?, sx);
+
+        // The channel disconnects once the search is over
+        assert_eq!(rx.iter().collect::<Vec<_>>(), vec![0, 2]);
+        assert!(lines.is_complete());
+        assert_eq!(lines.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn search_inverted() -> Result<()> {
+        let buf = SegBuffer::read_stream(Box::new(Cursor::new(b"foo\nbar\nbarfoo\nbaz\n")), true)?;
+        buf.get_line(0);
+
+        let lines = LineSet::search_inverted(buf.segment_iter()?, Regex::new("foo")?, None);
+        while !lines.is_complete() {
+            std::hint::spin_loop();
         }
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines.get(0), Some(1));
+        assert_eq!(lines.get(1), Some(3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn count_matching_lines() -> Result<()> {
+        let data: &[u8] = b"foo foo\nbar\nbarfoo\nfoo\n";
+        let buf = SegBuffer::read_stream(Box::new(Cursor::new(data)), true)?;
+        buf.get_line(0);
+
+        let count = |within| -> Result<usize> {
+            let count = MatchCount::search(buf.segment_iter()?, Regex::new("foo")?, within);
+            while !count.is_complete() {
+                std::hint::spin_loop();
+            }
+            Ok(count.count())
+        };
+        assert_eq!(count(None)?, 3);
+        assert_eq!(count(Some(LineSet::from(vec![1, 2])))?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn search_without_spans() -> Result<()> {
+        let lines = search_complete(b"foo bar foo\nbar\nbarfoo\n", "foo", false)?;
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines.spans(0), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn search_stops_at_limit() -> Result<()> {
+        let lines = search_limited(
+            b"foo
+foo
+bar
+foo
+",
+            "foo",
+            2,
+        )?;
+        assert!(lines.is_capped());
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines.get(1), Some(1));
+
+        let lines = search_limited(
+            b"foo
+foo
+bar
+foo
+",
+            "foo",
+            3,
+        )?;
+        assert!(!lines.is_capped());
+        assert_eq!(lines.len(), 3);
+
+        Ok(())
     }
 }